@@ -0,0 +1,27 @@
+#version 130
+
+const vec3 c_Normals[6] = vec3[6](
+    vec3(-1.0,  0.0,  0.0),
+    vec3( 1.0,  0.0,  0.0),
+    vec3( 0.0, -1.0,  0.0),
+    vec3( 0.0,  1.0,  0.0),
+    vec3( 0.0,  0.0, -1.0),
+    vec3( 0.0,  0.0,  1.0)
+);
+
+in vec3 a_Position;
+in vec2 a_TexCoord;
+in uint a_Face;
+
+uniform mat4 u_MVPMatrix;
+uniform mat4 u_ModelMatrix;
+
+out vec2 v_TexCoord;
+out vec3 v_Normal;
+
+void main() {
+	gl_Position = u_MVPMatrix * vec4(a_Position, 1.0);
+
+	v_TexCoord = a_TexCoord;
+	v_Normal = normalize(mat3(u_ModelMatrix) * c_Normals[a_Face]);
+}
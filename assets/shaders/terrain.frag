@@ -2,6 +2,8 @@
 
 varying vec2 v_TexCoord;
 varying vec3 v_Normal;
+// Sky and block light, from 0 to 1.
+varying vec2 v_Light;
 
 uniform sampler2D u_ColorSampler;
 
@@ -10,6 +12,7 @@ uniform vec3 u_SkyColor, u_SunColor;
 
 void main() {
 	gl_FragColor = texture2D(u_ColorSampler, v_TexCoord);
+	vec3 albedo = gl_FragColor.rgb;
 
 	float diffuse = dot(v_Normal, -u_SunDirection);
 	diffuse = max(diffuse, 0.0);
@@ -23,4 +26,11 @@ void main() {
 
 		vec3(diffuse * (1.0 - SKY_AMBIENT) + SKY_AMBIENT)
 	);
+
+	// Every light level is a fifth darker than the one above it.
+	const float LIGHT_FALLOFF = 0.8;
+	const vec3 BLOCK_LIGHT_COLOR = vec3(1.0, 0.85, 0.6);
+
+	gl_FragColor.rgb *= pow(LIGHT_FALLOFF, (1.0 - v_Light.x) * 15.0);
+	gl_FragColor.rgb = max(gl_FragColor.rgb, albedo * BLOCK_LIGHT_COLOR * pow(LIGHT_FALLOFF, (1.0 - v_Light.y) * 15.0));
 }
\ No newline at end of file
@@ -10,12 +10,14 @@ const vec3 c_Normals[6] = vec3[6](
 );
 
 in uint a_Data;
+in uint a_Light;
 
 uniform mat4 u_MVPMatrix;
 uniform vec2 u_AtlasScalar;
 
 out vec2 v_TexCoord;
 out vec3 v_Normal;
+out vec2 v_Light;
 
 void main() {
     uint    x =  a_Data        & uint( 0x1F);
@@ -25,7 +27,7 @@ void main() {
     uint    u = (a_Data >> 19) & uint( 0x1F);
     uint    v = (a_Data >> 24) & uint( 0x1F);
 
-    uint face = (a_Data >> 29) & uint(  0x5);
+    uint face = (a_Data >> 29) & uint(  0x7);
 
 	gl_Position = u_MVPMatrix * vec4(float(x), float(y), float(z), 1.0);
 
@@ -33,4 +35,5 @@ void main() {
     v_TexCoord.y = 1.0 - v_TexCoord.y;
 
     v_Normal = c_Normals[face];
+    v_Light = vec2(float(a_Light >> 4), float(a_Light & uint(0xF))) / 15.0;
 }
\ No newline at end of file
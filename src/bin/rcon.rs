@@ -0,0 +1,105 @@
+// Command line client for the server's remote console.
+// Runs the command given as arguments, or reads commands from stdin until it is closed.
+//
+//     cargo run --bin rcon -- --password=secret save
+//     cargo run --bin rcon -- --address=127.0.0.1:25575 --password=secret
+
+use std::{io::{BufRead, BufReader, Write}, net::TcpStream};
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:25575";
+
+struct RemoteConsole {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl RemoteConsole {
+    fn connect(address: &str, password: &str) -> Result<Self, String> {
+        let stream = TcpStream::connect(address).map_err(|error| format!("Failed to connect to: {}. Error: {}.", address, error))?;
+        let writer = stream.try_clone().map_err(|error| error.to_string())?;
+
+        let mut console = Self { reader: BufReader::new(stream), writer };
+        console.write_line(password)?;
+
+        match console.read_line()?.as_deref() {
+            Some("OK") => Ok(console),
+            Some(error) => Err(error.strip_prefix("ERROR ").unwrap_or(error).to_string()),
+            None => Err(String::from("The server closed the connection")),
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        writeln!(self.writer, "{}", line).map_err(|error| error.to_string())
+    }
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(line.trim_end_matches(['\r', '\n']).to_string())),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    // Sends a command and returns its output, which ends with an empty line.
+    fn execute(&mut self, command: &str) -> Result<Vec<String>, String> {
+        self.write_line(command)?;
+
+        let mut output = vec![];
+        loop {
+            match self.read_line()? {
+                Some(line) if line.is_empty() => return Ok(output),
+                Some(line) => output.push(line),
+                None => return Err(String::from("The server closed the connection")),
+            }
+        }
+    }
+}
+
+fn main() {
+    let mut address = String::from(DEFAULT_ADDRESS);
+    let mut password = std::env::var("RCON_PASSWORD").ok();
+    let mut command: Vec<String> = vec![];
+
+    for arg in std::env::args().skip(1) {
+        if let Some(address_arg) = arg.strip_prefix("--address=") {
+            address = address_arg.to_string();
+        } else if let Some(password_arg) = arg.strip_prefix("--password=") {
+            password = Some(password_arg.to_string());
+        } else {
+            command.push(arg);
+        }
+    }
+
+    let Some(password) = password else {
+        eprintln!("No password given, use --password=<password> or set RCON_PASSWORD.");
+        std::process::exit(2);
+    };
+
+    let mut console = match RemoteConsole::connect(&address, &password) {
+        Ok(console) => console,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        },
+    };
+
+    let commands: Box<dyn Iterator<Item = String>> = if command.is_empty() {
+        Box::new(std::io::stdin().lock().lines().map_while(Result::ok))
+    } else {
+        Box::new(std::iter::once(command.join(" ")))
+    };
+
+    for command in commands {
+        if command.trim().is_empty() {
+            continue;
+        }
+
+        match console.execute(&command) {
+            Ok(output) => output.iter().for_each(|line| println!("{}", line)),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            },
+        }
+    }
+}
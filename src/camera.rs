@@ -1,5 +1,5 @@
-use cgmath::{Deg, Matrix4, Point3, Quaternion, Rotation, Rotation3, SquareMatrix, Vector3, VectorSpace};
-use crate::{engine::{timer::Timer, window::Window}, game::common::coords::Coord};
+use cgmath::{Deg, InnerSpace, Matrix4, Point3, Quaternion, Rotation, Rotation3, SquareMatrix, Vector3, VectorSpace};
+use crate::{engine::{timer::Timer, window::Window}, game::common::{coords::{Coord, CoordAxis}, physics::PlayerBody, world::block_view::BlockView}};
 
 pub struct Camera {
     pub position: Coord,
@@ -32,19 +32,25 @@ impl Camera {
         }
     }
 
-    pub fn fly(&mut self, window: &Window, timer: &Timer) {
+    fn look(&mut self, window: &Window) {
         const SENSITIVITY: f32 = 0.05;
-        const SPEED: f32 = 4.13;
-        const FAST_SPEED: f32 = 67.6;
-
-        const ACCELERATION: f32 = 2.0;
-        const FAST_ACCELERATION: f32 = 10.0;
 
         self.rotation.y -= window.get_mouse_dx() * SENSITIVITY;
         self.rotation.y -= (self.rotation.y / 360.0).floor() * 360.0;
 
         self.rotation.x -= window.get_mouse_dy() * SENSITIVITY;
         self.rotation.x = self.rotation.x.clamp(-90.0, 90.0);
+    }
+
+    // Moves freely through blocks wherever the camera looks.
+    pub fn fly(&mut self, window: &Window, timer: &Timer) {
+        const SPEED: f32 = 4.13;
+        const FAST_SPEED: f32 = 67.6;
+
+        const ACCELERATION: f32 = 2.0;
+        const FAST_ACCELERATION: f32 = 10.0;
+
+        self.look(window);
 
         let mut wish_velocity = Vector3::new(0.0, 0.0, 0.0);
 
@@ -77,6 +83,41 @@ impl Camera {
         );
         self.position += self.velocity.map(|v| v as f64) * timer.get_delta() as f64;
     }
+    // Walks the body over the ground, looking up or down does not change where it goes. The camera sits at its eyes.
+    pub fn walk(&mut self, window: &Window, timer: &Timer, body: &mut PlayerBody, world: &dyn BlockView) {
+        const SPEED: CoordAxis = 4.3;
+        const SPRINT_SPEED: CoordAxis = 5.6;
+
+        self.look(window);
+
+        let (sin, cos) = (self.rotation.y as CoordAxis).to_radians().sin_cos();
+        let forward = Vector3::new(-sin, 0.0, -cos);
+        let right = Vector3::new(cos, 0.0, -sin);
+
+        let mut wish_direction = Vector3::new(0.0, 0.0, 0.0);
+        if window.is_key_pressed(glfw::Key::W) {
+            wish_direction += forward;
+        }
+        if window.is_key_pressed(glfw::Key::S) {
+            wish_direction -= forward;
+        }
+        if window.is_key_pressed(glfw::Key::D) {
+            wish_direction += right;
+        }
+        if window.is_key_pressed(glfw::Key::A) {
+            wish_direction -= right;
+        }
+        if wish_direction.magnitude2() > 0.0 {
+            wish_direction = wish_direction.normalize();
+        }
+
+        let speed = if window.is_key_pressed(glfw::Key::LeftControl) { SPRINT_SPEED } else { SPEED };
+        body.update(world, wish_direction * speed, window.is_key_pressed(glfw::Key::Space), timer.get_delta() as CoordAxis);
+
+        // Flying starts from standing still again.
+        self.velocity = Vector3::new(0.0, 0.0, 0.0);
+        self.position = body.get_eye_position();
+    }
     pub fn update(&mut self, fov: f32, aspect: f32, near: f32, far: f32) {
         let quaternion_pitch = Quaternion::from_angle_x(Deg(self.rotation.x));
         let quaternion_yaw = Quaternion::from_angle_y(Deg(self.rotation.y));
@@ -106,12 +147,12 @@ impl Camera {
     pub fn get_front(&self) -> &Vector3<f32> {
         &self.front
     }
-    // pub fn get_up(&self) -> &Vector3<f32> {
-    //     &self.up
-    // }
-    // pub fn get_right(&self) -> &Vector3<f32> {
-    //     &self.right
-    // }
+    pub fn get_up(&self) -> &Vector3<f32> {
+        &self.up
+    }
+    pub fn get_right(&self) -> &Vector3<f32> {
+        &self.right
+    }
 
     // pub fn get_project_matrix(&self) -> &Matrix4<f32> {
     // 	return &self.project_matrix;
@@ -11,6 +11,8 @@ pub struct Window {
     events: glfw::GlfwReceiver<(f64, glfw::WindowEvent)>,
     keys: [u64; glfw::ffi::KEY_LAST as usize + 1],
     mouse_buttons: [u64; glfw::ffi::MOUSE_BUTTON_LAST as usize + 1],
+    repeated_keys: Vec<glfw::Key>,
+    typed_text: String,
 
     current_frame: u64,
 
@@ -53,6 +55,9 @@ impl Window {
         self.glfw.poll_events();
         self.current_frame += 1;
 
+        self.repeated_keys.clear();
+        self.typed_text.clear();
+
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
                 glfw::WindowEvent::FramebufferSize(width, height) => {
@@ -70,9 +75,14 @@ impl Window {
                         glfw::Action::Release => {
                             self.keys[key as usize] = 0;
                         }
-                        _ => {}
+                        glfw::Action::Repeat => {
+                            self.repeated_keys.push(key);
+                        }
                     }
                 }
+                glfw::WindowEvent::Char(character) => {
+                    self.typed_text.push(character);
+                }
                 glfw::WindowEvent::MouseButton(button, action, _) => {
                     match action {
                         glfw::Action::Press => {
@@ -133,10 +143,17 @@ impl Window {
     pub fn is_key_just_pressed(&self, key: glfw::Key) -> bool {
         self.keys[key as usize] == self.current_frame
     }
+    // Like is_key_just_pressed, but also true for the repeats the OS sends while the key is held, for text editing.
+    pub fn is_key_typed(&self, key: glfw::Key) -> bool {
+        self.is_key_just_pressed(key) || self.repeated_keys.contains(&key)
+    }
+    pub fn get_typed_text(&self) -> &str {
+        &self.typed_text
+    }
 
-    // pub fn is_mouse_button_pressed(&self, button: glfw::MouseButton) -> bool {
-    //     self.mouse_buttons[button as usize] > 0
-    // }
+    pub fn is_mouse_button_pressed(&self, button: glfw::MouseButton) -> bool {
+        self.mouse_buttons[button as usize] > 0
+    }
     pub fn is_mouse_button_just_pressed(&self, button: glfw::MouseButton) -> bool {
         self.mouse_buttons[button as usize] == self.current_frame
     }
@@ -155,12 +172,12 @@ impl Window {
         self.mouse_dy
     }
 
-    // pub fn get_width(&self) -> u32 {
-    //     self.width
-    // }
-    // pub fn get_height(&self) -> u32 {
-    //     self.height
-    // }
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
 
     pub fn get_aspect(&self) -> f32 {
         self.aspect
@@ -170,9 +187,9 @@ impl Window {
         self.handle.get_cursor_mode() == glfw::CursorMode::Disabled
     }
 
-    // pub fn close(&mut self) {
-    //     self.handle.set_should_close(true);
-    // }
+    pub fn close(&mut self) {
+        self.handle.set_should_close(true);
+    }
 }
 
 pub struct WindowBuilder {
@@ -237,6 +254,7 @@ impl WindowBuilder {
         }
 
         handle.set_key_polling(true);
+        handle.set_char_polling(true);
         handle.set_mouse_button_polling(true);
         handle.set_framebuffer_size_polling(true);
 
@@ -252,6 +270,8 @@ impl WindowBuilder {
 
             keys: [0; glfw::ffi::KEY_LAST as usize + 1],
             mouse_buttons: [0; glfw::ffi::MOUSE_BUTTON_LAST as usize + 1],
+            repeated_keys: vec![],
+            typed_text: String::new(),
 
             current_frame: 0,
 
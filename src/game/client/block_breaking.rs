@@ -0,0 +1,65 @@
+use crate::game::{common::{coords::{BlockPos, Coord, CoordAxis}, protocol::packet::ClientPacket, world::block_types::BlockTypes}, server::game_server::GameServer};
+
+use super::{debug::LineDebug, network::ServerConnection, world::ClientWorld};
+
+// Mining in survival: the left button is held on a block for as long as it takes to break.
+// The server is told when mining starts, so it can check the block was mined long enough.
+pub struct BlockBreaking {
+    // The block being mined and for how many seconds.
+    target: Option<(BlockPos, f32)>,
+    cooldown: f32,
+}
+
+impl BlockBreaking {
+    // After a block breaks the next one starts a moment later, so holding the button does not clear blocks that break at once.
+    const COOLDOWN: f32 = 0.25;
+
+    pub fn create() -> Self {
+        Self { target: None, cooldown: 0.0 }
+    }
+
+    fn get_progress(mined: f32, break_ticks: u64) -> f32 {
+        if break_ticks == 0 { 1.0 } else { mined * GameServer::TICKS_PER_SECOND as f32 / break_ticks as f32 }
+    }
+
+    // Returns the block once it is mined. Looking away or letting go of the button starts over.
+    pub fn update(&mut self, connection: &mut ServerConnection, world: &ClientWorld, looked_at: Option<BlockPos>, is_mining: bool, delta: f32) -> Option<BlockPos> {
+        self.cooldown = (self.cooldown - delta).max(0.0);
+
+        let target = looked_at.filter(|_| is_mining && self.cooldown == 0.0);
+        let Some((position, break_ticks)) = target.and_then(|position| Some((position.clone(), BlockTypes::get_break_ticks(world.get_block(&position))?))) else {
+            self.target = None;
+            return None;
+        };
+
+        let mined = match &self.target {
+            Some((mining, mined)) if *mining == position => mined + delta,
+            _ => {
+                connection.send(ClientPacket::StartBreaking { position: position.clone() });
+                0.0
+            },
+        };
+        if Self::get_progress(mined, break_ticks) >= 1.0 {
+            self.target = None;
+            self.cooldown = Self::COOLDOWN;
+            return Some(position);
+        }
+
+        self.target = Some((position, mined));
+        None
+    }
+
+    // A box grows from the middle of the block as it is mined.
+    pub fn draw(&self, world: &ClientWorld, line_debug: &mut LineDebug) {
+        let Some((position, mined)) = &self.target else { return; };
+        let Some(break_ticks) = BlockTypes::get_break_ticks(world.get_block(position)) else { return; };
+
+        let size = Self::get_progress(*mined, break_ticks).min(1.0) as CoordAxis;
+        let offset = (1.0 - size) * 0.5;
+        line_debug.color_hex(0xffffffaa);
+        line_debug.cube(
+            &Coord::new(position.x as CoordAxis + offset, position.y as CoordAxis + offset, position.z as CoordAxis + offset),
+            &(size, size, size),
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use cgmath::{InnerSpace, Vector3};
+
+use crate::{camera::Camera, game::common::{coords::{BlockPos, CoordAxis, Direction}, item::{ItemId, ItemTypes}, world::block_entity::BlockEntity}};
+
+use super::{debug::LineDebug, text::LineFont, world::ClientWorld};
+
+// Shows what block entities hold: the text of signs on their four sides and the contents of the chest the player looks at.
+pub struct BlockEntityRenderer;
+
+impl BlockEntityRenderer {
+    // Signs further away than this are not drawn.
+    const MAX_DISTANCE: CoordAxis = 24.0;
+    // Sign text shrinks to fit the side of the block, but never grows beyond this.
+    const MAX_SIGN_SCALE: f32 = 0.03;
+    const SIGN_WIDTH: f32 = 0.9;
+    // Keeps the text in front of the face it is written on.
+    const FACE_OFFSET: f32 = 0.51;
+    const LABEL_SCALE: f32 = 0.02;
+    const GLYPH_HEIGHT: f32 = 6.0;
+
+    // Items in slot order with their counts added up, like "12 dirt, 3 sand".
+    fn get_contents(slots: &[Option<(ItemId, u8)>]) -> String {
+        let mut counts: BTreeMap<ItemId, u32> = BTreeMap::new();
+        for (item, count) in slots.iter().flatten() {
+            *counts.entry(*item).or_default() += *count as u32;
+        }
+
+        if counts.is_empty() {
+            return String::from("empty");
+        }
+        counts.iter().map(|(item, count)| format!("{} {}", count, ItemTypes::get_name(*item))).collect::<Vec<String>>().join(", ")
+    }
+
+    pub fn draw(world: &ClientWorld, camera: &Camera, looked_at: Option<&BlockPos>, line_debug: &mut LineDebug) {
+        for (position, entity) in world.get_all_block_entities() {
+            let center = position.get_center();
+            let difference = Vector3::new(
+                center.get_world_x() - camera.position.get_world_x(),
+                center.get_world_y() - camera.position.get_world_y(),
+                center.get_world_z() - camera.position.get_world_z(),
+            );
+            if difference.magnitude() > Self::MAX_DISTANCE {
+                continue;
+            }
+
+            if let BlockEntity::Sign { text } = entity {
+                Self::draw_sign(&position, text, line_debug);
+            }
+        }
+
+        if let Some(position) = looked_at {
+            if let Some(BlockEntity::Chest { inventory }) = world.get_block_entity(position) {
+                Self::draw_label(position, &Self::get_contents(inventory.get_slots()), camera, line_debug);
+            }
+        }
+    }
+
+    fn draw_sign(position: &BlockPos, text: &str, line_debug: &mut LineDebug) {
+        let width = LineFont::get_width(text);
+        if width == 0.0 {
+            return;
+        }
+        let scale = (Self::SIGN_WIDTH / width).min(Self::MAX_SIGN_SCALE);
+
+        line_debug.color_hex(0x000000ff);
+        for direction in Direction::HORIZONTAL {
+            let (dx, _, dz) = direction.get_offset();
+            let normal = Vector3::new(dx as f32, 0.0, dz as f32);
+            // Right as seen by someone in front of the face, looking at it.
+            let right = Vector3::unit_y().cross(normal);
+
+            let mut origin = position.get_center();
+            origin += (normal * Self::FACE_OFFSET - right * (width * scale * 0.5) - Vector3::unit_y() * (Self::GLYPH_HEIGHT * scale * 0.5)).map(|v| v as CoordAxis);
+            LineFont::draw(line_debug, text, &origin, &right, &Vector3::unit_y(), scale);
+        }
+    }
+
+    // Text above the block that always faces the camera, like a name tag.
+    fn draw_label(position: &BlockPos, text: &str, camera: &Camera, line_debug: &mut LineDebug) {
+        let width = LineFont::get_width(text) * Self::LABEL_SCALE;
+
+        let mut origin = position.get_center();
+        origin += (Vector3::unit_y() * 0.75 - camera.get_right() * (width * 0.5)).map(|v| v as CoordAxis);
+
+        line_debug.color_hex(0xffffffff);
+        LineFont::draw(line_debug, text, &origin, camera.get_right(), camera.get_up(), Self::LABEL_SCALE);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+
+use cgmath::Vector3;
+
+use crate::{engine::window::Window, game::common::{coords::Coord, protocol::packet::ClientPacket}};
+
+use super::{debug::LineDebug, text::LineFont};
+
+struct ChatMessage {
+    text: String,
+    received_at: f32,
+}
+
+pub struct Chat {
+    messages: VecDeque<ChatMessage>,
+    input: Option<String>,
+
+    // Completions from the server replace the last word of the input, Tab cycles through them.
+    completions: Vec<String>,
+    completion_index: usize,
+    completion_base: String,
+}
+
+impl Chat {
+    const MAX_MESSAGES: usize = 100;
+    const VISIBLE_MESSAGES: usize = 10;
+    const MESSAGE_DURATION: f32 = 10.0;
+    const MAX_INPUT_LENGTH: usize = 256;
+
+    const SCALE: f32 = 2.0;
+    const LINE_HEIGHT: f32 = 10.0 * Self::SCALE;
+    const MARGIN: f32 = 8.0;
+
+    pub fn new() -> Self {
+        Self {
+            messages: VecDeque::new(),
+            input: None,
+
+            completions: vec![],
+            completion_index: 0,
+            completion_base: String::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn add_message(&mut self, text: String, time: f32) {
+        self.messages.push_back(ChatMessage { text, received_at: time });
+        if self.messages.len() > Self::MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+    }
+
+    pub fn set_completions(&mut self, completions: Vec<String>) {
+        let Some(input) = &mut self.input else { return; };
+        if completions.is_empty() {
+            return;
+        }
+
+        let base_length = input.rfind(char::is_whitespace).map(|index| index + 1).unwrap_or(if input.starts_with('/') { 1 } else { 0 });
+        self.completion_base = input[..base_length].to_string();
+        self.completions = completions;
+        self.completion_index = 0;
+
+        *input = format!("{}{}", self.completion_base, self.completions[0]);
+    }
+    fn clear_completions(&mut self) {
+        self.completions.clear();
+    }
+
+    // Handles typing while the chat is open, or the keys that open it. Returns a packet to send when needed.
+    pub fn update(&mut self, window: &Window) -> Option<ClientPacket> {
+        let Some(input) = &mut self.input else {
+            if window.is_key_just_pressed(glfw::Key::T) {
+                self.input = Some(String::new());
+            } else if window.is_key_just_pressed(glfw::Key::Slash) {
+                self.input = Some(String::from("/"));
+            }
+            self.clear_completions();
+
+            return None;
+        };
+
+        if window.is_key_just_pressed(glfw::Key::Escape) {
+            self.input = None;
+            return None;
+        }
+        if window.is_key_just_pressed(glfw::Key::Enter) || window.is_key_just_pressed(glfw::Key::KpEnter) {
+            let message = self.input.take().unwrap_or_default();
+            return if message.trim().is_empty() { None } else { Some(ClientPacket::ChatMessage { message }) };
+        }
+        if window.is_key_typed(glfw::Key::Tab) {
+            if self.completions.len() > 1 {
+                self.completion_index = (self.completion_index + 1) % self.completions.len();
+                *input = format!("{}{}", self.completion_base, self.completions[self.completion_index]);
+                return None;
+            }
+            if input.starts_with('/') {
+                return Some(ClientPacket::TabComplete { text: input.clone() });
+            }
+            return None;
+        }
+
+        let mut edited = false;
+        if window.is_key_typed(glfw::Key::Backspace) {
+            edited |= input.pop().is_some();
+        }
+        for character in window.get_typed_text().chars() {
+            if input.chars().count() < Self::MAX_INPUT_LENGTH {
+                input.push(character);
+                edited = true;
+            }
+        }
+        if edited {
+            self.clear_completions();
+        }
+
+        None
+    }
+
+    // Draws the chat in screen space, with the origin in the bottom left corner and one unit per pixel.
+    pub fn draw(&self, line_debug: &mut LineDebug, time: f32) {
+        let right = Vector3::unit_x();
+        let up = Vector3::unit_y();
+
+        let mut y = Self::MARGIN;
+        if let Some(input) = &self.input {
+            line_debug.color_hex(0xffffffff);
+            LineFont::draw(line_debug, &format!("> {}_", input), &Coord::new(Self::MARGIN as f64, y as f64, 0.0), &right, &up, Self::SCALE);
+        }
+        y += Self::LINE_HEIGHT * 1.5;
+
+        for message in self.messages.iter().rev().take(Self::VISIBLE_MESSAGES) {
+            let age = time - message.received_at;
+            if !self.is_open() && age > Self::MESSAGE_DURATION {
+                break;
+            }
+
+            let alpha = if self.is_open() { 1.0 } else { (Self::MESSAGE_DURATION - age).clamp(0.0, 1.0) };
+            line_debug.color_hex(0xffffff00 | (alpha * 255.0) as u32);
+            LineFont::draw(line_debug, &message.text, &Coord::new(Self::MARGIN as f64, y as f64, 0.0), &right, &up, Self::SCALE);
+
+            y += Self::LINE_HEIGHT;
+        }
+    }
+}
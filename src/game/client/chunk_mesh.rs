@@ -1,3 +1,5 @@
+use std::{ffi::c_void, mem::offset_of};
+
 use cgmath::{Matrix4, Vector3};
 use gl::types::{GLsizei, GLsizeiptr, GLuint};
 
@@ -26,6 +28,8 @@ impl<'a> NextChunks<'a> {
 #[repr(C)]
 struct Vertex {
     pub data: u32,
+    // Sky light in the high and block light in the low four bits, like chunks store it.
+    pub light: u32,
 }
 
 #[repr(u8)]
@@ -36,7 +40,7 @@ enum Face {
 }
 
 impl Vertex {
-    pub fn create(position: &LocalBlockPos, uv: (usize, usize), face: Face) -> Self {
+    pub fn create(position: &LocalBlockPos, uv: (usize, usize), face: Face, light: u8) -> Self {
         Self {
             data: 
                  (position.x &   0x1F) as u32        |
@@ -44,7 +48,8 @@ impl Vertex {
                 ((position.z &   0x1F) as u32) << 14 |
                 ((      uv.0 &   0x1F) as u32) << 19 |
                 ((      uv.1 &   0x1F) as u32) << 24 |
-                 (face as u32 &   0x5)         << 29
+                 (face as u32 &   0x7)         << 29,
+            light: light as u32,
         }
     }
 }
@@ -53,6 +58,31 @@ pub struct ChunkData {
     vertices: Vec<Vertex>,
 }
 
+impl<'a> NextChunks<'a> {
+    // The light a face looking into the given position gets. Faces on the border of unloaded chunks and at the top
+    // of the world see the open sky, the bottom of the world stays dark.
+    fn get_light(&self, chunk: &Chunk, x: isize, y: isize, z: isize) -> u8 {
+        const SKY: u8 = 0xF0;
+        if y < 0 {
+            return 0;
+        }
+        if y >= Chunk::HEIGHT as isize {
+            return SKY;
+        }
+
+        let (width, length) = (Chunk::WIDTH as isize, Chunk::LENGTH as isize);
+        let (chunk, x, z) = match (x, z) {
+            (x, _) if x < 0 => (self.left, width - 1, z),
+            (x, _) if x >= width => (self.right, 0, z),
+            (_, z) if z < 0 => (self.back, x, length - 1),
+            (_, z) if z >= length => (self.front, x, 0),
+            _ => (Some(chunk), x, z),
+        };
+
+        chunk.map_or(SKY, |chunk| chunk.get_packed_light(&LocalBlockPos::new(x as usize, y as usize, z as usize)))
+    }
+}
+
 impl ChunkData {
     pub fn create() -> Self {
         Self {
@@ -83,16 +113,17 @@ impl ChunkData {
                     } else {
                         chunk.get_block(&LocalBlockPos::new(x - 1, y, z)) == 0
                     } {
+                        let light = next_chunks.get_light(chunk, x as isize - 1, y as isize, z as isize);
                         let (u0, v0) = TerrainAtlas::get_uv(block.left_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Left));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u1, v0), Face::Left));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Left));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Left, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u1, v0), Face::Left, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Left, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u1, v1), Face::Left));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Left));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u1, v0), Face::Left));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u1, v1), Face::Left, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Left, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u1, v0), Face::Left, light));
                     }
                     
                     if if x >= Chunk::WIDTH - 1 {
@@ -100,42 +131,45 @@ impl ChunkData {
                     } else {
                         chunk.get_block(&LocalBlockPos::new(x + 1, y, z)) == 0
                     } {
+                        let light = next_chunks.get_light(chunk, x as isize + 1, y as isize, z as isize);
                         let (u0, v0) = TerrainAtlas::get_uv(block.right_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u0, v0), Face::Right));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Right));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u0, v1), Face::Right));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u0, v0), Face::Right, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Right, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u0, v1), Face::Right, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Right));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u0, v1), Face::Right));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Right));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Right, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u0, v1), Face::Right, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Right, light));
                     }
                     
                     if y == 0 || chunk.get_block(&LocalBlockPos::new(x, y - 1, z)) == 0 {
+                        let light = next_chunks.get_light(chunk, x as isize, y as isize - 1, z as isize);
                         let (u0, v0) = TerrainAtlas::get_uv(block.bottom_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Bottom));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Bottom));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v1), Face::Bottom));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Bottom, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Bottom, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v1), Face::Bottom, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v1), Face::Bottom));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v1), Face::Bottom));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Bottom));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v1), Face::Bottom, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v1), Face::Bottom, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Bottom, light));
                     }
                     
                     if y >= Chunk::HEIGHT - 1 || chunk.get_block(&LocalBlockPos::new(x, y + 1, z)) == 0 {
+                        let light = next_chunks.get_light(chunk, x as isize, y as isize + 1, z as isize);
                         let (u0, v0) = TerrainAtlas::get_uv(block.top_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v0), Face::Top));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v0), Face::Top));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Top));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v0), Face::Top, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v0), Face::Top, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Top, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Top));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Top));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v0), Face::Top));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Top, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Top, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v0), Face::Top, light));
                     }
                     
                     if if z == 0 {
@@ -143,16 +177,17 @@ impl ChunkData {
                     } else {
                         chunk.get_block(&LocalBlockPos::new(x, y, z - 1)) == 0
                     } {
+                        let light = next_chunks.get_light(chunk, x as isize, y as isize, z as isize - 1);
                         let (u0, v0) = TerrainAtlas::get_uv(block.back_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Back));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Back));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Back));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z), (u1, v0), Face::Back, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Back, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Back, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Back));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Back));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Back));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z), (u0, v1), Face::Back, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z), (u1, v1), Face::Back, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z), (u0, v0), Face::Back, light));
                     }
                     
                     if if z >= Chunk::LENGTH - 1 {
@@ -160,16 +195,17 @@ impl ChunkData {
                     } else {
                         chunk.get_block(&LocalBlockPos::new(x, y, z + 1)) == 0
                     } {
+                        let light = next_chunks.get_light(chunk, x as isize, y as isize, z as isize + 1);
                         let (u0, v0) = TerrainAtlas::get_uv(block.front_texture());
                         let (u1, v1) = (u0 + 1, v0 + 1);
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v0), Face::Front));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v0), Face::Front));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v1), Face::Front));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y, z + 1), (u0, v0), Face::Front, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v0), Face::Front, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v1), Face::Front, light));
                     
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v1), Face::Front));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v1), Face::Front));
-                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v0), Face::Front));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y + 1, z + 1), (u1, v1), Face::Front, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x, y + 1, z + 1), (u0, v1), Face::Front, light));
+                        self.put_vertex(Vertex::create(&LocalBlockPos::new(x + 1, y, z + 1), (u1, v0), Face::Front, light));
                     }
                 }
             }
@@ -197,6 +233,9 @@ impl ChunkMesh {
             gl::EnableVertexAttribArray(0);
             gl::VertexAttribIPointer(0, 1, gl::UNSIGNED_INT, size_of::<Vertex>() as GLsizei, std::ptr::null());
 
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribIPointer(1, 1, gl::UNSIGNED_INT, size_of::<Vertex>() as GLsizei, offset_of!(Vertex, light) as *const c_void);
+
             Self {
                 vao,
                 vbo,
@@ -81,12 +81,12 @@ impl LineDebug {
         self.vertices.push(Vertex { position: [to.0 as f32, to.1 as f32, to.2 as f32], color: self.picked_color });
     }
     
-    // pub fn line(&mut self, from: &Coord, to: &Coord) {
-    //     self.basic_line(
-    //         &(from.get_world_x(), from.get_world_y(), from.get_world_z()),
-    //         &(to.get_world_x(), to.get_world_y(), to.get_world_z()),
-    //     );
-    // }
+    pub fn line(&mut self, from: &Coord, to: &Coord) {
+        self.basic_line(
+            &(from.get_world_x(), from.get_world_y(), from.get_world_z()),
+            &(to.get_world_x(), to.get_world_y(), to.get_world_z()),
+        );
+    }
     pub fn cube(&mut self, position: &Coord, size: &(CoordAxis, CoordAxis, CoordAxis)) {
         let x0 = position.get_world_x();
         let y0 = position.get_world_y();
@@ -0,0 +1,91 @@
+use std::{ffi::c_void, mem::offset_of};
+
+use cgmath::Vector3;
+use gl::types::{GLsizei, GLsizeiptr, GLuint};
+
+#[repr(C)]
+struct Vertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+    face: u32,
+}
+
+// Texture rectangle of a box face as (u0, v0, u1, v1), where v0 is the top edge of the face.
+pub type FaceUv = (f32, f32, f32, f32);
+
+pub struct EntityMesh {
+    vao: GLuint,
+    vbo: GLuint,
+    num_vertices: GLsizei,
+}
+
+impl EntityMesh {
+    // Faces go in the same order as chunk mesh faces: left, right, bottom, top, back, front.
+    pub fn create_box(min: Vector3<f32>, max: Vector3<f32>, uvs: &[FaceUv; 6]) -> Self {
+        let (x0, y0, z0) = (min.x, min.y, min.z);
+        let (x1, y1, z1) = (max.x, max.y, max.z);
+
+        // Corners of every face as seen from outside: bottom left, bottom right, top left, top right.
+        let faces = [
+            [[x0, y0, z0], [x0, y0, z1], [x0, y1, z0], [x0, y1, z1]],
+            [[x1, y0, z1], [x1, y0, z0], [x1, y1, z1], [x1, y1, z0]],
+            [[x0, y0, z0], [x1, y0, z0], [x0, y0, z1], [x1, y0, z1]],
+            [[x0, y1, z1], [x1, y1, z1], [x0, y1, z0], [x1, y1, z0]],
+            [[x1, y0, z0], [x0, y0, z0], [x1, y1, z0], [x0, y1, z0]],
+            [[x0, y0, z1], [x1, y0, z1], [x0, y1, z1], [x1, y1, z1]],
+        ];
+
+        let mut vertices: Vec<Vertex> = vec![];
+        for (face, (corners, uv)) in faces.iter().zip(uvs.iter()).enumerate() {
+            let (u0, v0, u1, v1) = *uv;
+            let corner_uvs = [[u0, v1], [u1, v1], [u0, v0], [u1, v0]];
+
+            for index in [0, 1, 2, 3, 2, 1] {
+                vertices.push(Vertex { position: corners[index], uv: corner_uvs[index], face: face as u32 });
+            }
+        }
+
+        unsafe {
+            let mut vao: GLuint = 0;
+            gl::CreateVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+
+            let mut vbo: GLuint = 0;
+            gl::GenBuffers(1, &mut vbo);
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (vertices.len() * size_of::<Vertex>()) as GLsizeiptr,
+                vertices.as_ptr() as *const c_void,
+                gl::STATIC_DRAW
+            );
+
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, size_of::<Vertex>() as GLsizei, std::ptr::null());
+
+            gl::EnableVertexAttribArray(1);
+            gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, size_of::<Vertex>() as GLsizei, offset_of!(Vertex, uv) as *const c_void);
+
+            gl::EnableVertexAttribArray(2);
+            gl::VertexAttribIPointer(2, 1, gl::UNSIGNED_INT, size_of::<Vertex>() as GLsizei, offset_of!(Vertex, face) as *const c_void);
+
+            Self { vao, vbo, num_vertices: vertices.len() as GLsizei }
+        }
+    }
+
+    pub fn render(&self) {
+        unsafe {
+            gl::BindVertexArray(self.vao);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.num_vertices);
+        }
+    }
+}
+
+impl Drop for EntityMesh {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use cgmath::{Deg, Matrix4, Vector3};
+
+use crate::{camera::Camera, game::{common::{coords::{ChunkAxis, Coord}, entity::{EntityId, EntityKind}, item::{ItemId, ItemTypes}, world::block_types::BlockTypes}, server::world::chunk::Chunk}};
+
+use super::{entity_mesh::{EntityMesh, FaceUv}, remote_entity::RemoteEntity, resources::{BlockRegistry, EntityShader, TerrainAtlas}};
+
+// Draws falling blocks and items as boxes with the block's textures, the terrain atlas has to be bound.
+// Items that are not blocks are flat boxes with their own tile.
+pub struct EntityRenderer {
+    // One mesh per block id, a block sized box standing on its bottom center.
+    block_meshes: Vec<EntityMesh>,
+    // One mesh per item that is not a block, from the first one on.
+    item_meshes: Vec<EntityMesh>,
+}
+
+impl EntityRenderer {
+    const ITEM_SCALE: f32 = 0.25;
+    // Degrees per second.
+    const ITEM_SPIN_SPEED: f32 = 90.0;
+    // Tiles of the items that are not blocks, in the order of their ids.
+    const ITEM_TILES: [usize; 3] = [43, 44, 45];
+    const ITEM_THICKNESS: f32 = 0.1;
+
+    fn get_tile_uv(texture: usize) -> FaceUv {
+        let (x, y) = TerrainAtlas::get_uv(texture);
+        let (width, height) = TerrainAtlas::ITEM_UV;
+
+        // The atlas is upside down in texture space, the same way chunk meshes sample it.
+        (x as f32 * width, 1.0 - (y + 1) as f32 * height, (x + 1) as f32 * width, 1.0 - y as f32 * height)
+    }
+
+    pub fn new(block_registry: &BlockRegistry) -> Self {
+        let block_meshes = (0..BlockTypes::NAMES.len()).map(|id| {
+            let block = block_registry.get(id);
+            let uvs = [
+                Self::get_tile_uv(block.left_texture()),
+                Self::get_tile_uv(block.right_texture()),
+                Self::get_tile_uv(block.bottom_texture()),
+                Self::get_tile_uv(block.top_texture()),
+                Self::get_tile_uv(block.back_texture()),
+                Self::get_tile_uv(block.front_texture()),
+            ];
+
+            EntityMesh::create_box(Vector3::new(-0.5, 0.0, -0.5), Vector3::new(0.5, 1.0, 0.5), &uvs)
+        }).collect();
+
+        let item_meshes = Self::ITEM_TILES.iter().map(|tile| {
+            let uvs = [Self::get_tile_uv(*tile); 6];
+            let half_thickness = Self::ITEM_THICKNESS * 0.5;
+            EntityMesh::create_box(Vector3::new(-0.5, 0.0, -half_thickness), Vector3::new(0.5, 1.0, half_thickness), &uvs)
+        }).collect();
+
+        Self { block_meshes, item_meshes }
+    }
+
+    fn get_item_mesh(&self, item: ItemId) -> Option<&EntityMesh> {
+        match ItemTypes::get_block(item) {
+            Some(block) => self.block_meshes.get(block as usize),
+            None => self.item_meshes.get(item.checked_sub(ItemTypes::FIRST)? as usize),
+        }
+    }
+
+    fn get_render_offset(position: &Coord, camera: &Camera) -> Vector3<f32> {
+        Vector3::new(
+            ((position.get_chunk_x() - camera.position.get_chunk_x()) * Chunk::WIDTH as ChunkAxis) as f32 + position.get_local_x(),
+            position.get_world_y() as f32,
+            ((position.get_chunk_z() - camera.position.get_chunk_z()) * Chunk::LENGTH as ChunkAxis) as f32 + position.get_local_z(),
+        )
+    }
+
+    pub fn render(&self, entities: &HashMap<EntityId, RemoteEntity>, camera: &Camera, shader: &EntityShader, time: f32) {
+        for entity in entities.values() {
+            let translation = Matrix4::from_translation(Self::get_render_offset(&entity.get_position(), camera));
+            let (mesh, model_matrix) = match entity.get_kind() {
+                EntityKind::FallingBlock { block } => (self.block_meshes.get(block as usize), translation),
+                EntityKind::Item { item } => {
+                    let spin = Matrix4::from_angle_y(Deg(time * Self::ITEM_SPIN_SPEED));
+                    (self.get_item_mesh(item), translation * spin * Matrix4::from_scale(Self::ITEM_SCALE))
+                },
+            };
+            let Some(mesh) = mesh else { continue; };
+
+            shader.set_model_matrix(&model_matrix);
+            shader.set_mvp_matrix(&(camera.get_project_view_matrix() * model_matrix));
+            mesh.render();
+        }
+    }
+}
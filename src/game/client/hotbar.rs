@@ -0,0 +1,84 @@
+use cgmath::Vector3;
+
+use crate::{engine::window::Window, game::common::{coords::Coord, inventory::Inventory, item::{ItemId, ItemTypes}, player::{INVENTORY_SLOTS, MAX_HEALTH}, world::{block_types::BlockTypes, signal::SignalBlock}}};
+
+use super::{debug::LineDebug, text::LineFont};
+
+// The blocks a player can place and the items they can use, picked with the number keys, zero and minus.
+// In creative every key has a fixed item to use as often as the player likes, in survival the keys pick slots of the inventory.
+pub struct Hotbar {
+    selected: usize,
+}
+
+impl Hotbar {
+    const SLOTS: [ItemId; 11] = [
+        BlockTypes::Cobblestone as ItemId,
+        BlockTypes::Dirt as ItemId,
+        BlockTypes::Lever as ItemId,
+        BlockTypes::Button as ItemId,
+        BlockTypes::Wire as ItemId,
+        BlockTypes::Lamp as ItemId,
+        BlockTypes::Door as ItemId,
+        BlockTypes::Repeater as ItemId,
+        BlockTypes::Inverter as ItemId,
+        ItemTypes::Hoe as ItemId,
+        ItemTypes::Seeds as ItemId,
+    ];
+    const KEYS: [glfw::Key; INVENTORY_SLOTS] = [
+        glfw::Key::Num1, glfw::Key::Num2, glfw::Key::Num3,
+        glfw::Key::Num4, glfw::Key::Num5, glfw::Key::Num6,
+        glfw::Key::Num7, glfw::Key::Num8, glfw::Key::Num9,
+        glfw::Key::Num0, glfw::Key::Minus,
+    ];
+    const KEY_NAMES: [&'static str; INVENTORY_SLOTS] = ["1", "2", "3", "4", "5", "6", "7", "8", "9", "0", "-"];
+
+    const SCALE: f32 = 2.0;
+    const MARGIN: f32 = 8.0;
+    const LINE_HEIGHT: f32 = 10.0 * Self::SCALE;
+
+    pub fn new() -> Self {
+        Self { selected: 0 }
+    }
+
+    pub fn update(&mut self, window: &Window) {
+        if let Some(index) = Self::KEYS.iter().position(|key| window.is_key_just_pressed(*key)) {
+            self.selected = index;
+        }
+    }
+    // The inventory is only given in survival, None if the selected slot is empty.
+    pub fn get_selected(&self, inventory: Option<&Inventory>) -> Option<ItemId> {
+        match inventory {
+            Some(inventory) => inventory.get(self.selected).map(|(item, _)| item),
+            None => Some(Self::SLOTS[self.selected]),
+        }
+    }
+
+    // Draws the selected slot in the bottom right corner, in the same screen space as the chat.
+    pub fn draw(&self, line_debug: &mut LineDebug, screen_width: f32, inventory: Option<&Inventory>) {
+        let name = match self.get_selected(inventory) {
+            Some(item) => match ItemTypes::get_block(item).and_then(SignalBlock::from_block) {
+                Some(SignalBlock::Repeater { .. }) => "repeater",
+                Some(SignalBlock::Inverter { .. }) => "inverter",
+                _ => ItemTypes::get_name(item),
+            },
+            None => "empty",
+        };
+        let text = match inventory.and_then(|inventory| inventory.get(self.selected)) {
+            Some((_, count)) => format!("[{}] {} {}", Self::KEY_NAMES[self.selected], count, name),
+            None => format!("[{}] {}", Self::KEY_NAMES[self.selected], name),
+        };
+
+        Self::draw_line(line_debug, screen_width, &text, 0);
+    }
+    // Survival players see their health above the selected slot.
+    pub fn draw_health(line_debug: &mut LineDebug, screen_width: f32, health: u8) {
+        Self::draw_line(line_debug, screen_width, &format!("health {}/{}", health, MAX_HEALTH), 1);
+    }
+    // Right aligned, lines counted up from the bottom.
+    fn draw_line(line_debug: &mut LineDebug, screen_width: f32, text: &str, line: usize) {
+        let x = screen_width - Self::MARGIN - LineFont::get_width(text) * Self::SCALE;
+        let y = Self::MARGIN + line as f32 * Self::LINE_HEIGHT;
+        line_debug.color_hex(0xffffffff);
+        LineFont::draw(line_debug, text, &Coord::new(x as f64, y as f64, 0.0), &Vector3::unit_x(), &Vector3::unit_y(), Self::SCALE);
+    }
+}
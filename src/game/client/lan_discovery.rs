@@ -0,0 +1,98 @@
+use std::{net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket}, time::{Duration, Instant}};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::game::common::protocol::{discovery::{LanAnnouncement, DISCOVERY_PORT}, PROTOCOL_VERSION};
+
+pub struct DiscoveredServer {
+    pub name: String,
+    pub address: SocketAddr,
+    pub player_count: u32,
+    pub protocol_version: u32,
+
+    // Announced from this machine, so it may be the server we are on under another address.
+    is_local: bool,
+    last_seen: Instant,
+}
+
+// Only addresses of this machine can be bound to.
+fn is_local_address(address: IpAddr) -> bool {
+    address.is_loopback() || address.is_unspecified() || UdpSocket::bind((address, 0)).is_ok()
+}
+
+impl DiscoveredServer {
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+    pub fn get_description(&self) -> String {
+        let compatibility = if self.is_compatible() { "" } else { ", incompatible version" };
+        format!("{} ({} players{}) at {}", self.name, self.player_count, compatibility, self.address)
+    }
+    // A server on this machine is heard from the loopback and from the network address, both are the same server.
+    pub fn is_at(&self, address: &SocketAddr) -> bool {
+        self.address == *address
+            || (self.address.port() == address.port() && self.is_local && is_local_address(address.ip()))
+    }
+}
+
+// Listens for server announcements. Several clients on one machine share the port, so it is bound with address reuse.
+pub struct LanDiscovery {
+    socket: UdpSocket,
+    servers: Vec<DiscoveredServer>,
+}
+
+impl LanDiscovery {
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    pub fn listen() -> std::io::Result<Self> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.set_reuse_address(true)?;
+        #[cfg(unix)]
+        socket.set_reuse_port(true)?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT).into())?;
+
+        Ok(Self { socket: socket.into(), servers: vec![] })
+    }
+
+    // Reads pending announcements and forgets servers that went quiet.
+    // Returns how many servers are new, they are the last ones in get_servers().
+    pub fn update(&mut self) -> usize {
+        let mut buffer = [0u8; 1024];
+        let mut new_servers = 0;
+
+        while let Ok((length, sender)) = self.socket.recv_from(&mut buffer) {
+            let Some(announcement) = LanAnnouncement::decode(&buffer[..length]) else { continue; };
+            let address = SocketAddr::new(sender.ip(), announcement.port);
+
+            match self.servers.iter_mut().find(|server| server.address == address) {
+                Some(server) => {
+                    server.name = announcement.name;
+                    server.player_count = announcement.player_count;
+                    server.protocol_version = announcement.protocol_version;
+                    server.last_seen = Instant::now();
+                },
+                None => {
+                    self.servers.push(DiscoveredServer {
+                        name: announcement.name,
+                        address,
+                        player_count: announcement.player_count,
+                        protocol_version: announcement.protocol_version,
+
+                        is_local: is_local_address(address.ip()),
+                        last_seen: Instant::now(),
+                    });
+                    new_servers += 1;
+                },
+            }
+        }
+
+        self.servers.retain(|server| server.last_seen.elapsed() < Self::TIMEOUT);
+        new_servers
+    }
+
+    pub fn get_servers(&self) -> &[DiscoveredServer] {
+        &self.servers
+    }
+}
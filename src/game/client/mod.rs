@@ -1,4 +1,19 @@
 pub mod chunk_mesh;
 pub mod world;
 pub mod resources;
-pub mod debug;
\ No newline at end of file
+pub mod debug;
+pub mod network;
+pub mod remote_player;
+pub mod entity_mesh;
+pub mod player_renderer;
+pub mod remote_entity;
+pub mod entity_renderer;
+pub mod text;
+pub mod chat;
+pub mod lan_discovery;
+pub mod prediction;
+pub mod sky;
+pub mod precipitation;
+pub mod hotbar;
+pub mod block_entity_renderer;
+pub mod block_breaking;
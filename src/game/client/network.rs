@@ -0,0 +1,58 @@
+use std::net::ToSocketAddrs;
+
+use crate::game::common::{protocol::{connection::{Connection, TcpConnection}, packet::{ClientPacket, ServerPacket}, stream::PacketStream, PROTOCOL_VERSION}, world::block_types::BlockTypes};
+
+pub struct ServerConnection {
+    stream: PacketStream,
+}
+
+impl ServerConnection {
+    // Connects and starts the handshake, the server answers with its own Handshake or a Disconnect.
+    pub fn connect<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        Ok(Self::create(Box::new(TcpConnection::connect(address)?)))
+    }
+    // Starts the handshake over a connection that is already established, like the one to an integrated server.
+    pub fn create(connection: Box<dyn Connection>) -> Self {
+        let mut connection = Self { stream: PacketStream::create(connection) };
+        connection.send(ClientPacket::Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            registry_hash: BlockTypes::get_registry_hash(),
+        });
+
+        connection
+    }
+
+    // Checks the server's half of the handshake and turns compression on, or returns why we can not play there.
+    pub fn accept_handshake(&mut self, protocol_version: u32, registry_hash: u64, compression_threshold: u32) -> Result<(), String> {
+        if protocol_version != PROTOCOL_VERSION {
+            return Err(format!("Server uses protocol version {}, but we use {}", protocol_version, PROTOCOL_VERSION));
+        }
+        if registry_hash != BlockTypes::get_registry_hash() {
+            return Err(String::from("Server has a different block registry"));
+        }
+
+        self.stream.enable_compression(compression_threshold as usize);
+        Ok(())
+    }
+
+    pub fn send(&mut self, packet: ClientPacket) {
+        self.stream.send(packet.encode());
+    }
+    pub fn receive(&mut self) -> Option<ServerPacket> {
+        while let Some(bytes) = self.stream.receive() {
+            match ServerPacket::decode(&bytes) {
+                Some(packet) => return Some(packet),
+                None => println!("Received a malformed packet from the server."),
+            }
+        }
+
+        None
+    }
+    pub fn flush(&mut self) {
+        self.stream.flush();
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.stream.is_open()
+    }
+}
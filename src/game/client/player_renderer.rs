@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use cgmath::{Deg, Matrix4, Vector3};
+
+use crate::{camera::Camera, engine::texture::Texture, game::{common::{coords::{ChunkAxis, Coord, CoordAxis}, physics::PlayerBody, player::PlayerId}, server::world::chunk::Chunk}};
+
+use super::{debug::LineDebug, entity_mesh::{EntityMesh, FaceUv}, remote_player::RemotePlayer, resources::EntityShader, text::LineFont};
+
+pub struct PlayerRenderer {
+    head: EntityMesh,
+    body: EntityMesh,
+    texture: Texture,
+}
+
+impl PlayerRenderer {
+    const NAME_TAG_SCALE: f32 = 0.05;
+
+    // The player texture is a 4x2 grid of tiles: front, side, top and back of the head, then the same for the body.
+    fn get_tile_uv(x: usize, y: usize) -> FaceUv {
+        (x as f32 / 4.0, y as f32 / 2.0, (x + 1) as f32 / 4.0, (y + 1) as f32 / 2.0)
+    }
+    fn get_box_uvs(row: usize) -> [FaceUv; 6] {
+        let front = Self::get_tile_uv(0, row);
+        let side = Self::get_tile_uv(1, row);
+        let top = Self::get_tile_uv(2, row);
+        let back = Self::get_tile_uv(3, row);
+
+        // Models look towards -Z, the same way the camera does.
+        [side, side, top, top, front, back]
+    }
+
+    pub fn new() -> Self {
+        Self {
+            head: EntityMesh::create_box(Vector3::new(-0.25, -0.25, -0.25), Vector3::new(0.25, 0.25, 0.25), &Self::get_box_uvs(0)),
+            body: EntityMesh::create_box(Vector3::new(-0.3, -PlayerBody::EYE_HEIGHT as f32, -0.15), Vector3::new(0.3, -0.25, 0.15), &Self::get_box_uvs(1)),
+            texture: Texture::load_from_file(
+                "./assets/textures/player.png",
+                gl::NEAREST,
+                gl::CLAMP_TO_EDGE,
+            ),
+        }
+    }
+
+    fn get_render_offset(position: &Coord, camera: &Camera) -> Vector3<f32> {
+        Vector3::new(
+            ((position.get_chunk_x() - camera.position.get_chunk_x()) * Chunk::WIDTH as ChunkAxis) as f32 + position.get_local_x(),
+            position.get_world_y() as f32,
+            ((position.get_chunk_z() - camera.position.get_chunk_z()) * Chunk::LENGTH as ChunkAxis) as f32 + position.get_local_z(),
+        )
+    }
+
+    pub fn render(&self, players: &HashMap<PlayerId, RemotePlayer>, camera: &Camera, shader: &EntityShader) {
+        self.texture.bind(EntityShader::COLOR_TEXTURE_SLOT);
+
+        for player in players.values() {
+            let yaw_matrix = Matrix4::from_angle_y(Deg(player.get_yaw()));
+            let body_matrix = Matrix4::from_translation(Self::get_render_offset(&player.get_position(), camera)) * yaw_matrix;
+            let head_matrix = body_matrix * Matrix4::from_angle_x(Deg(player.get_pitch()));
+
+            shader.set_model_matrix(&body_matrix);
+            shader.set_mvp_matrix(&(camera.get_project_view_matrix() * body_matrix));
+            self.body.render();
+
+            shader.set_model_matrix(&head_matrix);
+            shader.set_mvp_matrix(&(camera.get_project_view_matrix() * head_matrix));
+            self.head.render();
+        }
+
+        Texture::unbind();
+    }
+
+    pub fn draw_name_tags(&self, players: &HashMap<PlayerId, RemotePlayer>, camera: &Camera, line_debug: &mut LineDebug) {
+        line_debug.color_hex(0xffffffff);
+
+        for player in players.values() {
+            let width = LineFont::get_width(player.get_name()) * Self::NAME_TAG_SCALE;
+
+            let mut origin = player.get_position();
+            origin += (Vector3::unit_y() * 0.5 - camera.get_right() * (width * 0.5)).map(|v| v as CoordAxis);
+
+            LineFont::draw(line_debug, player.get_name(), &origin, camera.get_right(), camera.get_up(), Self::NAME_TAG_SCALE);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use rand::Rng;
+
+use crate::game::common::coords::{BlockAxis, Coord, CoordAxis};
+
+use super::{debug::LineDebug, world::ClientWorld};
+
+struct Particle {
+    x: CoordAxis,
+    y: CoordAxis,
+    z: CoordAxis,
+    // Snowflakes sway from side to side, each at its own point of the swing.
+    phase: f32,
+}
+
+// Rain and snow falling around the camera, drawn as lines. Particles stop at the highest block of their column,
+// so nothing falls under roofs and overhangs. Above the snow line it snows instead of raining.
+pub struct Precipitation {
+    particles: Vec<Particle>,
+}
+
+impl Precipitation {
+    const MAX_PARTICLES: usize = 1500;
+    const RADIUS: CoordAxis = 20.0;
+    // Particles spawn this high above the camera at most.
+    const SPAWN_HEIGHT: CoordAxis = 24.0;
+    const SNOW_LINE: CoordAxis = 120.0;
+    // Blocks per second.
+    const RAIN_SPEED: CoordAxis = 18.0;
+    const SNOW_SPEED: CoordAxis = 2.5;
+
+    pub fn new() -> Self {
+        Self { particles: vec![] }
+    }
+
+    // Strength is how hard it is raining, from 0 to 1.
+    pub fn update(&mut self, world: &ClientWorld, camera_position: &Coord, strength: f32, delta: f32) {
+        let (camera_x, camera_y, camera_z) = (camera_position.get_world_x(), camera_position.get_world_y(), camera_position.get_world_z());
+        let mut random = rand::rng();
+
+        let is_sheltered = |x: CoordAxis, y: CoordAxis, z: CoordAxis| {
+            world.get_height(x.floor() as BlockAxis, z.floor() as BlockAxis).is_none_or(|height| y < height as CoordAxis)
+        };
+        self.particles.retain_mut(|particle| {
+            let speed = if particle.y >= Self::SNOW_LINE { Self::SNOW_SPEED } else { Self::RAIN_SPEED };
+            particle.y -= speed * delta as CoordAxis;
+            particle.phase += delta;
+
+            let distance = (particle.x - camera_x).hypot(particle.z - camera_z);
+            distance <= Self::RADIUS && particle.y >= camera_y - Self::SPAWN_HEIGHT && !is_sheltered(particle.x, particle.y, particle.z)
+        });
+
+        let target = (Self::MAX_PARTICLES as f32 * strength) as usize;
+        // Spawning is spread over a few frames, so the particles don't fall in one sheet.
+        for _ in 0..target.saturating_sub(self.particles.len()).min(Self::MAX_PARTICLES / 20) {
+            let angle = random.random_range(0.0..std::f64::consts::TAU);
+            let distance = Self::RADIUS * random.random::<CoordAxis>().sqrt();
+            let (x, z) = (camera_x + angle.cos() * distance, camera_z + angle.sin() * distance);
+            let y = camera_y + random.random_range(0.0..Self::SPAWN_HEIGHT);
+
+            if !is_sheltered(x, y, z) {
+                self.particles.push(Particle { x, y, z, phase: random.random_range(0.0..std::f32::consts::TAU) });
+            }
+        }
+    }
+
+    pub fn draw(&self, line_debug: &mut LineDebug) {
+        for particle in &self.particles {
+            if particle.y >= Self::SNOW_LINE {
+                let sway = particle.phase.sin() as CoordAxis * 0.3;
+                let (x, y, z) = (particle.x + sway, particle.y, particle.z);
+
+                line_debug.color_hex(0xf4f8ffdd);
+                line_debug.line(&Coord::new(x - 0.06, y, z), &Coord::new(x + 0.06, y, z));
+                line_debug.line(&Coord::new(x, y - 0.06, z), &Coord::new(x, y + 0.06, z));
+                line_debug.line(&Coord::new(x, y, z - 0.06), &Coord::new(x, y, z + 0.06));
+            } else {
+                line_debug.color_hex(0x8ca6d899);
+                line_debug.line(&Coord::new(particle.x, particle.y, particle.z), &Coord::new(particle.x, particle.y + 0.8, particle.z));
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::game::{common::coords::{BlockAxis, BlockPos, ChunkPos}, server::world::chunk::Chunk};
+
+use super::world::ClientWorld;
+
+// An edit that is already shown, waiting for the server to answer it.
+struct PendingEdit {
+    sequence: u32,
+    position: BlockPos,
+    block: u8,
+    // The last block the server sent for the position while the edit was pending.
+    server_block: Option<u8>,
+}
+
+// Block edits are shown right away instead of after a round trip, and rolled back when the server rejects them.
+// Until an edit is answered, changes the server sends for its block are kept aside so they don't undo it.
+pub struct BlockPredictions {
+    pending: Vec<PendingEdit>,
+    next_sequence: u32,
+}
+
+impl BlockPredictions {
+    pub fn create() -> Self {
+        Self {
+            pending: vec![],
+            next_sequence: 0,
+        }
+    }
+
+    // Applies the edit locally and returns the sequence number to send it to the server with.
+    pub fn predict(&mut self, world: &mut ClientWorld, position: BlockPos, block: u8) -> u32 {
+        self.next_sequence += 1;
+        world.set_block(&position, block);
+        self.pending.push(PendingEdit { sequence: self.next_sequence, position, block, server_block: None });
+
+        self.next_sequence
+    }
+
+    // The server handles edits in order, so this answers every edit up to the sequence that was not rejected.
+    pub fn acknowledge(&mut self, world: &mut ClientWorld, sequence: u32) {
+        let (answered, pending): (Vec<PendingEdit>, Vec<PendingEdit>) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|edit| edit.sequence <= sequence);
+        self.pending = pending;
+
+        for edit in answered {
+            self.settle(world, &edit.position, edit.server_block);
+        }
+    }
+    pub fn reject(&mut self, world: &mut ClientWorld, sequence: u32, position: &BlockPos, block: u8) {
+        self.pending.retain(|edit| edit.sequence != sequence);
+        self.settle(world, position, Some(block));
+    }
+
+    // Once nothing is pending at a position, it shows the last block the server sent for it.
+    fn settle(&mut self, world: &mut ClientWorld, position: &BlockPos, server_block: Option<u8>) {
+        let Some(block) = server_block else { return; };
+
+        let mut is_pending = false;
+        for edit in self.pending.iter_mut().filter(|edit| edit.position == *position) {
+            edit.server_block = Some(block);
+            is_pending = true;
+        }
+        if !is_pending {
+            world.set_block(position, block);
+        }
+    }
+
+    // Takes the changes to blocks with a pending edit out of a batch from the server.
+    pub fn filter_changes(&mut self, chunk_pos: &ChunkPos, section: usize, changes: &mut Vec<(u16, u8)>) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        changes.retain(|(index, block)| {
+            let local = Chunk::from_section_index(section, *index);
+            let position = BlockPos::new(
+                chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis + local.x as BlockAxis,
+                local.y as BlockAxis,
+                chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis + local.z as BlockAxis,
+            );
+
+            let mut is_pending = false;
+            for edit in self.pending.iter_mut().filter(|edit| edit.position == position) {
+                edit.server_block = Some(*block);
+                is_pending = true;
+            }
+            !is_pending
+        });
+    }
+
+    // Blocks the server sent again as a whole chunk or section replace the predictions, so they are put back on top.
+    pub fn reapply(&mut self, world: &mut ClientWorld, chunk_pos: &ChunkPos, section: Option<usize>) {
+        let is_replaced = |position: &BlockPos| {
+            position.to_chunk_pos() == *chunk_pos &&
+                section.is_none_or(|section| position.y.div_euclid(Chunk::SECTION_HEIGHT as BlockAxis) == section as BlockAxis)
+        };
+
+        for edit in self.pending.iter_mut().filter(|edit| is_replaced(&edit.position)) {
+            edit.server_block = Some(world.get_block(&edit.position));
+        }
+        for edit in self.pending.iter().filter(|edit| is_replaced(&edit.position)) {
+            world.set_block(&edit.position, edit.block);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::game::{common::{coords::{Coord, CoordAxis}, entity::EntityKind}, server::game_server::GameServer};
+
+// Like remote players, entities are drawn between the last two positions the server sent.
+pub struct RemoteEntity {
+    kind: EntityKind,
+
+    previous_position: Coord,
+    target_position: Coord,
+    progress: f32,
+}
+
+impl RemoteEntity {
+    pub fn create(kind: EntityKind, position: Coord) -> Self {
+        Self {
+            kind,
+
+            previous_position: position.clone(),
+            target_position: position,
+            progress: 1.0,
+        }
+    }
+
+    pub fn get_kind(&self) -> EntityKind {
+        self.kind
+    }
+
+    pub fn set_target(&mut self, position: Coord) {
+        self.previous_position = self.get_position();
+        self.target_position = position;
+        self.progress = 0.0;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.progress = (self.progress + delta * GameServer::TICKS_PER_SECOND as f32).min(1.0);
+    }
+
+    pub fn get_position(&self) -> Coord {
+        let mut position = self.previous_position.clone();
+        position += self.previous_position.direction_to(&self.target_position).map(|v| (v * self.progress) as CoordAxis);
+
+        position
+    }
+}
@@ -0,0 +1,69 @@
+use crate::game::{common::coords::{Coord, CoordAxis}, server::game_server::GameServer};
+
+// Remote players are drawn between the last two received positions, so movement stays smooth between server ticks.
+pub struct RemotePlayer {
+    name: String,
+
+    previous_position: Coord,
+    previous_yaw: f32,
+    previous_pitch: f32,
+
+    target_position: Coord,
+    target_yaw: f32,
+    target_pitch: f32,
+
+    progress: f32,
+}
+
+impl RemotePlayer {
+    pub fn create(name: String, position: Coord, yaw: f32, pitch: f32) -> Self {
+        Self {
+            name,
+
+            previous_position: position.clone(),
+            previous_yaw: yaw,
+            previous_pitch: pitch,
+
+            target_position: position,
+            target_yaw: yaw,
+            target_pitch: pitch,
+
+            progress: 1.0,
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_target(&mut self, position: Coord, yaw: f32, pitch: f32) {
+        self.previous_position = self.get_position();
+        self.previous_yaw = self.get_yaw();
+        self.previous_pitch = self.get_pitch();
+
+        self.target_position = position;
+        self.target_yaw = yaw;
+        self.target_pitch = pitch;
+
+        self.progress = 0.0;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.progress = (self.progress + delta * GameServer::TICKS_PER_SECOND as f32).min(1.0);
+    }
+
+    pub fn get_position(&self) -> Coord {
+        let mut position = self.previous_position.clone();
+        position += self.previous_position.direction_to(&self.target_position).map(|v| (v * self.progress) as CoordAxis);
+
+        position
+    }
+    pub fn get_yaw(&self) -> f32 {
+        // Turn the short way around, so 350 -> 10 does not spin through 180.
+        let difference = (self.target_yaw - self.previous_yaw + 540.0).rem_euclid(360.0) - 180.0;
+        self.previous_yaw + difference * self.progress
+    }
+    pub fn get_pitch(&self) -> f32 {
+        self.previous_pitch + (self.target_pitch - self.previous_pitch) * self.progress
+    }
+}
@@ -1,6 +1,6 @@
 use cgmath::{Matrix4, Vector3};
 
-use crate::{engine::{shader::Shader, texture::Texture}, game::{common::coords::{ChunkAxis, ChunkPos}, server::world::chunk::Chunk}};
+use crate::{engine::{shader::Shader, texture::Texture}, game::{common::coords::{ChunkAxis, ChunkPos, Direction}, server::world::chunk::Chunk}};
 
 pub struct TerrainAtlas {
     texture: Texture,
@@ -67,6 +67,18 @@ impl Block {
             front_texture: side_texture,
         }
     }
+    // Blocks that point somewhere show a different texture on the face they point to.
+    pub fn facing(side_texture: usize, front_texture: usize, facing: Direction) -> Self {
+        let texture = |direction: Direction| if direction == facing { front_texture } else { side_texture };
+        Self {
+            left_texture: texture(Direction::Left),
+            right_texture: texture(Direction::Right),
+            bottom_texture: side_texture,
+            top_texture: side_texture,
+            back_texture: texture(Direction::Back),
+            front_texture: texture(Direction::Front),
+        }
+    }
     // pub fn each(
     //     left_texture: usize, right_texture: usize,
     //     bottom_texture: usize, top_texture: usize,
@@ -154,6 +166,42 @@ impl TerrainShader {
     }
 }
 
+pub struct EntityShader {
+    base: Shader,
+}
+impl EntityShader {
+    pub const COLOR_TEXTURE_SLOT: u32 = 0;
+
+    pub fn create() -> Self {
+        let base = Shader::create("./assets/shaders/entity.vert", "./assets/shaders/entity.frag");
+        base.bind();
+        base.set_int("u_ColorSampler", 0);
+        Shader::unbind();
+
+        Self { base }
+    }
+
+    pub fn bind(&self) {
+        self.base.bind();
+    }
+
+    pub fn set_mvp_matrix(&self, matrix: &Matrix4<f32>) {
+        self.base.set_mat4("u_MVPMatrix", matrix);
+    }
+    pub fn set_model_matrix(&self, matrix: &Matrix4<f32>) {
+        self.base.set_mat4("u_ModelMatrix", matrix);
+    }
+    pub fn set_sun_direction(&self, direction: &Vector3<f32>) {
+        self.base.set_vec3("u_SunDirection", (direction.x, direction.y, direction.z));
+    }
+    pub fn set_sky_color(&self, color: &Vector3<f32>) {
+        self.base.set_vec3("u_SkyColor", (color.x, color.y, color.z));
+    }
+    pub fn set_sun_color(&self, color: &Vector3<f32>) {
+        self.base.set_vec3("u_SunColor", (color.x, color.y, color.z));
+    }
+}
+
 pub struct LineShader {
     base: Shader,
 }
@@ -0,0 +1,97 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::game::{common::{day_cycle::{self, DAY_LENGTH}, weather::Weather}, server::game_server::GameServer};
+
+// Colors at points of the day, between them they are blended. The sky color is also the ambient light,
+// at night the sun color is the light of the moon.
+const KEYFRAMES: [(u64, [f32; 3], [f32; 3]); 6] = [
+    (day_cycle::SUNRISE, [0.62, 0.46, 0.42], [1.25, 0.78, 0.5]),
+    (day_cycle::SUNRISE + 1500, [0.08, 0.47, 0.8], [1.23, 1.18, 1.1]),
+    (day_cycle::SUNSET - 1500, [0.08, 0.47, 0.8], [1.23, 1.18, 1.1]),
+    (day_cycle::SUNSET, [0.66, 0.38, 0.3], [1.25, 0.66, 0.4]),
+    (day_cycle::SUNSET + 1500, [0.02, 0.03, 0.08], [0.22, 0.27, 0.42]),
+    (day_cycle::SUNRISE + DAY_LENGTH - 1500, [0.02, 0.03, 0.08], [0.22, 0.27, 0.42]),
+];
+
+// Seconds it takes rain to start or stop completely.
+const WEATHER_FADE_TIME: f32 = 5.0;
+
+// The world time and weather as the client sees it. The server sends the time every now and then,
+// in between it runs on its own.
+pub struct Sky {
+    time: f64,
+    weather: Weather,
+    // How far the weather has faded in, from 0 to 1.
+    rain_strength: f32,
+    thunder_strength: f32,
+}
+
+impl Sky {
+    pub fn create() -> Self {
+        Self {
+            time: day_cycle::NOON as f64,
+            weather: Weather::Clear,
+            rain_strength: 0.0,
+            thunder_strength: 0.0,
+        }
+    }
+
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time as f64;
+    }
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta as f64 * GameServer::TICKS_PER_SECOND as f64;
+
+        let fade = |strength: f32, is_on: bool| {
+            let target = if is_on { 1.0 } else { 0.0 };
+            strength + (target - strength).clamp(-delta / WEATHER_FADE_TIME, delta / WEATHER_FADE_TIME)
+        };
+        self.rain_strength = fade(self.rain_strength, self.weather.is_raining());
+        self.thunder_strength = fade(self.thunder_strength, self.weather == Weather::Thunder);
+    }
+    pub fn get_rain_strength(&self) -> f32 {
+        self.rain_strength
+    }
+
+    fn get_time_of_day(&self) -> f64 {
+        self.time.rem_euclid(DAY_LENGTH as f64)
+    }
+
+    // Where light comes from, the sun circles from east to west and the moon is opposite of it.
+    // The returned direction points from the light towards the ground, like the shaders expect.
+    pub fn get_sun_direction(&self) -> Vector3<f32> {
+        let angle = (self.get_time_of_day() / DAY_LENGTH as f64 * std::f64::consts::TAU) as f32;
+        let sun = Vector3::new(angle.cos(), angle.sin(), -0.4);
+
+        let light = if sun.y >= 0.0 { sun } else { -sun };
+        -light.normalize()
+    }
+
+    fn blend(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let time = self.get_time_of_day() as f32;
+        let next_index = KEYFRAMES.iter().position(|(start, _, _)| *start as f32 > time).unwrap_or(KEYFRAMES.len());
+
+        let (from_time, from_sky, from_sun) = KEYFRAMES[next_index - 1];
+        let (to_time, to_sky, to_sun) = match KEYFRAMES.get(next_index) {
+            Some(keyframe) => *keyframe,
+            None => (KEYFRAMES[0].0 + DAY_LENGTH, KEYFRAMES[0].1, KEYFRAMES[0].2),
+        };
+
+        let amount = (time - from_time as f32) / (to_time - from_time) as f32;
+        let lerp = |from: [f32; 3], to: [f32; 3]| Vector3::from(from) + (Vector3::from(to) - Vector3::from(from)) * amount;
+        (lerp(from_sky, to_sky), lerp(from_sun, to_sun))
+    }
+    // Clouds grey out the sky and dim the light, more so in a thunderstorm.
+    pub fn get_sky_color(&self) -> Vector3<f32> {
+        let color = self.blend().0;
+        let grey = Vector3::from([color.x * 0.3 + color.y * 0.59 + color.z * 0.11; 3]);
+
+        (color + (grey - color) * 0.7 * self.rain_strength) * (1.0 - 0.35 * self.rain_strength - 0.25 * self.thunder_strength)
+    }
+    pub fn get_sun_color(&self) -> Vector3<f32> {
+        self.blend().1 * (1.0 - 0.5 * self.rain_strength - 0.25 * self.thunder_strength)
+    }
+}
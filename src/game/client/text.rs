@@ -0,0 +1,112 @@
+use cgmath::Vector3;
+
+use crate::game::common::coords::{Coord, CoordAxis};
+
+use super::debug::LineDebug;
+
+// A stroke font drawn with debug lines: every glyph is a list of segments "x0y0x1y1" on a 4x6 grid.
+pub struct LineFont;
+
+impl LineFont {
+    pub const GLYPH_WIDTH: f32 = 4.0;
+    pub const ADVANCE: f32 = 6.0;
+
+    fn get_glyph(character: char) -> &'static str {
+        match character.to_ascii_uppercase() {
+            'A' => "0004 0426 2644 4440 0343",
+            'B' => "0006 0636 3645 4544 4433 0333 3342 4241 4130 3000",
+            'C' => "4606 0600 0040",
+            'D' => "0006 0626 2644 4442 4220 2000",
+            'E' => "4606 0600 0040 0333",
+            'F' => "4606 0600 0333",
+            'G' => "4606 0600 0040 4043 4323",
+            'H' => "0006 4046 0343",
+            'I' => "0646 2620 0040",
+            'J' => "4640 4000 0002",
+            'K' => "0006 1346 1340 0313",
+            'L' => "0600 0040",
+            'M' => "0006 0623 2346 4640",
+            'N' => "0006 0640 4046",
+            'O' => "0006 0646 4640 4000",
+            'P' => "0006 0646 4643 4303",
+            'Q' => "0006 0646 4640 4000 2240",
+            'R' => "0006 0646 4643 4303 1340",
+            'S' => "4606 0603 0343 4340 4000",
+            'T' => "0646 2620",
+            'U' => "0600 0040 4046",
+            'V' => "0620 2046",
+            'W' => "0600 0023 2340 4046",
+            'X' => "0046 0640",
+            'Y' => "0623 4623 2320",
+            'Z' => "0646 4600 0040",
+            '0' => "0006 0646 4640 4000 0046",
+            '1' => "1526 2620 0040",
+            '2' => "0646 4643 4303 0300 0040",
+            '3' => "0646 4640 4000 1343",
+            '4' => "0603 0343 4640",
+            '5' => "4606 0603 0343 4340 4000",
+            '6' => "4606 0600 0040 4043 4303",
+            '7' => "0646 4610",
+            '8' => "0006 0646 4640 4000 0343",
+            '9' => "4303 0306 0646 4640 4000",
+            '_' => "0040",
+            '-' => "1333",
+            '+' => "2125 0343",
+            '=' => "0242 0444",
+            '.' => "2021",
+            ',' => "2110",
+            ':' => "2122 2425",
+            ';' => "2110 2425",
+            '!' => "2622 2120",
+            '?' => "0646 4643 4323 2322 2120",
+            '/' => "0046",
+            '\\' => "0640",
+            '~' => "0314 1432 3243",
+            '\'' => "2624",
+            '"' => "1614 3634",
+            '(' => "3614 1412 1230",
+            ')' => "1634 3432 3210",
+            '[' => "3616 1610 1030",
+            ']' => "1636 3630 3010",
+            '<' => "4603 0340",
+            '>' => "0643 4300",
+            '#' => "1016 3036 0242 0444",
+            '*' => "0145 0541 2026",
+            '%' => "0046 0605 4140",
+            '@' => "4000 0006 0646 4622 2242",
+            ' ' => "",
+            _ => "0040 4046 4606 0600",
+        }
+    }
+
+    pub fn get_width(text: &str) -> f32 {
+        let length = text.chars().count();
+        if length == 0 {
+            return 0.0;
+        }
+
+        length as f32 * Self::ADVANCE - (Self::ADVANCE - Self::GLYPH_WIDTH)
+    }
+
+    // Draws text starting at origin (its bottom left corner), along the given right and up axes.
+    pub fn draw(line_debug: &mut LineDebug, text: &str, origin: &Coord, right: &Vector3<f32>, up: &Vector3<f32>, scale: f32) {
+        let point = |x: f32, y: f32| {
+            let mut point = origin.clone();
+            point += (right * x + up * y).map(|v| (v * scale) as CoordAxis);
+            point
+        };
+
+        for (index, character) in text.chars().enumerate() {
+            let offset = index as f32 * Self::ADVANCE;
+
+            for segment in Self::get_glyph(character).split(' ').filter(|segment| segment.len() == 4) {
+                let digits: Vec<f32> = segment.chars().map(|digit| digit.to_digit(10).unwrap_or(0) as f32).collect();
+
+                line_debug.line(
+                    &point(offset + digits[0], digits[1]),
+                    &point(offset + digits[2], digits[3]),
+                );
+            }
+        }
+    }
+}
@@ -1,41 +1,229 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use cgmath::{MetricSpace, Point2};
-use crate::game::{common::{coords::{ChunkPos, Coord}, world::mapping::ChunkPosHasherBuilder}, server::{world::chunk::Chunk, ServerWorld}};
-use super::{chunk_mesh::{ChunkData, ChunkMesh, NextChunks}, resources::BlockRegistry};
+use crate::game::{common::{coords::{BlockAxis, BlockPos, ChunkPos, Coord, LocalBlockAxis, LocalBlockPos}, entity::EntityId, player::PlayerId, world::{block_entity::BlockEntity, block_types::BlockTypes, block_view::BlockView, lighting, mapping::ChunkPosHasherBuilder}}, server::world::chunk::Chunk};
+use super::{chunk_mesh::{ChunkData, ChunkMesh, NextChunks}, remote_entity::RemoteEntity, remote_player::RemotePlayer, resources::BlockRegistry};
 
 pub struct ClientWorld {
+    chunks: HashMap<ChunkPos, Chunk, ChunkPosHasherBuilder>,
     chunk_meshes: HashMap<ChunkPos, ChunkMesh, ChunkPosHasherBuilder>,
+    urgent_chunks: Vec<ChunkPos>,
+
+    players: HashMap<PlayerId, RemotePlayer>,
+    entities: HashMap<EntityId, RemoteEntity>,
 }
 
 impl ClientWorld {
     pub fn create() -> Self {
-        Self { chunk_meshes: HashMap::default() }
+        Self {
+            chunks: HashMap::default(),
+            chunk_meshes: HashMap::default(),
+            urgent_chunks: vec![],
+
+            players: HashMap::new(),
+            entities: HashMap::new(),
+        }
     }
 
-    pub fn update(&mut self, block_registry: &BlockRegistry, view_position: &Coord, view_distance: usize, world: &mut ServerWorld) {
-        let chunk_view_pos = Point2::new(
-            view_position.get_chunk_x() as f64 + (view_position.get_local_x() as f64 / Chunk::WIDTH as f64).round(),
-            view_position.get_chunk_z() as f64 + (view_position.get_local_z() as f64 / Chunk::LENGTH as f64).round(),
-        );
+    fn mark_neighbours_dirty(&mut self, position: &ChunkPos) {
+        if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(position.x - 1,        position.z)) { chunk.mark_dirty(); }
+        if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(position.x + 1,        position.z)) { chunk.mark_dirty(); }
+        if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(       position.x, position.z - 1)) { chunk.mark_dirty(); }
+        if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(       position.x, position.z + 1)) { chunk.mark_dirty(); }
+    }
+
+    pub fn insert_chunk(&mut self, position: ChunkPos, chunk: Chunk) {
+        self.mark_neighbours_dirty(&position);
+        self.chunks.insert(position.clone(), chunk);
+
+        let touched = lighting::light_chunk(&mut self.chunks, &position);
+        self.mark_all_dirty(touched);
+    }
+    pub fn remove_chunk(&mut self, position: &ChunkPos) {
+        self.chunks.remove(position);
+        self.chunk_meshes.remove(position);
+        self.mark_neighbours_dirty(position);
+    }
+
+    pub fn set_block(&mut self, position: &BlockPos, block: u8) -> bool {
+        let chunk_pos = position.to_chunk_pos();
+
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else { return false; };
+        let local_x = (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis;
+        let local_z = (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis;
+
+        if !chunk.set_block(&LocalBlockPos::new(local_x, position.y as LocalBlockAxis, local_z), block) {
+            return false;
+        }
+
+        let touched = lighting::update_block(&mut self.chunks, position);
+        self.mark_all_dirty(touched);
 
-        let mut chunk_meshes_to_remove: Vec<ChunkPos> = vec![];
-        for position in self.chunk_meshes.keys() {
-            let chunk = world.get_chunk(position);
-            if chunk.is_some() && !chunk.unwrap().is_dirty() {
-                continue;
+        if local_x == 0 {
+            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x - 1, chunk_pos.z)) { chunk.mark_dirty(); }
+        } else if local_x >= Chunk::WIDTH - 1 {
+            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x + 1, chunk_pos.z)) { chunk.mark_dirty(); }
+        }
+
+        if local_z == 0 {
+            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x, chunk_pos.z - 1)) { chunk.mark_dirty(); }
+        } else if local_z >= Chunk::LENGTH - 1 {
+            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x, chunk_pos.z + 1)) { chunk.mark_dirty(); }
+        }
+
+        // Edited chunks skip the queue, so the player sees the result of an edit right away.
+        if !self.urgent_chunks.contains(&chunk_pos) {
+            self.urgent_chunks.push(chunk_pos);
+        }
+
+        true
+    }
+    // Applies all changes of a batch before anything is remeshed, so a half finished edit is never drawn.
+    pub fn apply_block_changes(&mut self, chunk_pos: &ChunkPos, section: usize, changes: &[(u16, u8)]) -> bool {
+        let Some(chunk) = self.chunks.get_mut(chunk_pos) else { return false; };
+        if section >= Chunk::SECTION_COUNT || changes.iter().any(|(index, _)| *index as usize >= Chunk::SECTION_VOLUME) {
+            return false;
+        }
+
+        let mut touched_edges = [false; 4];
+        for (index, block) in changes {
+            let position = Chunk::from_section_index(section, *index);
+            touched_edges[0] |= position.x == 0;
+            touched_edges[1] |= position.x == Chunk::WIDTH - 1;
+            touched_edges[2] |= position.z == 0;
+            touched_edges[3] |= position.z == Chunk::LENGTH - 1;
+
+            chunk.set_block(&position, *block);
+        }
+
+        self.mark_edges_dirty(chunk_pos, touched_edges);
+        let positions: Vec<u16> = changes.iter().map(|(index, _)| *index).collect();
+        self.update_light(chunk_pos, section, &positions);
+        if !self.urgent_chunks.contains(chunk_pos) {
+            self.urgent_chunks.push(chunk_pos.clone());
+        }
+
+        true
+    }
+    pub fn set_section(&mut self, chunk_pos: &ChunkPos, section: usize, blocks: &[u8]) -> bool {
+        let Some(chunk) = self.chunks.get_mut(chunk_pos) else { return false; };
+        let Some(old_blocks) = chunk.get_section(section).map(|blocks| blocks.to_vec()) else { return false; };
+        if !chunk.set_section(section, blocks) {
+            return false;
+        }
+
+        self.mark_edges_dirty(chunk_pos, [true; 4]);
+        // Only the blocks that really changed need their light fixed up.
+        let changed: Vec<u16> = (0..Chunk::SECTION_VOLUME)
+            .filter(|index| old_blocks[*index] != blocks[*index])
+            .map(|index| index as u16)
+            .collect();
+        self.update_light(chunk_pos, section, &changed);
+        if !self.urgent_chunks.contains(chunk_pos) {
+            self.urgent_chunks.push(chunk_pos.clone());
+        }
+
+        true
+    }
+    fn update_light(&mut self, chunk_pos: &ChunkPos, section: usize, indices: &[u16]) {
+        let (start_x, start_z) = chunk_pos.to_block_xz_pos();
+        let mut touched = HashSet::new();
+        for index in indices {
+            let local = Chunk::from_section_index(section, *index);
+            let position = BlockPos::new(start_x + local.x as BlockAxis, local.y as BlockAxis, start_z + local.z as BlockAxis);
+            touched.extend(lighting::update_block(&mut self.chunks, &position));
+        }
+        self.mark_all_dirty(touched);
+    }
+    fn mark_all_dirty(&mut self, positions: HashSet<ChunkPos>) {
+        for position in positions {
+            if let Some(chunk) = self.chunks.get_mut(&position) {
+                chunk.mark_dirty();
             }
+        }
+    }
+    // Neighbours share faces with the edges of a chunk, in order -x, +x, -z and +z.
+    fn mark_edges_dirty(&mut self, position: &ChunkPos, edges: [bool; 4]) {
+        let neighbours = [
+            ChunkPos::new(position.x - 1, position.z),
+            ChunkPos::new(position.x + 1, position.z),
+            ChunkPos::new(position.x, position.z - 1),
+            ChunkPos::new(position.x, position.z + 1),
+        ];
 
-            if chunk_view_pos.distance(Point2::new(position.x as f64, position.z as f64)) > view_distance as f64 {
-                chunk_meshes_to_remove.push(position.clone());
+        for (neighbour, is_touched) in neighbours.iter().zip(edges) {
+            if let (true, Some(chunk)) = (is_touched, self.chunks.get_mut(neighbour)) {
+                chunk.mark_dirty();
             }
         }
-        for position in chunk_meshes_to_remove {
-            self.chunk_meshes.remove(&position);
+    }
+    // The height above the highest block of a column, None while its chunk is not loaded.
+    pub fn get_height(&self, x: BlockAxis, z: BlockAxis) -> Option<BlockAxis> {
+        let chunk_pos = BlockPos::new(x, 0, z).to_chunk_pos();
+        let chunk = self.chunks.get(&chunk_pos)?;
+
+        Some(chunk.get_height(
+            (x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+            (z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+        ) as BlockAxis)
+    }
+    pub fn get_block(&self, position: &BlockPos) -> u8 {
+        let chunk_pos = position.to_chunk_pos();
+
+        match self.chunks.get(&chunk_pos) {
+            Some(chunk) => {
+                chunk.get_block(&LocalBlockPos::new(
+                    (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+                    position.y as LocalBlockAxis,
+                    (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+                ))
+            },
+            None => { BlockTypes::Air as u8 },
         }
+    }
+
+    pub fn get_block_entity(&self, position: &BlockPos) -> Option<&BlockEntity> {
+        let chunk_pos = position.to_chunk_pos();
+        self.chunks.get(&chunk_pos)?.get_block_entity(&LocalBlockPos::new(
+            (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+            position.y as LocalBlockAxis,
+            (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+        ))
+    }
+    pub fn set_block_entity(&mut self, position: &BlockPos, entity: BlockEntity) -> bool {
+        let chunk_pos = position.to_chunk_pos();
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else { return false; };
+
+        chunk.set_block_entity(&LocalBlockPos::new(
+            (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+            position.y as LocalBlockAxis,
+            (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+        ), entity)
+    }
+    // Block entities of every loaded chunk, by their world position.
+    pub fn get_all_block_entities(&self) -> impl Iterator<Item = (BlockPos, &BlockEntity)> + '_ {
+        self.chunks.iter().flat_map(|(chunk_pos, chunk)| {
+            let (start_x, start_z) = chunk_pos.to_block_xz_pos();
+            chunk.get_block_entities().map(move |(local, entity)| {
+                (BlockPos::new(start_x + local.x as BlockAxis, local.y as BlockAxis, start_z + local.z as BlockAxis), entity)
+            })
+        })
+    }
+
+    pub fn update(&mut self, block_registry: &BlockRegistry, view_position: &Coord, view_distance: usize, delta: f32) {
+        for player in self.players.values_mut() {
+            player.update(delta);
+        }
+        for entity in self.entities.values_mut() {
+            entity.update(delta);
+        }
+
+        let chunk_view_pos = Point2::new(
+            view_position.get_chunk_x() as f64 + (view_position.get_local_x() as f64 / Chunk::WIDTH as f64).round(),
+            view_position.get_chunk_z() as f64 + (view_position.get_local_z() as f64 / Chunk::LENGTH as f64).round(),
+        );
 
-        let chunks_to_update: Vec<ChunkPos> = world
-            .get_all_chunks()
+        let chunks_to_update: Vec<ChunkPos> = self.chunks
             .iter()
             .filter(|(position, chunk)| chunk_view_pos.distance(
                 Point2::new(position.x as f64, position.z as f64)) <= view_distance as f64 &&
@@ -43,28 +231,29 @@ impl ClientWorld {
             )
             .map(|(position, _)| position.clone())
             .collect();
-        
-        if let Some(position) = chunks_to_update.first() {
-            if let Some(chunk) = world.get_chunk(position) {
+
+        let urgent_position = self.urgent_chunks.pop();
+        if let Some(position) = urgent_position.as_ref().or(chunks_to_update.first()) {
+            if let Some(chunk) = self.chunks.get(position) {
                 let mut chunk_data = ChunkData::create();
                 chunk_data.build(block_registry, chunk, &NextChunks::create(
-                    world.get_chunk(&ChunkPos::new(position.x - 1, position.z)),
-                    world.get_chunk(&ChunkPos::new(position.x + 1, position.z)),
-                    world.get_chunk(&ChunkPos::new(position.x, position.z - 1)),
-                    world.get_chunk(&ChunkPos::new(position.x, position.z + 1)),
+                    self.chunks.get(&ChunkPos::new(position.x - 1, position.z)),
+                    self.chunks.get(&ChunkPos::new(position.x + 1, position.z)),
+                    self.chunks.get(&ChunkPos::new(position.x, position.z - 1)),
+                    self.chunks.get(&ChunkPos::new(position.x, position.z + 1)),
                 ));
-    
+
                 if self.chunk_meshes.contains_key(position) {
                     let chunk_mesh = self.chunk_meshes.get_mut(position).unwrap();
                     chunk_mesh.build(&chunk_data);
                 } else {
                     let mut chunk_mesh = ChunkMesh::create();
                     chunk_mesh.build(&chunk_data);
-    
+
                     self.chunk_meshes.insert(ChunkPos::new(position.x, position.z), chunk_mesh);
                 }
             }
-            if let Some(chunk) = world.get_chunk_mut(position) {
+            if let Some(chunk) = self.chunks.get_mut(position) {
                 chunk.mark_clean();
             }
         }
@@ -73,4 +262,36 @@ impl ClientWorld {
     pub fn get_all_meshes(&self) -> &HashMap<ChunkPos, ChunkMesh, ChunkPosHasherBuilder> {
         &self.chunk_meshes
     }
-}
\ No newline at end of file
+
+    pub fn add_player(&mut self, id: PlayerId, player: RemotePlayer) {
+        self.players.insert(id, player);
+    }
+    pub fn remove_player(&mut self, id: PlayerId) {
+        self.players.remove(&id);
+    }
+    pub fn get_player_mut(&mut self, id: PlayerId) -> Option<&mut RemotePlayer> {
+        self.players.get_mut(&id)
+    }
+    pub fn get_all_players(&self) -> &HashMap<PlayerId, RemotePlayer> {
+        &self.players
+    }
+
+    pub fn add_entity(&mut self, id: EntityId, entity: RemoteEntity) {
+        self.entities.insert(id, entity);
+    }
+    pub fn remove_entity(&mut self, id: EntityId) {
+        self.entities.remove(&id);
+    }
+    pub fn get_entity_mut(&mut self, id: EntityId) -> Option<&mut RemoteEntity> {
+        self.entities.get_mut(&id)
+    }
+    pub fn get_all_entities(&self) -> &HashMap<EntityId, RemoteEntity> {
+        &self.entities
+    }
+}
+
+impl BlockView for ClientWorld {
+    fn get_block(&self, position: &BlockPos) -> u8 {
+        ClientWorld::get_block(self, position)
+    }
+}
@@ -11,6 +11,9 @@ pub type CoordAxis = f64;
 pub type LocalCoordAxis = f32;
 pub type LocalBlockAxis = usize;
 
+// Nothing lies further out on x or z, so chunk coordinates stay far away from overflowing.
+pub const WORLD_BORDER: BlockAxis = 30_000_000;
+
 #[derive(Clone)]
 pub struct ChunkPos {
     pub x: ChunkAxis,
@@ -32,14 +35,14 @@ impl Add<ChunkPos> for &ChunkPos {
     type Output = ChunkPos;
     
     fn add(self, rhs: ChunkPos) -> Self::Output {
-        ChunkPos::new(self.x + rhs.x, self.z + rhs.z)
+        ChunkPos::new(self.x.saturating_add(rhs.x), self.z.saturating_add(rhs.z))
     }
 }
 impl Sub<&ChunkPos> for &ChunkPos {
     type Output = ChunkPos;
     
     fn sub(self, rhs: &ChunkPos) -> Self::Output {
-        ChunkPos::new(self.x - rhs.x, self.z - rhs.z)
+        ChunkPos::new(self.x.saturating_sub(rhs.x), self.z.saturating_sub(rhs.z))
     }
 }
 impl PartialEq for ChunkPos {
@@ -55,6 +58,7 @@ impl Hash for ChunkPos {
     }
 }
 
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct BlockPos {
     pub x: BlockAxis,
     pub y: BlockAxis,
@@ -65,6 +69,17 @@ impl BlockPos {
         Self { x, y, z }
     }
 
+    pub fn offset(&self, direction: Direction) -> Self {
+        let (dx, dy, dz) = direction.get_offset();
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+    pub fn get_bottom_center(&self) -> Coord {
+        Coord::new(self.x as CoordAxis + 0.5, self.y as CoordAxis, self.z as CoordAxis + 0.5)
+    }
+    pub fn get_center(&self) -> Coord {
+        Coord::new(self.x as CoordAxis + 0.5, self.y as CoordAxis + 0.5, self.z as CoordAxis + 0.5)
+    }
+
     pub fn to_chunk_pos(&self) -> ChunkPos {
         ChunkPos::new(
             self.x.div_euclid(Chunk::WIDTH as BlockAxis) as ChunkAxis,
@@ -73,6 +88,64 @@ impl BlockPos {
     }
 }
 
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left, Right,
+    Bottom, Top,
+    Back, Front,
+}
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::Left, Direction::Right,
+        Direction::Bottom, Direction::Top,
+        Direction::Back, Direction::Front,
+    ];
+    pub const HORIZONTAL: [Direction; 4] = [Direction::Left, Direction::Right, Direction::Back, Direction::Front];
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+    pub fn from_normal(normal: &Vector3<BlockAxis>) -> Option<Self> {
+        Self::ALL.into_iter().find(|direction| {
+            let offset = direction.get_offset();
+            offset.0 == normal.x && offset.1 == normal.y && offset.2 == normal.z
+        })
+    }
+
+    pub fn get_opposite(&self) -> Self {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::Bottom => Direction::Top,
+            Direction::Top => Direction::Bottom,
+            Direction::Back => Direction::Front,
+            Direction::Front => Direction::Back,
+        }
+    }
+    // The horizontal direction closest to where a vector points.
+    pub fn from_horizontal(x: f32, z: f32) -> Self {
+        match (x.abs() >= z.abs(), x >= 0.0, z >= 0.0) {
+            (true, true, _) => Direction::Right,
+            (true, false, _) => Direction::Left,
+            (false, _, true) => Direction::Front,
+            (false, _, false) => Direction::Back,
+        }
+    }
+
+    pub fn get_offset(&self) -> (BlockAxis, BlockAxis, BlockAxis) {
+        match self {
+            Direction::Left => (-1, 0, 0),
+            Direction::Right => (1, 0, 0),
+            Direction::Bottom => (0, -1, 0),
+            Direction::Top => (0, 1, 0),
+            Direction::Back => (0, 0, -1),
+            Direction::Front => (0, 0, 1),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct LocalBlockPos {
     pub x: LocalBlockAxis,
     pub y: LocalBlockAxis,
@@ -84,6 +157,7 @@ impl LocalBlockPos {
     }
 }
 
+#[derive(Clone)]
 pub struct Coord {
     chunk_x: ChunkAxis, chunk_z: ChunkAxis,
     frac_x: LocalCoordAxis, frac_z: LocalCoordAxis,
@@ -105,6 +179,29 @@ impl Coord {
             y,
         }
     }
+    pub fn from_local(chunk_x: ChunkAxis, chunk_z: ChunkAxis, local_x: LocalCoordAxis, y: CoordAxis, local_z: LocalCoordAxis) -> Self {
+        Self {
+            chunk_x, chunk_z,
+            frac_x: local_x, frac_z: local_z,
+
+            y,
+        }
+    }
+
+    pub fn distance(&self, other: &Coord) -> CoordAxis {
+        let dx = (self.chunk_x - other.chunk_x) as CoordAxis * Chunk::WIDTH as CoordAxis + (self.frac_x - other.frac_x) as CoordAxis;
+        let dz = (self.chunk_z - other.chunk_z) as CoordAxis * Chunk::LENGTH as CoordAxis + (self.frac_z - other.frac_z) as CoordAxis;
+        let dy = self.y - other.y;
+
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+    pub fn direction_to(&self, other: &Coord) -> Vector3<LocalCoordAxis> {
+        Vector3::new(
+            ((other.chunk_x - self.chunk_x) as CoordAxis * Chunk::WIDTH as CoordAxis + (other.frac_x - self.frac_x) as CoordAxis) as LocalCoordAxis,
+            (other.y - self.y) as LocalCoordAxis,
+            ((other.chunk_z - self.chunk_z) as CoordAxis * Chunk::LENGTH as CoordAxis + (other.frac_z - self.frac_z) as CoordAxis) as LocalCoordAxis,
+        )
+    }
 
     pub fn get_block_x(&self) -> BlockAxis {
         self.chunk_x as BlockAxis * Chunk::WIDTH as BlockAxis + self.frac_x as BlockAxis
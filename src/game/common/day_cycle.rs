@@ -0,0 +1,21 @@
+use super::world::light::MAX_LIGHT;
+
+// A day lasts 20 minutes. Like in Minecraft the day starts at sunrise, so the sun is highest a quarter day in.
+pub const DAY_LENGTH: u64 = 24000;
+
+pub const SUNRISE: u64 = 0;
+pub const NOON: u64 = 6000;
+pub const SUNSET: u64 = 12000;
+pub const MIDNIGHT: u64 = 18000;
+
+pub fn get_time_of_day(time: u64) -> u64 {
+    time % DAY_LENGTH
+}
+
+// Sky light at night, as dim as the darkest nights in Minecraft.
+pub const NIGHT_SKY_LIGHT: u8 = 4;
+
+// The light of the open sky, full while the sun is up.
+pub fn get_sky_light(time: u64) -> u8 {
+    if get_time_of_day(time) < SUNSET { MAX_LIGHT } else { NIGHT_SKY_LIGHT }
+}
@@ -0,0 +1,32 @@
+use super::item::ItemId;
+
+pub type EntityId = u32;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    // A block that lost its support, it turns back into a block where it lands.
+    FallingBlock { block: u8 },
+    Item { item: ItemId },
+}
+
+impl EntityKind {
+    pub fn get_id(&self) -> u8 {
+        match self {
+            EntityKind::FallingBlock { .. } => 0,
+            EntityKind::Item { .. } => 1,
+        }
+    }
+    pub fn from_id(id: u8, data: u16) -> Option<Self> {
+        match id {
+            0 => Some(EntityKind::FallingBlock { block: u8::try_from(data).ok()? }),
+            1 => Some(EntityKind::Item { item: data }),
+            _ => None,
+        }
+    }
+    pub fn get_data(&self) -> u16 {
+        match self {
+            EntityKind::FallingBlock { block } => *block as u16,
+            EntityKind::Item { item } => *item,
+        }
+    }
+}
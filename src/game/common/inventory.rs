@@ -0,0 +1,77 @@
+use super::{item::ItemId, protocol::buffer::{PacketReader, PacketWriter}};
+
+// Stacks of items by slot, as item and count. Chests and players hold their items in one.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Inventory {
+    slots: Vec<Option<(ItemId, u8)>>,
+}
+
+impl Inventory {
+    pub const MAX_STACK_SIZE: u8 = 64;
+
+    pub fn create(size: usize) -> Self {
+        Self { slots: vec![None; size] }
+    }
+
+    pub fn get_slots(&self) -> &[Option<(ItemId, u8)>] {
+        &self.slots
+    }
+    pub fn get(&self, slot: usize) -> Option<(ItemId, u8)> {
+        self.slots.get(slot).copied().flatten()
+    }
+    pub fn contains(&self, item: ItemId) -> bool {
+        self.slots.iter().flatten().any(|(stacked, _)| *stacked == item)
+    }
+
+    // Puts one item on a stack of the same item or into the first empty slot, returns false if the inventory is full.
+    pub fn add_item(&mut self, item: ItemId) -> bool {
+        if let Some(Some((_, count))) = self.slots.iter_mut().find(|slot| matches!(slot, Some((stacked, count)) if *stacked == item && *count < Self::MAX_STACK_SIZE)) {
+            *count += 1;
+            return true;
+        }
+        match self.slots.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some((item, 1));
+                true
+            },
+            None => false,
+        }
+    }
+    // Takes one item from the last stack of it, returns false if there is none.
+    pub fn remove_item(&mut self, item: ItemId) -> bool {
+        let Some(slot) = self.slots.iter_mut().rev().find(|slot| matches!(slot, Some((stacked, _)) if *stacked == item)) else { return false; };
+
+        if let Some((_, count)) = slot {
+            *count -= 1;
+            if *count == 0 {
+                *slot = None;
+            }
+        }
+        true
+    }
+    // Empties the inventory and returns every item in it, one entry per item.
+    pub fn take_all(&mut self) -> Vec<ItemId> {
+        self.slots.iter_mut()
+            .filter_map(|slot| slot.take())
+            .flat_map(|(item, count)| std::iter::repeat_n(item, count as usize))
+            .collect()
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        for slot in &self.slots {
+            let (item, count) = slot.unwrap_or((0, 0));
+            writer.write_u16(item);
+            writer.write_u8(count);
+        }
+    }
+    // Chunks saved while items were only blocks store item ids in a single byte.
+    pub fn read(reader: &mut PacketReader, size: usize, has_block_item_ids: bool) -> Option<Self> {
+        let mut slots = Vec::with_capacity(size);
+        for _ in 0..size {
+            let item = if has_block_item_ids { reader.read_u8()? as ItemId } else { reader.read_u16()? };
+            let count = reader.read_u8()?;
+            slots.push(if count > 0 { Some((item, count.min(Self::MAX_STACK_SIZE))) } else { None });
+        }
+        Some(Self { slots })
+    }
+}
@@ -0,0 +1,62 @@
+use super::{coords::Direction, world::{block_types::BlockTypes, signal::SignalBlock}};
+
+// Every block is also an item with the same id, the items that are not blocks come after them.
+pub type ItemId = u16;
+
+#[repr(u16)]
+pub enum ItemTypes {
+    Hoe = 256,
+    Seeds,
+    Wheat,
+}
+
+impl ItemTypes {
+    pub const FIRST: ItemId = ItemTypes::Hoe as ItemId;
+    // Names of the items that are not blocks, from the first one on.
+    pub const NAMES: [&'static str; 3] = ["hoe", "seeds", "wheat"];
+
+    pub fn from_block(block: u8) -> ItemId {
+        block as ItemId
+    }
+    // The block the item places, None for items that are not blocks.
+    pub fn get_block(item: ItemId) -> Option<u8> {
+        u8::try_from(item).ok()
+    }
+
+    // The item a player gets for breaking the block, None for blocks that are never carried.
+    // Blocks with states give the item of their type, grass and farmland give dirt.
+    pub fn get_block_item(block: u8) -> Option<ItemId> {
+        if let Some(signal) = SignalBlock::from_block(block) {
+            return Some(Self::from_block(signal.get_type_block()));
+        }
+
+        match block {
+            block if block == BlockTypes::GrassBlock as u8 || BlockTypes::is_farmland(block) => Some(BlockTypes::Dirt as ItemId),
+            block if block == BlockTypes::Air as u8 || block == BlockTypes::Bedrock as u8 || block as usize >= BlockTypes::NAMES.len() => None,
+            block if BlockTypes::is_liquid(block) || BlockTypes::get_fire_age(block).is_some() || BlockTypes::get_wheat_age(block).is_some() => None,
+            block => Some(Self::from_block(block)),
+        }
+    }
+    // Whether placing the item can give the block, directional blocks can point anywhere.
+    pub fn places(item: ItemId, block: u8) -> bool {
+        Self::get_block(item).is_some_and(|item_block| {
+            Direction::HORIZONTAL.iter().any(|facing| SignalBlock::get_placed_block(item_block, *facing) == block)
+        })
+    }
+    // The item that places the block, None for blocks that are never placed like that, such as fire, powered wire or a lit lamp.
+    pub fn get_placing_item(block: u8) -> Option<ItemId> {
+        Self::get_block_item(block).filter(|item| Self::places(*item, block))
+    }
+
+    // Seeds are planted, tools are kept.
+    pub fn is_used_up(item: ItemId) -> bool {
+        item == ItemTypes::Seeds as ItemId
+    }
+
+    pub fn get_name(item: ItemId) -> &'static str {
+        match Self::get_block(item) {
+            Some(block) => BlockTypes::get_name(block),
+            None => Self::NAMES.get((item - Self::FIRST) as usize).copied().unwrap_or("unknown"),
+        }
+    }
+}
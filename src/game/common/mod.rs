@@ -1,2 +1,10 @@
 pub mod world;
-pub mod coords;
\ No newline at end of file
+pub mod coords;
+pub mod player;
+pub mod entity;
+pub mod item;
+pub mod inventory;
+pub mod physics;
+pub mod day_cycle;
+pub mod weather;
+pub mod protocol;
@@ -0,0 +1,201 @@
+use cgmath::{InnerSpace, Vector3};
+
+use super::{coords::{BlockAxis, BlockPos, Coord, CoordAxis}, world::{block_types::BlockTypes, block_view::BlockView}};
+
+// A box in world coordinates whose sides line up with the axes.
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vector3<CoordAxis>,
+    max: Vector3<CoordAxis>,
+}
+
+impl Aabb {
+    // Positions are stored with less precision than a move is worked out in, so touching boxes can overlap by a tiny bit.
+    const EPSILON: CoordAxis = 1e-4;
+
+    fn of_block(x: BlockAxis, y: BlockAxis, z: BlockAxis) -> Self {
+        let min = Vector3::new(x as CoordAxis, y as CoordAxis, z as CoordAxis);
+        Self { min, max: min + Vector3::new(1.0, 1.0, 1.0) }
+    }
+
+    fn offset(&self, offset: Vector3<CoordAxis>) -> Self {
+        Self { min: self.min + offset, max: self.max + offset }
+    }
+    // The box grown to also cover where it would be after the move.
+    fn expand(&self, offset: Vector3<CoordAxis>) -> Self {
+        Self {
+            min: self.min + offset.map(|v| v.min(0.0)),
+            max: self.max + offset.map(|v| v.max(0.0)),
+        }
+    }
+
+    fn overlaps_on(&self, other: &Aabb, axis: usize) -> bool {
+        self.min[axis] < other.max[axis] - Self::EPSILON && self.max[axis] > other.min[axis] + Self::EPSILON
+    }
+    // How far the box can move along the axis before it touches the other box, at most the offset.
+    // Boxes that already overlap don't stop it, so a body stuck in a block can walk out of it.
+    fn clip(&self, other: &Aabb, axis: usize, offset: CoordAxis) -> CoordAxis {
+        if !(0..3).filter(|other_axis| *other_axis != axis).all(|other_axis| self.overlaps_on(other, other_axis)) {
+            return offset;
+        }
+
+        if offset > 0.0 && self.max[axis] <= other.min[axis] + Self::EPSILON {
+            offset.min(other.min[axis] - self.max[axis])
+        } else if offset < 0.0 && self.min[axis] >= other.max[axis] - Self::EPSILON {
+            offset.max(other.max[axis] - self.min[axis])
+        } else {
+            offset
+        }
+    }
+}
+
+// The box a player walks around in: it falls, jumps and is stopped by the blocks it collides with.
+// Velocities are in blocks per second, so it can be moved every frame.
+pub struct PlayerBody {
+    // The bottom center of the body.
+    pub position: Coord,
+    velocity: Vector3<CoordAxis>,
+    is_on_ground: bool,
+}
+
+impl PlayerBody {
+    const WIDTH: CoordAxis = 0.6;
+    const HEIGHT: CoordAxis = 1.8;
+    pub const EYE_HEIGHT: CoordAxis = 1.62;
+
+    // Blocks per second squared and blocks per second, a jump clears a bit more than a block like in Minecraft.
+    const GRAVITY: CoordAxis = 32.0;
+    const MAX_FALL_SPEED: CoordAxis = 78.4;
+    const JUMP_SPEED: CoordAxis = 9.0;
+    // How much of the way to the wished velocity the body gets per second. Feet on the ground grip, in the air they barely do.
+    const GROUND_FRICTION: CoordAxis = 12.0;
+    const AIR_FRICTION: CoordAxis = 2.0;
+    // Ledges up to this high are walked onto without jumping.
+    const STEP_HEIGHT: CoordAxis = 0.5;
+    // Feet at most this far above a block still stand on it.
+    const GROUND_DISTANCE: CoordAxis = 0.05;
+
+    pub fn create(eye_position: &Coord) -> Self {
+        let mut body = Self {
+            position: eye_position.clone(),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
+            is_on_ground: false,
+        };
+        body.set_eye_position(eye_position);
+        body
+    }
+
+    pub fn get_eye_position(&self) -> Coord {
+        let mut position = self.position.clone();
+        position += Vector3::new(0.0, Self::EYE_HEIGHT, 0.0);
+        position
+    }
+    // Puts the eyes of the body at the position and stops it, like after a teleport.
+    pub fn set_eye_position(&mut self, position: &Coord) {
+        self.position = position.clone();
+        self.position += Vector3::new(0.0, -Self::EYE_HEIGHT, 0.0);
+        self.velocity = Vector3::new(0.0, 0.0, 0.0);
+        self.is_on_ground = false;
+    }
+
+    pub fn is_on_ground(&self) -> bool {
+        self.is_on_ground
+    }
+
+    fn get_box(&self) -> Aabb {
+        Self::get_box_at(&self.position)
+    }
+    fn get_box_at(position: &Coord) -> Aabb {
+        let half_width = Self::WIDTH * 0.5;
+        let (x, y, z) = (position.get_world_x(), position.get_world_y(), position.get_world_z());
+        Aabb {
+            min: Vector3::new(x - half_width, y, z - half_width),
+            max: Vector3::new(x + half_width, y + Self::HEIGHT, z + half_width),
+        }
+    }
+
+    // The boxes of the blocks in the area that the body collides with.
+    fn get_colliders(world: &dyn BlockView, area: &Aabb) -> Vec<Aabb> {
+        let min = area.min.map(|v| v.floor() as BlockAxis);
+        let max = area.max.map(|v| v.ceil() as BlockAxis);
+
+        let mut colliders = Vec::new();
+        for y in min.y..max.y {
+            for z in min.z..max.z {
+                for x in min.x..max.x {
+                    if BlockTypes::has_collision(world.get_block(&BlockPos::new(x, y, z))) {
+                        colliders.push(Aabb::of_block(x, y, z));
+                    }
+                }
+            }
+        }
+        colliders
+    }
+    // Moves the box as far as the colliders let it, one axis after the other starting with the height, and returns how far it got.
+    fn sweep(colliders: &[Aabb], aabb: &Aabb, offset: Vector3<CoordAxis>) -> Vector3<CoordAxis> {
+        let mut aabb = *aabb;
+        let mut moved = Vector3::new(0.0, 0.0, 0.0);
+
+        for axis in [1, 0, 2] {
+            let distance = colliders.iter().fold(offset[axis], |distance, collider| aabb.clip(collider, axis, distance));
+            moved[axis] = distance;
+
+            let mut axis_offset = Vector3::new(0.0, 0.0, 0.0);
+            axis_offset[axis] = distance;
+            aabb = aabb.offset(axis_offset);
+        }
+        moved
+    }
+
+    // Whether a body with its feet at the position stands on a block it collides with, so the server doesn't have to take the client's word for it.
+    pub fn is_standing_at(world: &dyn BlockView, feet: &Coord) -> bool {
+        let aabb = Self::get_box_at(feet);
+        let offset = Vector3::new(0.0, -Self::GROUND_DISTANCE, 0.0);
+        let colliders = Self::get_colliders(world, &aabb.expand(offset));
+        Self::sweep(&colliders, &aabb, offset).y > offset.y
+    }
+
+    // Speeds the body up towards the wished horizontal velocity, lets it fall and moves it through the world.
+    pub fn update(&mut self, world: &dyn BlockView, wish_velocity: Vector3<CoordAxis>, jump: bool, delta: CoordAxis) {
+        let friction = if self.is_on_ground { Self::GROUND_FRICTION } else { Self::AIR_FRICTION };
+        let blend = (friction * delta).min(1.0);
+        self.velocity.x += (wish_velocity.x - self.velocity.x) * blend;
+        self.velocity.z += (wish_velocity.z - self.velocity.z) * blend;
+
+        if jump && self.is_on_ground {
+            self.velocity.y = Self::JUMP_SPEED;
+        }
+        self.velocity.y = (self.velocity.y - Self::GRAVITY * delta).max(-Self::MAX_FALL_SPEED);
+
+        let offset = self.velocity * delta;
+        let aabb = self.get_box();
+        let colliders = Self::get_colliders(world, &aabb.expand(offset).expand(Vector3::new(0.0, Self::STEP_HEIGHT, 0.0)));
+
+        let mut moved = Self::sweep(&colliders, &aabb, offset);
+        let mut has_stepped = false;
+        if self.is_on_ground && (moved.x != offset.x || moved.z != offset.z) {
+            // Blocked on the ground: try the same move from higher up and settle back down, and keep it if that gets further.
+            let up = Self::sweep(&colliders, &aabb, Vector3::new(0.0, Self::STEP_HEIGHT, 0.0));
+            let across = Self::sweep(&colliders, &aabb.offset(up), Vector3::new(offset.x, 0.0, offset.z));
+            let down = Self::sweep(&colliders, &aabb.offset(up + across), Vector3::new(0.0, -up.y, 0.0));
+
+            let stepped = up + across + down;
+            if Vector3::new(stepped.x, 0.0, stepped.z).magnitude2() > Vector3::new(moved.x, 0.0, moved.z).magnitude2() {
+                moved = stepped;
+                has_stepped = true;
+            }
+        }
+
+        self.is_on_ground = has_stepped || (offset.y < 0.0 && moved.y > offset.y);
+        if moved.x != offset.x {
+            self.velocity.x = 0.0;
+        }
+        if moved.y != offset.y {
+            self.velocity.y = 0.0;
+        }
+        if moved.z != offset.z {
+            self.velocity.z = 0.0;
+        }
+        self.position += moved;
+    }
+}
@@ -0,0 +1,38 @@
+pub type PlayerId = u32;
+
+pub const MAX_REACH_DISTANCE: f32 = 5.0;
+pub const MAX_HEALTH: u8 = 20;
+// One slot for each hotbar key.
+pub const INVENTORY_SLOTS: usize = 11;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    // Walks, takes damage, breaks blocks over time and places only what it carries.
+    Survival,
+    // Flies, breaks blocks at once and places as many as it likes.
+    Creative,
+    // Flies through blocks and only watches.
+    Spectator,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Survival, GameMode::Creative, GameMode::Spectator];
+
+    pub fn get_id(&self) -> u8 {
+        *self as u8
+    }
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            GameMode::Survival => "survival",
+            GameMode::Creative => "creative",
+            GameMode::Spectator => "spectator",
+        }
+    }
+
+    pub fn can_interact(&self) -> bool {
+        *self != GameMode::Spectator
+    }
+}
@@ -0,0 +1,161 @@
+use crate::game::common::coords::{BlockPos, ChunkPos, Coord};
+
+pub struct PacketWriter {
+    bytes: Vec<u8>,
+}
+
+impl PacketWriter {
+    pub fn create() -> Self {
+        Self { bytes: vec![] }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+    pub fn write_u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_i32(&mut self, value: i32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_i64(&mut self, value: i64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+    pub fn write_f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    // Small numbers take a single byte, seven bits are stored per byte and the high bit marks that more follow.
+    pub fn write_var_u32(&mut self, mut value: u32) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.bytes.extend_from_slice(bytes);
+    }
+    pub fn write_string(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn write_block_pos(&mut self, position: &BlockPos) {
+        self.write_i64(position.x);
+        self.write_i64(position.y);
+        self.write_i64(position.z);
+    }
+    pub fn write_chunk_pos(&mut self, position: &ChunkPos) {
+        self.write_i32(position.x);
+        self.write_i32(position.z);
+    }
+    pub fn write_coord(&mut self, coord: &Coord) {
+        self.write_i32(coord.get_chunk_x());
+        self.write_i32(coord.get_chunk_z());
+        self.write_f32(coord.get_local_x());
+        self.write_f64(coord.get_world_y());
+        self.write_f32(coord.get_local_z());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+pub struct PacketReader<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn create(bytes: &'a [u8]) -> Self {
+        Self { bytes, cursor: 0 }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let slice = self.bytes.get(self.cursor..self.cursor + N)?;
+        self.cursor += N;
+
+        slice.try_into().ok()
+    }
+
+    pub fn read_u8(&mut self) -> Option<u8> {
+        Some(self.read_array::<1>()?[0])
+    }
+    pub fn read_u16(&mut self) -> Option<u16> {
+        Some(u16::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_u32(&mut self) -> Option<u32> {
+        Some(u32::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_u64(&mut self) -> Option<u64> {
+        Some(u64::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_i32(&mut self) -> Option<i32> {
+        Some(i32::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_i64(&mut self) -> Option<i64> {
+        Some(i64::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_f32(&mut self) -> Option<f32> {
+        Some(f32::from_be_bytes(self.read_array()?))
+    }
+    pub fn read_f64(&mut self) -> Option<f64> {
+        Some(f64::from_be_bytes(self.read_array()?))
+    }
+
+    pub fn read_var_u32(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7F) as u32).checked_shl(shift)?;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+
+    pub fn read_bytes(&mut self) -> Option<&'a [u8]> {
+        let length = self.read_u32()? as usize;
+        let slice = self.bytes.get(self.cursor..self.cursor + length)?;
+        self.cursor += length;
+
+        Some(slice)
+    }
+    pub fn read_string(&mut self) -> Option<String> {
+        String::from_utf8(self.read_bytes()?.to_vec()).ok()
+    }
+
+    pub fn read_block_pos(&mut self) -> Option<BlockPos> {
+        Some(BlockPos::new(self.read_i64()?, self.read_i64()?, self.read_i64()?))
+    }
+    pub fn read_chunk_pos(&mut self) -> Option<ChunkPos> {
+        Some(ChunkPos::new(self.read_i32()?, self.read_i32()?))
+    }
+    pub fn read_coord(&mut self) -> Option<Coord> {
+        let chunk_x = self.read_i32()?;
+        let chunk_z = self.read_i32()?;
+        let local_x = self.read_f32()?;
+        let y = self.read_f64()?;
+        let local_z = self.read_f32()?;
+
+        Some(Coord::from_local(chunk_x, chunk_z, local_x, y, local_z))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursor >= self.bytes.len()
+    }
+}
@@ -0,0 +1,173 @@
+use std::{io::{ErrorKind, Read, Write}, net::{TcpStream, ToSocketAddrs}, sync::mpsc::{self, Receiver, Sender, TryRecvError}};
+
+// Connections are moved to the server thread, so they have to be Send.
+pub trait Connection: Send {
+    fn send(&mut self, packet: Vec<u8>);
+    fn receive(&mut self) -> Option<Vec<u8>>;
+    fn flush(&mut self);
+
+    fn is_open(&self) -> bool;
+}
+
+// Every packet is framed as a big endian u32 length followed by the packet bytes.
+pub struct TcpConnection {
+    stream: TcpStream,
+
+    read_buffer: Vec<u8>,
+    write_buffer: Vec<u8>,
+
+    open: bool,
+}
+
+impl TcpConnection {
+    pub const MAX_PACKET_SIZE: usize = 4 * 1024 * 1024;
+    // A peer that does not read what is sent to it is dropped once this much is waiting for it.
+    const MAX_WRITE_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+    pub fn create(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            stream,
+
+            read_buffer: vec![],
+            write_buffer: vec![],
+
+            open: true,
+        })
+    }
+    pub fn connect<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        Self::create(TcpStream::connect(address)?)
+    }
+
+    fn close(&mut self) {
+        self.open = false;
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+
+    // Reads at most one full frame ahead, the rest stays in the socket until the buffered packets are taken.
+    fn fill_read_buffer(&mut self) {
+        let mut chunk = [0u8; 16 * 1024];
+        while self.read_buffer.len() < 4 + Self::MAX_PACKET_SIZE {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.close();
+                    break;
+                },
+                Ok(length) => self.read_buffer.extend_from_slice(&chunk[..length]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.close();
+                    break;
+                },
+            }
+        }
+    }
+}
+
+impl Connection for TcpConnection {
+    fn send(&mut self, packet: Vec<u8>) {
+        if !self.open {
+            return;
+        }
+
+        if packet.len() > Self::MAX_PACKET_SIZE || self.write_buffer.len() + 4 + packet.len() > Self::MAX_WRITE_BUFFER_SIZE {
+            self.close();
+            self.write_buffer.clear();
+            return;
+        }
+
+        self.write_buffer.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+        self.write_buffer.extend_from_slice(&packet);
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        if self.open {
+            self.fill_read_buffer();
+        }
+
+        if self.read_buffer.len() < 4 {
+            return None;
+        }
+
+        let length = u32::from_be_bytes(self.read_buffer[..4].try_into().unwrap()) as usize;
+        if length > Self::MAX_PACKET_SIZE {
+            self.close();
+            self.read_buffer.clear();
+            return None;
+        }
+        if self.read_buffer.len() < 4 + length {
+            return None;
+        }
+
+        let packet = self.read_buffer[4..4 + length].to_vec();
+        self.read_buffer.drain(..4 + length);
+
+        Some(packet)
+    }
+
+    fn flush(&mut self) {
+        while self.open && !self.write_buffer.is_empty() {
+            match self.stream.write(&self.write_buffer) {
+                Ok(0) => self.close(),
+                Ok(length) => { self.write_buffer.drain(..length); },
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.close(),
+            }
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+}
+
+// Connects a client and a server in the same process, every packet is handed over as is without framing.
+pub struct ChannelConnection {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+
+    open: bool,
+}
+
+impl ChannelConnection {
+    // Returns both ends, whatever one end sends the other receives.
+    pub fn pair() -> (Self, Self) {
+        let (first_sender, second_receiver) = mpsc::channel();
+        let (second_sender, first_receiver) = mpsc::channel();
+
+        (
+            Self { sender: first_sender, receiver: first_receiver, open: true },
+            Self { sender: second_sender, receiver: second_receiver, open: true },
+        )
+    }
+}
+
+impl Connection for ChannelConnection {
+    fn send(&mut self, packet: Vec<u8>) {
+        if self.open && self.sender.send(packet).is_err() {
+            self.open = false;
+        }
+    }
+
+    fn receive(&mut self) -> Option<Vec<u8>> {
+        // Packets that were sent before the other end went away are still delivered.
+        match self.receiver.try_recv() {
+            Ok(packet) => Some(packet),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.open = false;
+                None
+            },
+        }
+    }
+
+    fn flush(&mut self) {}
+
+    fn is_open(&self) -> bool {
+        self.open
+    }
+}
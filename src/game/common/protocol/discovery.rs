@@ -0,0 +1,41 @@
+use super::buffer::{PacketReader, PacketWriter};
+
+// Servers broadcast an announcement to this port, every client on the network listens on it.
+pub const DISCOVERY_PORT: u16 = 25566;
+
+pub struct LanAnnouncement {
+    pub protocol_version: u32,
+    pub name: String,
+    pub player_count: u32,
+    pub port: u16,
+}
+
+impl LanAnnouncement {
+    const MAGIC: &'static [u8] = b"MFGL";
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+        writer.write_bytes(Self::MAGIC);
+        writer.write_u32(self.protocol_version);
+        writer.write_string(&self.name);
+        writer.write_u32(self.player_count);
+        writer.write_u32(self.port as u32);
+
+        writer.into_bytes()
+    }
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = PacketReader::create(bytes);
+        if reader.read_bytes()? != Self::MAGIC {
+            return None;
+        }
+
+        let announcement = Self {
+            protocol_version: reader.read_u32()?,
+            name: reader.read_string()?,
+            player_count: reader.read_u32()?,
+            port: reader.read_u32()?.try_into().ok()?,
+        };
+
+        if reader.is_empty() { Some(announcement) } else { None }
+    }
+}
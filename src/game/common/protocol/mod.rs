@@ -0,0 +1,9 @@
+pub mod buffer;
+pub mod packet;
+pub mod connection;
+pub mod stream;
+pub mod discovery;
+
+pub const DEFAULT_PORT: u16 = 25565;
+// Bump whenever packets change, clients and servers only talk to the same version.
+pub const PROTOCOL_VERSION: u32 = 10;
@@ -0,0 +1,510 @@
+use crate::game::common::{coords::{BlockPos, ChunkPos, Coord, Direction, LocalBlockPos}, entity::{EntityId, EntityKind}, inventory::Inventory, item::ItemId, player::{GameMode, PlayerId, INVENTORY_SLOTS}, weather::Weather, world::block_entity::BlockEntity};
+
+use super::buffer::{PacketReader, PacketWriter};
+
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BlockEditRejection {
+    TooFar,
+    NoLineOfSight,
+    InvalidTarget,
+    RateLimited,
+    UnloadedChunk,
+    // Spectators can only watch.
+    NotAllowed,
+    MissingItem,
+    TooFast,
+}
+
+impl BlockEditRejection {
+    const ALL: [BlockEditRejection; 8] = [
+        BlockEditRejection::TooFar,
+        BlockEditRejection::NoLineOfSight,
+        BlockEditRejection::InvalidTarget,
+        BlockEditRejection::RateLimited,
+        BlockEditRejection::UnloadedChunk,
+        BlockEditRejection::NotAllowed,
+        BlockEditRejection::MissingItem,
+        BlockEditRejection::TooFast,
+    ];
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+
+    pub fn get_reason(&self) -> &'static str {
+        match self {
+            BlockEditRejection::TooFar => "target is out of reach",
+            BlockEditRejection::NoLineOfSight => "target is not in line of sight",
+            BlockEditRejection::InvalidTarget => "target can not be edited",
+            BlockEditRejection::RateLimited => "editing too fast",
+            BlockEditRejection::UnloadedChunk => "target chunk is not loaded",
+            BlockEditRejection::NotAllowed => "not allowed in this game mode",
+            BlockEditRejection::MissingItem => "item is not in the inventory",
+            BlockEditRejection::TooFast => "block was not mined long enough",
+        }
+    }
+}
+
+// Handshake and Disconnect keep their id and layout in every protocol version,
+// so a client and server of different versions can still tell each other why they can not play together.
+#[derive(Clone)]
+pub enum ClientPacket {
+    Handshake { protocol_version: u32, registry_hash: u64 },
+    Login { name: String, view_distance: u8 },
+    PlayerMove { position: Coord, yaw: f32, pitch: f32, is_on_ground: bool },
+    BreakBlock { sequence: u32, position: BlockPos },
+    PlaceBlock { sequence: u32, position: BlockPos, face: Direction, block: u8 },
+    ChatMessage { message: String },
+    TabComplete { text: String },
+    // Right clicking a block that does something, like flipping a lever.
+    UseBlock { position: BlockPos },
+    // Right clicking a face of a block with an item that is not a block, like tilling dirt with a hoe.
+    UseItem { position: BlockPos, face: Direction, item: ItemId },
+    // A survival player started mining the block, BreakBlock follows once it is mined.
+    StartBreaking { position: BlockPos },
+}
+
+impl ClientPacket {
+    pub fn get_id(&self) -> u8 {
+        match self {
+            ClientPacket::Handshake { .. } => 0,
+            ClientPacket::Login { .. } => 1,
+            ClientPacket::PlayerMove { .. } => 2,
+            ClientPacket::BreakBlock { .. } => 3,
+            ClientPacket::PlaceBlock { .. } => 4,
+            ClientPacket::ChatMessage { .. } => 5,
+            ClientPacket::TabComplete { .. } => 6,
+            ClientPacket::UseBlock { .. } => 7,
+            ClientPacket::UseItem { .. } => 8,
+            ClientPacket::StartBreaking { .. } => 9,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+        writer.write_u8(self.get_id());
+
+        match self {
+            ClientPacket::Handshake { protocol_version, registry_hash } => {
+                writer.write_u32(*protocol_version);
+                writer.write_u64(*registry_hash);
+            },
+            ClientPacket::Login { name, view_distance } => {
+                writer.write_string(name);
+                writer.write_u8(*view_distance);
+            },
+            ClientPacket::PlayerMove { position, yaw, pitch, is_on_ground } => {
+                writer.write_coord(position);
+                writer.write_f32(*yaw);
+                writer.write_f32(*pitch);
+                writer.write_u8(*is_on_ground as u8);
+            },
+            ClientPacket::BreakBlock { sequence, position } => {
+                writer.write_u32(*sequence);
+                writer.write_block_pos(position);
+            },
+            ClientPacket::PlaceBlock { sequence, position, face, block } => {
+                writer.write_u32(*sequence);
+                writer.write_block_pos(position);
+                writer.write_u8(*face as u8);
+                writer.write_u8(*block);
+            },
+            ClientPacket::ChatMessage { message } => {
+                writer.write_string(message);
+            },
+            ClientPacket::TabComplete { text } => {
+                writer.write_string(text);
+            },
+            ClientPacket::UseBlock { position } => {
+                writer.write_block_pos(position);
+            },
+            ClientPacket::UseItem { position, face, item } => {
+                writer.write_block_pos(position);
+                writer.write_u8(*face as u8);
+                writer.write_u16(*item);
+            },
+            ClientPacket::StartBreaking { position } => {
+                writer.write_block_pos(position);
+            },
+        }
+
+        writer.into_bytes()
+    }
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = PacketReader::create(bytes);
+
+        let packet = match reader.read_u8()? {
+            0 => ClientPacket::Handshake {
+                protocol_version: reader.read_u32()?,
+                registry_hash: reader.read_u64()?,
+            },
+            1 => ClientPacket::Login {
+                name: reader.read_string()?,
+                view_distance: reader.read_u8()?,
+            },
+            2 => ClientPacket::PlayerMove {
+                position: reader.read_coord()?,
+                yaw: reader.read_f32()?,
+                pitch: reader.read_f32()?,
+                is_on_ground: reader.read_u8()? != 0,
+            },
+            3 => ClientPacket::BreakBlock {
+                sequence: reader.read_u32()?,
+                position: reader.read_block_pos()?,
+            },
+            4 => ClientPacket::PlaceBlock {
+                sequence: reader.read_u32()?,
+                position: reader.read_block_pos()?,
+                face: Direction::from_id(reader.read_u8()?)?,
+                block: reader.read_u8()?,
+            },
+            5 => ClientPacket::ChatMessage {
+                message: reader.read_string()?,
+            },
+            6 => ClientPacket::TabComplete {
+                text: reader.read_string()?,
+            },
+            7 => ClientPacket::UseBlock {
+                position: reader.read_block_pos()?,
+            },
+            8 => ClientPacket::UseItem {
+                position: reader.read_block_pos()?,
+                face: Direction::from_id(reader.read_u8()?)?,
+                item: reader.read_u16()?,
+            },
+            9 => ClientPacket::StartBreaking {
+                position: reader.read_block_pos()?,
+            },
+            _ => return None,
+        };
+
+        if reader.is_empty() { Some(packet) } else { None }
+    }
+}
+
+#[derive(Clone)]
+pub enum ServerPacket {
+    Handshake { protocol_version: u32, registry_hash: u64, compression_threshold: u32 },
+    Disconnect { reason: String },
+    LoginSuccess { player_id: PlayerId, position: Coord },
+    ChunkData { position: ChunkPos, blocks: Vec<u8>, block_entities: Vec<(LocalBlockPos, BlockEntity)> },
+    UnloadChunk { position: ChunkPos },
+    // Changes in one chunk section during a tick, as indices into the section sorted from low to high.
+    MultiBlockChange { position: ChunkPos, section: u8, changes: Vec<(u16, u8)> },
+    BlockEditRejected { sequence: u32, position: BlockPos, block: u8, reason: BlockEditRejection },
+    PlayerJoined { player_id: PlayerId, name: String, position: Coord, yaw: f32, pitch: f32 },
+    PlayerLeft { player_id: PlayerId },
+    PlayerMoved { player_id: PlayerId, position: Coord, yaw: f32, pitch: f32 },
+    ChatMessage { message: String },
+    TabCompletions { completions: Vec<String> },
+    Teleport { position: Coord },
+    // Replaces a whole section, sent instead of MultiBlockChange when most of it changed.
+    SectionData { position: ChunkPos, section: u8, blocks: Vec<u8> },
+    // Every edit up to this sequence number that was not rejected has been applied.
+    BlockEditAcknowledged { sequence: u32 },
+    SpawnEntity { entity_id: EntityId, kind: EntityKind, position: Coord },
+    EntityMoved { entity_id: EntityId, position: Coord },
+    RemoveEntity { entity_id: EntityId },
+    // The world time in ticks, sent on login, every second and when a command changes it.
+    TimeUpdate { time: u64 },
+    // Sent on login and whenever the weather changes.
+    WeatherChanged { weather: Weather },
+    // A block entity was created or changed. Removed ones go away with their block.
+    BlockEntityData { position: BlockPos, entity: BlockEntity },
+    // Sent on login and whenever they change, for the receiving player only.
+    GameModeChanged { mode: GameMode },
+    HealthChanged { health: u8 },
+    InventoryChanged { inventory: Inventory },
+}
+
+impl ServerPacket {
+    const NAMES: [&'static str; 24] = [
+        "Handshake", "Disconnect", "LoginSuccess",
+        "ChunkData", "UnloadChunk",
+        "MultiBlockChange", "BlockEditRejected",
+        "PlayerJoined", "PlayerLeft", "PlayerMoved",
+        "ChatMessage", "TabCompletions", "Teleport",
+        "SectionData", "BlockEditAcknowledged",
+        "SpawnEntity", "EntityMoved", "RemoveEntity",
+        "TimeUpdate", "WeatherChanged", "BlockEntityData",
+        "GameModeChanged", "HealthChanged", "InventoryChanged",
+    ];
+
+    pub fn get_name(id: u8) -> &'static str {
+        Self::NAMES.get(id as usize).copied().unwrap_or("Unknown")
+    }
+
+    pub fn get_id(&self) -> u8 {
+        match self {
+            ServerPacket::Handshake { .. } => 0,
+            ServerPacket::Disconnect { .. } => 1,
+            ServerPacket::LoginSuccess { .. } => 2,
+            ServerPacket::ChunkData { .. } => 3,
+            ServerPacket::UnloadChunk { .. } => 4,
+            ServerPacket::MultiBlockChange { .. } => 5,
+            ServerPacket::BlockEditRejected { .. } => 6,
+            ServerPacket::PlayerJoined { .. } => 7,
+            ServerPacket::PlayerLeft { .. } => 8,
+            ServerPacket::PlayerMoved { .. } => 9,
+            ServerPacket::ChatMessage { .. } => 10,
+            ServerPacket::TabCompletions { .. } => 11,
+            ServerPacket::Teleport { .. } => 12,
+            ServerPacket::SectionData { .. } => 13,
+            ServerPacket::BlockEditAcknowledged { .. } => 14,
+            ServerPacket::SpawnEntity { .. } => 15,
+            ServerPacket::EntityMoved { .. } => 16,
+            ServerPacket::RemoveEntity { .. } => 17,
+            ServerPacket::TimeUpdate { .. } => 18,
+            ServerPacket::WeatherChanged { .. } => 19,
+            ServerPacket::BlockEntityData { .. } => 20,
+            ServerPacket::GameModeChanged { .. } => 21,
+            ServerPacket::HealthChanged { .. } => 22,
+            ServerPacket::InventoryChanged { .. } => 23,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+        writer.write_u8(self.get_id());
+
+        match self {
+            ServerPacket::Handshake { protocol_version, registry_hash, compression_threshold } => {
+                writer.write_u32(*protocol_version);
+                writer.write_u64(*registry_hash);
+                writer.write_u32(*compression_threshold);
+            },
+            ServerPacket::Disconnect { reason } => {
+                writer.write_string(reason);
+            },
+            ServerPacket::LoginSuccess { player_id, position } => {
+                writer.write_u32(*player_id);
+                writer.write_coord(position);
+            },
+            ServerPacket::ChunkData { position, blocks, block_entities } => {
+                writer.write_chunk_pos(position);
+                writer.write_bytes(blocks);
+                writer.write_var_u32(block_entities.len() as u32);
+                for (position, entity) in block_entities {
+                    writer.write_u8(position.x as u8);
+                    writer.write_u8(position.y as u8);
+                    writer.write_u8(position.z as u8);
+                    entity.write(&mut writer);
+                }
+            },
+            ServerPacket::UnloadChunk { position } => {
+                writer.write_chunk_pos(position);
+            },
+            ServerPacket::MultiBlockChange { position, section, changes } => {
+                writer.write_chunk_pos(position);
+                writer.write_u8(*section);
+                writer.write_var_u32(changes.len() as u32);
+
+                // Each index is stored as the distance to the previous one, which usually fits in a byte.
+                let mut previous = 0;
+                for (index, block) in changes {
+                    writer.write_var_u32(index.wrapping_sub(previous) as u32);
+                    writer.write_u8(*block);
+                    previous = *index;
+                }
+            },
+            ServerPacket::BlockEditRejected { sequence, position, block, reason } => {
+                writer.write_u32(*sequence);
+                writer.write_block_pos(position);
+                writer.write_u8(*block);
+                writer.write_u8(*reason as u8);
+            },
+            ServerPacket::PlayerJoined { player_id, name, position, yaw, pitch } => {
+                writer.write_u32(*player_id);
+                writer.write_string(name);
+                writer.write_coord(position);
+                writer.write_f32(*yaw);
+                writer.write_f32(*pitch);
+            },
+            ServerPacket::PlayerLeft { player_id } => {
+                writer.write_u32(*player_id);
+            },
+            ServerPacket::PlayerMoved { player_id, position, yaw, pitch } => {
+                writer.write_u32(*player_id);
+                writer.write_coord(position);
+                writer.write_f32(*yaw);
+                writer.write_f32(*pitch);
+            },
+            ServerPacket::ChatMessage { message } => {
+                writer.write_string(message);
+            },
+            ServerPacket::TabCompletions { completions } => {
+                writer.write_u32(completions.len() as u32);
+                for completion in completions {
+                    writer.write_string(completion);
+                }
+            },
+            ServerPacket::Teleport { position } => {
+                writer.write_coord(position);
+            },
+            ServerPacket::SectionData { position, section, blocks } => {
+                writer.write_chunk_pos(position);
+                writer.write_u8(*section);
+                writer.write_bytes(blocks);
+            },
+            ServerPacket::BlockEditAcknowledged { sequence } => {
+                writer.write_u32(*sequence);
+            },
+            ServerPacket::SpawnEntity { entity_id, kind, position } => {
+                writer.write_u32(*entity_id);
+                writer.write_u8(kind.get_id());
+                writer.write_u16(kind.get_data());
+                writer.write_coord(position);
+            },
+            ServerPacket::EntityMoved { entity_id, position } => {
+                writer.write_u32(*entity_id);
+                writer.write_coord(position);
+            },
+            ServerPacket::RemoveEntity { entity_id } => {
+                writer.write_u32(*entity_id);
+            },
+            ServerPacket::TimeUpdate { time } => {
+                writer.write_u64(*time);
+            },
+            ServerPacket::WeatherChanged { weather } => {
+                writer.write_u8(weather.get_id());
+            },
+            ServerPacket::BlockEntityData { position, entity } => {
+                writer.write_block_pos(position);
+                entity.write(&mut writer);
+            },
+            ServerPacket::GameModeChanged { mode } => {
+                writer.write_u8(mode.get_id());
+            },
+            ServerPacket::HealthChanged { health } => {
+                writer.write_u8(*health);
+            },
+            ServerPacket::InventoryChanged { inventory } => {
+                inventory.write(&mut writer);
+            },
+        }
+
+        writer.into_bytes()
+    }
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = PacketReader::create(bytes);
+
+        let packet = match reader.read_u8()? {
+            0 => ServerPacket::Handshake {
+                protocol_version: reader.read_u32()?,
+                registry_hash: reader.read_u64()?,
+                compression_threshold: reader.read_u32()?,
+            },
+            1 => ServerPacket::Disconnect {
+                reason: reader.read_string()?,
+            },
+            2 => ServerPacket::LoginSuccess {
+                player_id: reader.read_u32()?,
+                position: reader.read_coord()?,
+            },
+            3 => {
+                let position = reader.read_chunk_pos()?;
+                let blocks = reader.read_bytes()?.to_vec();
+
+                let mut block_entities = vec![];
+                for _ in 0..reader.read_var_u32()? {
+                    let local = LocalBlockPos::new(reader.read_u8()? as usize, reader.read_u8()? as usize, reader.read_u8()? as usize);
+                    block_entities.push((local, BlockEntity::read(&mut reader, false)?));
+                }
+
+                ServerPacket::ChunkData { position, blocks, block_entities }
+            },
+            4 => ServerPacket::UnloadChunk {
+                position: reader.read_chunk_pos()?,
+            },
+            5 => {
+                let position = reader.read_chunk_pos()?;
+                let section = reader.read_u8()?;
+                let count = reader.read_var_u32()?;
+
+                let mut changes = vec![];
+                let mut previous = 0u32;
+                for _ in 0..count {
+                    let index = previous.checked_add(reader.read_var_u32()?)?;
+                    changes.push((u16::try_from(index).ok()?, reader.read_u8()?));
+                    previous = index;
+                }
+
+                ServerPacket::MultiBlockChange { position, section, changes }
+            },
+            6 => ServerPacket::BlockEditRejected {
+                sequence: reader.read_u32()?,
+                position: reader.read_block_pos()?,
+                block: reader.read_u8()?,
+                reason: BlockEditRejection::from_id(reader.read_u8()?)?,
+            },
+            7 => ServerPacket::PlayerJoined {
+                player_id: reader.read_u32()?,
+                name: reader.read_string()?,
+                position: reader.read_coord()?,
+                yaw: reader.read_f32()?,
+                pitch: reader.read_f32()?,
+            },
+            8 => ServerPacket::PlayerLeft {
+                player_id: reader.read_u32()?,
+            },
+            9 => ServerPacket::PlayerMoved {
+                player_id: reader.read_u32()?,
+                position: reader.read_coord()?,
+                yaw: reader.read_f32()?,
+                pitch: reader.read_f32()?,
+            },
+            10 => ServerPacket::ChatMessage {
+                message: reader.read_string()?,
+            },
+            11 => ServerPacket::TabCompletions {
+                completions: (0..reader.read_u32()?).map(|_| reader.read_string()).collect::<Option<Vec<String>>>()?,
+            },
+            12 => ServerPacket::Teleport {
+                position: reader.read_coord()?,
+            },
+            13 => ServerPacket::SectionData {
+                position: reader.read_chunk_pos()?,
+                section: reader.read_u8()?,
+                blocks: reader.read_bytes()?.to_vec(),
+            },
+            14 => ServerPacket::BlockEditAcknowledged {
+                sequence: reader.read_u32()?,
+            },
+            15 => ServerPacket::SpawnEntity {
+                entity_id: reader.read_u32()?,
+                kind: EntityKind::from_id(reader.read_u8()?, reader.read_u16()?)?,
+                position: reader.read_coord()?,
+            },
+            16 => ServerPacket::EntityMoved {
+                entity_id: reader.read_u32()?,
+                position: reader.read_coord()?,
+            },
+            17 => ServerPacket::RemoveEntity {
+                entity_id: reader.read_u32()?,
+            },
+            18 => ServerPacket::TimeUpdate {
+                time: reader.read_u64()?,
+            },
+            19 => ServerPacket::WeatherChanged {
+                weather: Weather::from_id(reader.read_u8()?)?,
+            },
+            20 => ServerPacket::BlockEntityData {
+                position: reader.read_block_pos()?,
+                entity: BlockEntity::read(&mut reader, false)?,
+            },
+            21 => ServerPacket::GameModeChanged {
+                mode: GameMode::from_id(reader.read_u8()?)?,
+            },
+            22 => ServerPacket::HealthChanged {
+                health: reader.read_u8()?,
+            },
+            23 => ServerPacket::InventoryChanged {
+                inventory: Inventory::read(&mut reader, INVENTORY_SLOTS, false)?,
+            },
+            _ => return None,
+        };
+
+        if reader.is_empty() { Some(packet) } else { None }
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, io::{Read, Write}};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use super::connection::Connection;
+
+#[derive(Clone, Copy, Default)]
+pub struct PacketStats {
+    pub count: u64,
+    pub raw_bytes: u64,
+    pub sent_bytes: u64,
+}
+
+// Bytes sent per packet id, before and after compression.
+#[derive(Clone, Default)]
+pub struct CompressionStats {
+    packets: BTreeMap<u8, PacketStats>,
+}
+
+impl CompressionStats {
+    pub fn record(&mut self, id: u8, raw_bytes: usize, sent_bytes: usize) {
+        let stats = self.packets.entry(id).or_default();
+        stats.count += 1;
+        stats.raw_bytes += raw_bytes as u64;
+        stats.sent_bytes += sent_bytes as u64;
+    }
+    pub fn merge(&mut self, other: &CompressionStats) {
+        for (id, other_stats) in &other.packets {
+            let stats = self.packets.entry(*id).or_default();
+            stats.count += other_stats.count;
+            stats.raw_bytes += other_stats.raw_bytes;
+            stats.sent_bytes += other_stats.sent_bytes;
+        }
+    }
+
+    pub fn get_packets(&self) -> &BTreeMap<u8, PacketStats> {
+        &self.packets
+    }
+}
+
+// Sits on top of a connection and compresses packets once the handshake enabled it.
+// Compressed frames start with the uncompressed length, or 0 if the packet was too small to be worth compressing.
+pub struct PacketStream {
+    connection: Box<dyn Connection>,
+    compression_threshold: Option<usize>,
+    stats: CompressionStats,
+    // Packets sent since the last take_sent, only kept while a session is recorded.
+    sent_log: Option<Vec<Vec<u8>>>,
+
+    failed: bool,
+}
+
+impl PacketStream {
+    pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 256;
+    const MAX_PACKET_SIZE: usize = 4 * 1024 * 1024;
+
+    pub fn create(connection: Box<dyn Connection>) -> Self {
+        Self {
+            connection,
+            compression_threshold: None,
+            stats: CompressionStats::default(),
+            sent_log: None,
+
+            failed: false,
+        }
+    }
+
+    pub fn enable_compression(&mut self, threshold: usize) {
+        self.compression_threshold = Some(threshold);
+    }
+    pub fn get_stats(&self) -> &CompressionStats {
+        &self.stats
+    }
+
+    pub fn log_sent_packets(&mut self) {
+        self.sent_log.get_or_insert_with(Vec::new);
+    }
+    pub fn take_sent(&mut self) -> Vec<Vec<u8>> {
+        self.sent_log.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    fn compress(packet: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        // Writing into a Vec can not fail.
+        encoder.write_all(packet).unwrap();
+        encoder.finish().unwrap()
+    }
+    fn decompress(frame: &[u8]) -> Option<Vec<u8>> {
+        let length = u32::from_be_bytes(frame.get(..4)?.try_into().ok()?) as usize;
+        let data = &frame[4..];
+
+        if length == 0 {
+            return Some(data.to_vec());
+        }
+        if length > Self::MAX_PACKET_SIZE {
+            return None;
+        }
+
+        let mut packet = Vec::with_capacity(length);
+        ZlibDecoder::new(data).take(length as u64 + 1).read_to_end(&mut packet).ok()?;
+
+        if packet.len() == length { Some(packet) } else { None }
+    }
+
+    pub fn send(&mut self, packet: Vec<u8>) {
+        let raw_length = packet.len();
+        let id = packet.first().copied().unwrap_or_default();
+        if let Some(log) = &mut self.sent_log {
+            log.push(packet.clone());
+        }
+
+        let frame = match self.compression_threshold {
+            None => packet,
+            Some(threshold) => {
+                let compressed = if raw_length >= threshold { Some(Self::compress(&packet)) } else { None };
+
+                // Random data can grow when compressed, then it is cheaper to send it as is.
+                let (length, data) = match compressed {
+                    Some(compressed) if compressed.len() < raw_length => (raw_length as u32, compressed),
+                    _ => (0, packet),
+                };
+
+                let mut frame = length.to_be_bytes().to_vec();
+                frame.extend_from_slice(&data);
+                frame
+            },
+        };
+
+        self.stats.record(id, raw_length, frame.len());
+        self.connection.send(frame);
+    }
+    pub fn receive(&mut self) -> Option<Vec<u8>> {
+        if self.failed {
+            return None;
+        }
+
+        let frame = self.connection.receive()?;
+        if self.compression_threshold.is_none() {
+            return Some(frame);
+        }
+
+        let packet = Self::decompress(&frame);
+        if packet.is_none() {
+            // The stream can not be trusted after a broken frame, so stop reading it.
+            self.failed = true;
+        }
+
+        packet
+    }
+    pub fn flush(&mut self) {
+        self.connection.flush();
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.failed && self.connection.is_open()
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Rain,
+    // Rain with a darker sky.
+    Thunder,
+}
+
+impl Weather {
+    pub const ALL: [Weather; 3] = [Weather::Clear, Weather::Rain, Weather::Thunder];
+
+    pub fn get_id(&self) -> u8 {
+        *self as u8
+    }
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.get(id as usize).copied()
+    }
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Thunder => "thunder",
+        }
+    }
+
+    pub fn is_raining(&self) -> bool {
+        *self != Weather::Clear
+    }
+}
@@ -0,0 +1,58 @@
+use crate::game::common::{inventory::Inventory, protocol::buffer::{PacketReader, PacketWriter}};
+
+use super::block_types::BlockTypes;
+
+// Data a block needs beyond its id, like the contents of a chest. It belongs to the block at its position:
+// it is created when the block is placed and removed together with it.
+#[derive(Clone, PartialEq, Eq)]
+pub enum BlockEntity {
+    Chest { inventory: Inventory },
+    Sign { text: String },
+}
+
+impl BlockEntity {
+    pub const CHEST_SLOTS: usize = 27;
+    // Longer text does not fit on the side of a sign.
+    pub const MAX_SIGN_LENGTH: usize = 24;
+
+    // The block entity a freshly placed block starts with, None for blocks without one.
+    pub fn create(block: u8) -> Option<Self> {
+        match block {
+            block if block == BlockTypes::Chest as u8 => Some(BlockEntity::Chest { inventory: Inventory::create(Self::CHEST_SLOTS) }),
+            block if block == BlockTypes::Sign as u8 => Some(BlockEntity::Sign { text: String::new() }),
+            _ => None,
+        }
+    }
+
+    pub fn get_id(&self) -> u8 {
+        match self {
+            BlockEntity::Chest { .. } => 0,
+            BlockEntity::Sign { .. } => 1,
+        }
+    }
+    // Whether the block entity belongs with the block, a chest stays a chest when its block is set to a chest again.
+    pub fn fits(&self, block: u8) -> bool {
+        Self::create(block).is_some_and(|entity| entity.get_id() == self.get_id())
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        writer.write_u8(self.get_id());
+
+        match self {
+            BlockEntity::Chest { inventory } => {
+                inventory.write(writer);
+            },
+            BlockEntity::Sign { text } => {
+                writer.write_string(text);
+            },
+        }
+    }
+    // Chunks saved while items were only blocks store item ids in a single byte.
+    pub fn read(reader: &mut PacketReader, has_block_item_ids: bool) -> Option<Self> {
+        match reader.read_u8()? {
+            0 => Some(BlockEntity::Chest { inventory: Inventory::read(reader, Self::CHEST_SLOTS, has_block_item_ids)? }),
+            1 => Some(BlockEntity::Sign { text: reader.read_string()?.chars().take(Self::MAX_SIGN_LENGTH).collect() }),
+            _ => None,
+        }
+    }
+}
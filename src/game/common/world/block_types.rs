@@ -1,7 +1,192 @@
+use super::{liquid::Liquid, signal::SignalBlock};
+
 #[repr(u8)]
 pub enum BlockTypes {
     Air,
     Dirt, GrassBlock,
     Stone, Cobblestone, Bedrock,
-    _Gravel, _Sand,
-}
\ No newline at end of file
+    Gravel, Sand,
+    // The ids after a liquid source are its flow levels, see Liquid.
+    Water,
+    Lava = 17,
+    // The ids after a signal block are its other states, see SignalBlock.
+    Lever = 26,
+    Button = 28,
+    Wire = 30,
+    Lamp = 46,
+    Door = 48,
+    Repeater = 52,
+    Inverter = 84,
+    Log = 92, Leaves, Planks,
+    // The ids after fire are its ages, see get_fire_age.
+    Fire,
+    Chest = 111, Sign,
+    // Farmland is dry, the id after it moist. The ids after wheat are its growth stages, see get_wheat_age.
+    Farmland = 113,
+    Wheat = 115,
+}
+
+impl BlockTypes {
+    // Names used by commands, indexed by block id.
+    pub const NAMES: [&'static str; 123] = [
+        "air",
+        "dirt", "grass_block",
+        "stone", "cobblestone", "bedrock",
+        "gravel", "sand",
+        "water", "flowing_water_1", "flowing_water_2", "flowing_water_3", "flowing_water_4",
+        "flowing_water_5", "flowing_water_6", "flowing_water_7", "falling_water",
+        "lava", "flowing_lava_1", "flowing_lava_2", "flowing_lava_3", "flowing_lava_4",
+        "flowing_lava_5", "flowing_lava_6", "flowing_lava_7", "falling_lava",
+        "lever", "lever_on", "button", "button_pressed",
+        "wire", "wire_1", "wire_2", "wire_3", "wire_4", "wire_5", "wire_6", "wire_7",
+        "wire_8", "wire_9", "wire_10", "wire_11", "wire_12", "wire_13", "wire_14", "wire_15",
+        "lamp", "lit_lamp", "door", "open_door", "powered_door", "powered_open_door",
+        "repeater_west_1", "powered_repeater_west_1", "repeater_west_2", "powered_repeater_west_2",
+        "repeater_west_3", "powered_repeater_west_3", "repeater_west_4", "powered_repeater_west_4",
+        "repeater_east_1", "powered_repeater_east_1", "repeater_east_2", "powered_repeater_east_2",
+        "repeater_east_3", "powered_repeater_east_3", "repeater_east_4", "powered_repeater_east_4",
+        "repeater_north_1", "powered_repeater_north_1", "repeater_north_2", "powered_repeater_north_2",
+        "repeater_north_3", "powered_repeater_north_3", "repeater_north_4", "powered_repeater_north_4",
+        "repeater_south_1", "powered_repeater_south_1", "repeater_south_2", "powered_repeater_south_2",
+        "repeater_south_3", "powered_repeater_south_3", "repeater_south_4", "powered_repeater_south_4",
+        "inverter_west", "lit_inverter_west", "inverter_east", "lit_inverter_east", "inverter_north", "lit_inverter_north", "inverter_south", "lit_inverter_south",
+        "log", "leaves", "planks",
+        "fire", "fire_1", "fire_2", "fire_3", "fire_4", "fire_5", "fire_6", "fire_7",
+        "fire_8", "fire_9", "fire_10", "fire_11", "fire_12", "fire_13", "fire_14", "fire_15",
+        "chest", "sign",
+        "farmland", "moist_farmland",
+        "wheat", "wheat_1", "wheat_2", "wheat_3", "wheat_4", "wheat_5", "wheat_6", "wheat_7",
+    ];
+
+    pub fn from_name(name: &str) -> Option<u8> {
+        Self::NAMES.iter().position(|block_name| block_name.eq_ignore_ascii_case(name)).map(|id| id as u8)
+    }
+    pub fn get_name(block: u8) -> &'static str {
+        Self::NAMES.get(block as usize).copied().unwrap_or("unknown")
+    }
+
+    pub fn is_liquid(block: u8) -> bool {
+        Liquid::from_block(block).is_some()
+    }
+    // Blocks that stop entities and rays, everything but air, liquids, fire and crops.
+    pub fn is_solid(block: u8) -> bool {
+        block != BlockTypes::Air as u8 && !Self::is_liquid(block) && Self::get_fire_age(block).is_none() && Self::get_wheat_age(block).is_none()
+    }
+    // Solid blocks a player's body bumps into. Open doors and the signal blocks smaller than a block are walked through.
+    pub fn has_collision(block: u8) -> bool {
+        match SignalBlock::from_block(block) {
+            Some(SignalBlock::Door { is_open, .. }) => !is_open,
+            Some(SignalBlock::Lamp { .. }) => true,
+            Some(_) => false,
+            None => Self::is_solid(block) && block != BlockTypes::Sign as u8,
+        }
+    }
+    // Blocks players can point at and break, crops can be harvested though they are not solid.
+    pub fn is_selectable(block: u8) -> bool {
+        Self::is_solid(block) || Self::get_wheat_age(block).is_some()
+    }
+    // Blocks that a placed or landing block takes the place of.
+    pub fn is_replaceable(block: u8) -> bool {
+        !Self::is_solid(block)
+    }
+
+    // How much light passing through the block loses, 15 stops it.
+    pub fn get_light_opacity(block: u8) -> u8 {
+        if let Some(SignalBlock::Door { is_open: true, .. }) = SignalBlock::from_block(block) {
+            return 0;
+        }
+        match Liquid::from_block(block) {
+            _ if Self::is_solid(block) => 15,
+            Some((Liquid::Water, _)) => 2,
+            _ => 0,
+        }
+    }
+    pub fn get_light_emission(block: u8) -> u8 {
+        match (Liquid::from_block(block), SignalBlock::from_block(block)) {
+            (Some((Liquid::Lava, _)), _) => 15,
+            (_, Some(SignalBlock::Lamp { is_lit: true })) => 15,
+            (_, Some(SignalBlock::Inverter { is_on: true, .. })) => 7,
+            _ if Self::get_fire_age(block).is_some() => 15,
+            _ => 0,
+        }
+    }
+
+    pub const MAX_FIRE_AGE: u8 = 15;
+
+    pub fn get_fire(age: u8) -> u8 {
+        BlockTypes::Fire as u8 + age.min(Self::MAX_FIRE_AGE)
+    }
+    // How long the fire has been burning, or None for any other block.
+    pub fn get_fire_age(block: u8) -> Option<u8> {
+        block.checked_sub(BlockTypes::Fire as u8).filter(|age| *age <= Self::MAX_FIRE_AGE)
+    }
+
+    pub const MOIST_FARMLAND: u8 = BlockTypes::Farmland as u8 + 1;
+
+    pub fn is_farmland(block: u8) -> bool {
+        block == BlockTypes::Farmland as u8 || block == Self::MOIST_FARMLAND
+    }
+
+    pub const MAX_WHEAT_AGE: u8 = 7;
+
+    pub fn get_wheat(age: u8) -> u8 {
+        BlockTypes::Wheat as u8 + age.min(Self::MAX_WHEAT_AGE)
+    }
+    // The growth stage of wheat, or None for any other block.
+    pub fn get_wheat_age(block: u8) -> Option<u8> {
+        block.checked_sub(BlockTypes::Wheat as u8).filter(|age| *age <= Self::MAX_WHEAT_AGE)
+    }
+
+    // Out of 100, how readily fire next to the block spreads to it. Zero for blocks that don't burn.
+    pub fn get_ignite_chance(block: u8) -> u8 {
+        match block {
+            block if block == BlockTypes::Log as u8 => 5,
+            block if block == BlockTypes::Leaves as u8 => 30,
+            block if block == BlockTypes::Planks as u8 => 5,
+            _ => 0,
+        }
+    }
+    // Out of 300, how likely the block burns away on each tick of a fire next to it.
+    pub fn get_burn_chance(block: u8) -> u8 {
+        match block {
+            block if block == BlockTypes::Log as u8 => 5,
+            block if block == BlockTypes::Leaves as u8 => 60,
+            block if block == BlockTypes::Planks as u8 => 20,
+            _ => 0,
+        }
+    }
+
+    // Ticks it takes to break the block by hand in survival, None for blocks that can't be broken.
+    // Wheat and the small signal parts break at once.
+    pub fn get_break_ticks(block: u8) -> Option<u64> {
+        if !Self::is_selectable(block) || block == BlockTypes::Bedrock as u8 {
+            return None;
+        }
+
+        Some(match block {
+            block if block == BlockTypes::Stone as u8 || block == BlockTypes::Cobblestone as u8 => 30,
+            block if block == BlockTypes::Log as u8 || block == BlockTypes::Planks as u8 => 20,
+            block if block == BlockTypes::Chest as u8 || block == BlockTypes::Sign as u8 => 20,
+            block if block == BlockTypes::Leaves as u8 => 4,
+            block if Self::get_wheat_age(block).is_some() => 0,
+            block => match SignalBlock::from_block(block) {
+                Some(SignalBlock::Lamp { .. } | SignalBlock::Door { .. }) => 15,
+                Some(_) => 0,
+                None => 10,
+            },
+        })
+    }
+
+    // FNV-1a over the block names in id order, so both sides of a connection can check they agree on block ids.
+    pub fn get_registry_hash() -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for name in Self::NAMES {
+            for byte in name.bytes().chain(std::iter::once(0)) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+
+        hash
+    }
+}
@@ -0,0 +1,5 @@
+use crate::game::common::coords::BlockPos;
+
+pub trait BlockView {
+    fn get_block(&self, position: &BlockPos) -> u8;
+}
@@ -0,0 +1,12 @@
+// Light levels go from 0 to 15. Sky light comes down from above, block light from blocks that glow.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LightChannel {
+    Sky,
+    Block,
+}
+
+impl LightChannel {
+    pub const ALL: [LightChannel; 2] = [LightChannel::Sky, LightChannel::Block];
+}
+
+pub const MAX_LIGHT: u8 = 15;
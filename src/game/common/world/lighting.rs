@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::game::{common::{coords::{BlockAxis, BlockPos, ChunkPos, Direction, LocalBlockAxis, LocalBlockPos}, world::{block_types::BlockTypes, light::{LightChannel, MAX_LIGHT}, mapping::ChunkPosHasherBuilder}}, server::world::chunk::Chunk};
+
+// Light spreads from block to block like a flood fill and loses one level per step, more through blocks like water.
+// Sky light shining straight down through clear blocks keeps its full strength.
+// Chunks that are not loaded are neither lit nor light others, once they arrive their light flows across the border.
+// The server and the clients light their chunks with the same code, so both agree on how light every block is.
+
+type Chunks = HashMap<ChunkPos, Chunk, ChunkPosHasherBuilder>;
+
+fn to_local(position: &BlockPos) -> Option<(ChunkPos, LocalBlockPos)> {
+    if position.y < 0 || position.y >= Chunk::HEIGHT as BlockAxis {
+        return None;
+    }
+
+    let chunk_pos = position.to_chunk_pos();
+    let local = LocalBlockPos::new(
+        (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+        position.y as LocalBlockAxis,
+        (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+    );
+    Some((chunk_pos, local))
+}
+
+// The block and light at a position, None where nothing is loaded.
+fn get_cell(chunks: &Chunks, position: &BlockPos, channel: LightChannel) -> Option<(u8, u8)> {
+    let (chunk_pos, local) = to_local(position)?;
+    let chunk = chunks.get(&chunk_pos)?;
+    Some((chunk.get_block(&local), chunk.get_light(&local, channel)))
+}
+
+// Meshes show the light of the blocks next to their faces, so changes on an edge touch the neighbouring chunk too.
+fn set_light(chunks: &mut Chunks, position: &BlockPos, channel: LightChannel, level: u8, touched: &mut HashSet<ChunkPos>) {
+    let Some((chunk_pos, local)) = to_local(position) else { return; };
+    let Some(chunk) = chunks.get_mut(&chunk_pos) else { return; };
+    if !chunk.set_light(&local, channel, level) {
+        return;
+    }
+
+    if local.x == 0 { touched.insert(ChunkPos::new(chunk_pos.x - 1, chunk_pos.z)); }
+    if local.x == Chunk::WIDTH - 1 { touched.insert(ChunkPos::new(chunk_pos.x + 1, chunk_pos.z)); }
+    if local.z == 0 { touched.insert(ChunkPos::new(chunk_pos.x, chunk_pos.z - 1)); }
+    if local.z == Chunk::LENGTH - 1 { touched.insert(ChunkPos::new(chunk_pos.x, chunk_pos.z + 1)); }
+    touched.insert(chunk_pos);
+}
+
+// The light a block gets from a neighbour with the given level in the given direction, None if it gets nothing.
+fn get_spread_level(channel: LightChannel, level: u8, direction: Direction, block: u8) -> Option<u8> {
+    let opacity = BlockTypes::get_light_opacity(block);
+    if opacity >= MAX_LIGHT {
+        return None;
+    }
+    if channel == LightChannel::Sky && direction == Direction::Bottom && level == MAX_LIGHT && opacity == 0 {
+        return Some(MAX_LIGHT);
+    }
+    level.checked_sub(opacity.max(1)).filter(|level| *level > 0)
+}
+
+fn propagate(chunks: &mut Chunks, channel: LightChannel, queue: &mut VecDeque<BlockPos>, touched: &mut HashSet<ChunkPos>) {
+    while let Some(position) = queue.pop_front() {
+        let Some((_, level)) = get_cell(chunks, &position, channel) else { continue; };
+
+        for direction in Direction::ALL {
+            let neighbour = position.offset(direction);
+            let Some((block, current)) = get_cell(chunks, &neighbour, channel) else { continue; };
+
+            match get_spread_level(channel, level, direction, block) {
+                Some(spread) if spread > current => {
+                    set_light(chunks, &neighbour, channel, spread, touched);
+                    queue.push_back(neighbour);
+                },
+                _ => {},
+            }
+        }
+    }
+}
+
+// Lights a chunk that just arrived from scratch and lets its light flow into the loaded neighbours and theirs into it.
+// Returns the chunks whose meshes have to be rebuilt.
+pub fn light_chunk(chunks: &mut Chunks, chunk_pos: &ChunkPos) -> HashSet<ChunkPos> {
+    let mut touched = HashSet::new();
+    let Some(chunk) = chunks.get_mut(chunk_pos) else { return touched; };
+    chunk.clear_light();
+
+    let (start_x, start_z) = chunk_pos.to_block_xz_pos();
+    let get_height = |chunks: &Chunks, x: BlockAxis, z: BlockAxis| {
+        let (neighbour_pos, local) = to_local(&BlockPos::new(x, 0, z))?;
+        chunks.get(&neighbour_pos).map(|chunk| chunk.get_height(local.x, local.z) as BlockAxis)
+    };
+
+    let mut sky_queue = VecDeque::new();
+    let mut block_queue = VecDeque::new();
+    for z in start_z..start_z + Chunk::LENGTH as BlockAxis {
+        for x in start_x..start_x + Chunk::WIDTH as BlockAxis {
+            let height = get_height(chunks, x, z).unwrap_or(0);
+            // Only the lowest sky light can shine down into the column, and only the one next to higher columns
+            // can spread sideways under overhangs, so only those need a flood fill.
+            let spread_height = Direction::HORIZONTAL.into_iter()
+                .filter_map(|direction| {
+                    let (dx, _, dz) = direction.get_offset();
+                    get_height(chunks, x + dx, z + dz)
+                })
+                .max()
+                .unwrap_or(0);
+
+            for y in height..Chunk::HEIGHT as BlockAxis {
+                let position = BlockPos::new(x, y, z);
+                set_light(chunks, &position, LightChannel::Sky, MAX_LIGHT, &mut touched);
+                if y == height || y < spread_height {
+                    sky_queue.push_back(position);
+                }
+            }
+        }
+    }
+
+    let Some(chunk) = chunks.get(chunk_pos) else { return touched; };
+    let emitters: Vec<(BlockPos, u8)> = chunk.get_blocks().iter().enumerate()
+        .filter(|(_, block)| BlockTypes::get_light_emission(**block) > 0)
+        .map(|(index, block)| {
+            let local = Chunk::from_index(index);
+            let position = BlockPos::new(start_x + local.x as BlockAxis, local.y as BlockAxis, start_z + local.z as BlockAxis);
+            (position, BlockTypes::get_light_emission(*block))
+        })
+        .collect();
+    for (position, emission) in emitters {
+        set_light(chunks, &position, LightChannel::Block, emission, &mut touched);
+        block_queue.push_back(position);
+    }
+
+    // Light already in the neighbours crosses the border on its own.
+    let width = Chunk::WIDTH as BlockAxis;
+    let length = Chunk::LENGTH as BlockAxis;
+    let borders = (0..length).flat_map(|i| [
+        BlockPos::new(start_x - 1, 0, start_z + i),
+        BlockPos::new(start_x + width, 0, start_z + i),
+        BlockPos::new(start_x + i, 0, start_z - 1),
+        BlockPos::new(start_x + i, 0, start_z + length),
+    ]);
+    for border in borders {
+        for y in 0..Chunk::HEIGHT as BlockAxis {
+            let position = BlockPos::new(border.x, y, border.z);
+            if get_cell(chunks, &position, LightChannel::Sky).is_some_and(|(_, level)| level > 1) {
+                sky_queue.push_back(position.clone());
+            }
+            if get_cell(chunks, &position, LightChannel::Block).is_some_and(|(_, level)| level > 1) {
+                block_queue.push_back(position);
+            }
+        }
+    }
+
+    propagate(chunks, LightChannel::Sky, &mut sky_queue, &mut touched);
+    propagate(chunks, LightChannel::Block, &mut block_queue, &mut touched);
+    touched
+}
+
+// Fixes up the light around a block that changed. The light the old block let through or gave off is taken away first,
+// then the blocks at the edge of the darkened area that are lit from elsewhere flow back into it.
+// Returns the chunks whose meshes have to be rebuilt.
+pub fn update_block(chunks: &mut Chunks, position: &BlockPos) -> HashSet<ChunkPos> {
+    let mut touched = HashSet::new();
+    let Some((block, _)) = get_cell(chunks, position, LightChannel::Sky) else { return touched; };
+
+    for channel in LightChannel::ALL {
+        let Some((_, old_level)) = get_cell(chunks, position, channel) else { continue; };
+        let mut removal = VecDeque::from([(position.clone(), old_level)]);
+        let mut relight = VecDeque::new();
+        set_light(chunks, position, channel, 0, &mut touched);
+
+        while let Some((removed, level)) = removal.pop_front() {
+            for direction in Direction::ALL {
+                let neighbour = removed.offset(direction);
+                let Some((neighbour_block, current)) = get_cell(chunks, &neighbour, channel) else { continue; };
+                if current == 0 {
+                    continue;
+                }
+
+                let is_straight_down = channel == LightChannel::Sky && direction == Direction::Bottom && level == MAX_LIGHT && current == MAX_LIGHT;
+                if current < level || is_straight_down {
+                    set_light(chunks, &neighbour, channel, 0, &mut touched);
+                    removal.push_back((neighbour.clone(), current));
+
+                    let emission = BlockTypes::get_light_emission(neighbour_block);
+                    if channel == LightChannel::Block && emission > 0 {
+                        set_light(chunks, &neighbour, channel, emission, &mut touched);
+                        relight.push_back(neighbour);
+                    }
+                } else {
+                    relight.push_back(neighbour);
+                }
+            }
+        }
+
+        let source = match channel {
+            LightChannel::Sky if position.y == Chunk::HEIGHT as BlockAxis - 1 && BlockTypes::get_light_opacity(block) == 0 => MAX_LIGHT,
+            LightChannel::Sky => 0,
+            LightChannel::Block => BlockTypes::get_light_emission(block),
+        };
+        if source > 0 {
+            set_light(chunks, position, channel, source, &mut touched);
+            relight.push_back(position.clone());
+        }
+
+        propagate(chunks, channel, &mut relight, &mut touched);
+    }
+
+    touched
+}
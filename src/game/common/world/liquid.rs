@@ -0,0 +1,40 @@
+use super::block_types::BlockTypes;
+
+// A liquid keeps its level in the block id: the source first, then flowing levels 1 to 7 and falling.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Liquid {
+    Water,
+    Lava,
+}
+
+impl Liquid {
+    pub const ALL: [Liquid; 2] = [Liquid::Water, Liquid::Lava];
+    pub const SOURCE_LEVEL: u8 = 0;
+    pub const MAX_FLOW_LEVEL: u8 = 7;
+    // Flowing straight down, it spreads like a source again where it lands.
+    pub const FALLING_LEVEL: u8 = 8;
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Liquid::Water => "water",
+            Liquid::Lava => "lava",
+        }
+    }
+
+    pub fn get_source(&self) -> u8 {
+        match self {
+            Liquid::Water => BlockTypes::Water as u8,
+            Liquid::Lava => BlockTypes::Lava as u8,
+        }
+    }
+    pub fn get_block(&self, level: u8) -> u8 {
+        self.get_source() + level.min(Self::FALLING_LEVEL)
+    }
+    // The liquid and its level, or None for any other block.
+    pub fn from_block(block: u8) -> Option<(Self, u8)> {
+        Self::ALL.into_iter().find_map(|liquid| {
+            let level = block.checked_sub(liquid.get_source())?;
+            if level <= Self::FALLING_LEVEL { Some((liquid, level)) } else { None }
+        })
+    }
+}
@@ -1,2 +1,9 @@
 pub mod mapping;
-pub mod block_types;
\ No newline at end of file
+pub mod block_types;
+pub mod block_view;
+pub mod raycast;
+pub mod liquid;
+pub mod light;
+pub mod lighting;
+pub mod signal;
+pub mod block_entity;
@@ -0,0 +1,76 @@
+use cgmath::{InnerSpace, Point3, Vector3, Zero};
+
+use crate::game::common::coords::{BlockPos, Coord};
+
+use super::{block_types::BlockTypes, block_view::BlockView};
+
+pub struct RayHitInfo {
+    pub position: Point3<i64>,
+    pub normal: Vector3<i64>,
+}
+
+impl RayHitInfo {
+    pub fn get_block_pos(&self) -> BlockPos {
+        BlockPos::new(self.position.x, self.position.y, self.position.z)
+    }
+}
+
+pub fn raycast<W: BlockView>(world: &W, origin: &Coord, direction: &Vector3<f32>, max_distance: f32) -> Option<RayHitInfo> {
+    let step = direction.map(|v| v.signum() as i64);
+    let delta = direction.map(|v| {
+        if v != 0.0 {
+            (1.0 / v).abs()
+        } else {
+            f32::INFINITY
+        }
+    });
+
+    let mut position = Point3::new(origin.get_block_x(), origin.get_block_y(), origin.get_block_z());
+    let mut tmax = Point3::new(
+        if step.x > 0 { 1.0 - origin.get_frac_x() } else { origin.get_frac_x() } * delta.x,
+        if step.y > 0 { 1.0 - origin.get_frac_y() } else { origin.get_frac_y() } * delta.y,
+        if step.z > 0 { 1.0 - origin.get_frac_z() } else { origin.get_frac_z() } * delta.z
+    );
+
+    let mut traveled_distance = 0.0f32;
+    let mut normal = Vector3::<f32>::zero();
+
+    while traveled_distance < max_distance {
+        if position.y >= u16::MIN as i64 && position.y <= u16::MAX as i64 && BlockTypes::is_selectable(world.get_block(&BlockPos::new(position.x, position.y, position.z))) {
+            return Some(RayHitInfo {
+                position,
+                normal: normal.normalize().map(|v| v as i64),
+            })
+        }
+
+        if tmax.x < tmax.y {
+            if tmax.x < tmax.z {
+                position.x += step.x;
+                normal = Vector3::unit_x() * -step.x as f32;
+
+                traveled_distance = tmax.x;
+                tmax.x += delta.x;
+            } else {
+                position.z += step.z;
+                normal = Vector3::unit_z() * -step.z as f32;
+
+                traveled_distance = tmax.z;
+                tmax.z += delta.z;
+            }
+        } else if tmax.y < tmax.z {
+            position.y += step.y;
+            normal = Vector3::unit_y() * -step.y as f32;
+
+            traveled_distance = tmax.y;
+            tmax.y += delta.y;
+        } else {
+            position.z += step.z;
+            normal = Vector3::unit_z() * -step.z as f32;
+
+            traveled_distance = tmax.z;
+            tmax.z += delta.z;
+        }
+    }
+
+    None
+}
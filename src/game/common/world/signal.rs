@@ -0,0 +1,89 @@
+use crate::game::common::coords::Direction;
+
+use super::block_types::BlockTypes;
+
+// Blocks that send, carry and react to power. Like liquid levels, every state is a block id of its own,
+// the block type's id is the first one. Directional blocks point where they send their power to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SignalBlock {
+    Lever { is_on: bool },
+    Button { is_pressed: bool },
+    Wire { power: u8 },
+    Lamp { is_lit: bool },
+    // A door remembers whether it is powered, so players can still open and close it by hand in between.
+    Door { is_open: bool, is_powered: bool },
+    Repeater { facing: Direction, delay: u8, is_powered: bool },
+    Inverter { facing: Direction, is_on: bool },
+}
+
+impl SignalBlock {
+    pub const MAX_POWER: u8 = 15;
+    // In redstone ticks of two world ticks each, like in Minecraft.
+    pub const MAX_REPEATER_DELAY: u8 = 4;
+
+    fn get_facing_index(facing: Direction) -> u8 {
+        Direction::HORIZONTAL.iter().position(|direction| *direction == facing).unwrap_or(0) as u8
+    }
+
+    pub fn get_block(&self) -> u8 {
+        match *self {
+            SignalBlock::Lever { is_on } => BlockTypes::Lever as u8 + is_on as u8,
+            SignalBlock::Button { is_pressed } => BlockTypes::Button as u8 + is_pressed as u8,
+            SignalBlock::Wire { power } => BlockTypes::Wire as u8 + power.min(Self::MAX_POWER),
+            SignalBlock::Lamp { is_lit } => BlockTypes::Lamp as u8 + is_lit as u8,
+            SignalBlock::Door { is_open, is_powered } => BlockTypes::Door as u8 + is_open as u8 + is_powered as u8 * 2,
+            SignalBlock::Repeater { facing, delay, is_powered } => {
+                let delay = delay.clamp(1, Self::MAX_REPEATER_DELAY);
+                BlockTypes::Repeater as u8 + Self::get_facing_index(facing) * 8 + (delay - 1) * 2 + is_powered as u8
+            },
+            SignalBlock::Inverter { facing, is_on } => BlockTypes::Inverter as u8 + Self::get_facing_index(facing) * 2 + is_on as u8,
+        }
+    }
+    // The signal block and its state, or None for any other block.
+    pub fn from_block(block: u8) -> Option<Self> {
+        let state = |first: BlockTypes, count: u8| block.checked_sub(first as u8).filter(|state| *state < count);
+        let facing = |index: u8| Direction::HORIZONTAL[index as usize];
+
+        if let Some(state) = state(BlockTypes::Lever, 2) {
+            Some(SignalBlock::Lever { is_on: state == 1 })
+        } else if let Some(state) = state(BlockTypes::Button, 2) {
+            Some(SignalBlock::Button { is_pressed: state == 1 })
+        } else if let Some(state) = state(BlockTypes::Wire, Self::MAX_POWER + 1) {
+            Some(SignalBlock::Wire { power: state })
+        } else if let Some(state) = state(BlockTypes::Lamp, 2) {
+            Some(SignalBlock::Lamp { is_lit: state == 1 })
+        } else if let Some(state) = state(BlockTypes::Door, 4) {
+            Some(SignalBlock::Door { is_open: state & 1 == 1, is_powered: state & 2 == 2 })
+        } else if let Some(state) = state(BlockTypes::Repeater, 32) {
+            Some(SignalBlock::Repeater { facing: facing(state / 8), delay: state % 8 / 2 + 1, is_powered: state & 1 == 1 })
+        } else {
+            state(BlockTypes::Inverter, 8).map(|state| SignalBlock::Inverter { facing: facing(state / 2), is_on: state & 1 == 1 })
+        }
+    }
+
+    // The first id of the block type, the state players carry it in.
+    pub fn get_type_block(&self) -> u8 {
+        match self {
+            SignalBlock::Lever { .. } => BlockTypes::Lever as u8,
+            SignalBlock::Button { .. } => BlockTypes::Button as u8,
+            SignalBlock::Wire { .. } => BlockTypes::Wire as u8,
+            SignalBlock::Lamp { .. } => BlockTypes::Lamp as u8,
+            SignalBlock::Door { .. } => BlockTypes::Door as u8,
+            SignalBlock::Repeater { .. } => BlockTypes::Repeater as u8,
+            SignalBlock::Inverter { .. } => BlockTypes::Inverter as u8,
+        }
+    }
+
+    // Right clicking these uses them instead of placing a block against them.
+    pub fn is_usable(&self) -> bool {
+        matches!(self, SignalBlock::Lever { .. } | SignalBlock::Button { .. } | SignalBlock::Door { .. } | SignalBlock::Repeater { .. })
+    }
+    // The block a player places, directional blocks point the way the player looks.
+    pub fn get_placed_block(block: u8, facing: Direction) -> u8 {
+        match Self::from_block(block) {
+            Some(SignalBlock::Repeater { delay, .. }) => SignalBlock::Repeater { facing, delay, is_powered: false }.get_block(),
+            Some(SignalBlock::Inverter { .. }) => SignalBlock::Inverter { facing, is_on: false }.get_block(),
+            _ => block,
+        }
+    }
+}
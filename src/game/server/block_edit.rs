@@ -0,0 +1,148 @@
+use cgmath::{InnerSpace, Vector3};
+
+use crate::game::common::{coords::{BlockPos, Coord, CoordAxis, Direction}, item::ItemTypes, player::MAX_REACH_DISTANCE, protocol::packet::BlockEditRejection, world::{block_types::BlockTypes, raycast::raycast, signal::SignalBlock}};
+
+use super::{player::ServerPlayer, world::chunk::Chunk, ServerWorld};
+
+pub enum BlockEdit {
+    Break { position: BlockPos },
+    Place { position: BlockPos, face: Direction, block: u8 },
+}
+
+impl BlockEdit {
+    // Half of a block's diagonal plus some slack for position updates arriving after the edit.
+    const REACH_TOLERANCE: CoordAxis = 1.5;
+    // Mining may end this many ticks early, the packets that start and end it can be handled in different updates.
+    const BREAK_TOLERANCE: u64 = 5;
+
+    pub fn get_target(&self) -> BlockPos {
+        match self {
+            BlockEdit::Break { position } => position.clone(),
+            BlockEdit::Place { position, face, .. } => position.offset(*face),
+        }
+    }
+
+    pub fn validate(&self, world: &ServerWorld, eye: &Coord) -> Result<(), BlockEditRejection> {
+        let target = self.get_target();
+        if target.y < 0 || target.y >= Chunk::HEIGHT as i64 {
+            return Err(BlockEditRejection::InvalidTarget);
+        }
+        if world.get_chunk(&target.to_chunk_pos()).is_none() {
+            return Err(BlockEditRejection::UnloadedChunk);
+        }
+
+        // The block we are looking at: the broken one or the one we place against.
+        let (looked_at, expected_face) = match self {
+            BlockEdit::Break { position } => (position, None),
+            BlockEdit::Place { position, face, .. } => (position, Some(*face)),
+        };
+
+        match self {
+            BlockEdit::Break { position } => {
+                let block = world.get_block(position);
+                if !BlockTypes::is_selectable(block) || block == BlockTypes::Bedrock as u8 {
+                    return Err(BlockEditRejection::InvalidTarget);
+                }
+            },
+            BlockEdit::Place { block, .. } => {
+                if ItemTypes::get_placing_item(*block).is_none() || !BlockTypes::is_replaceable(world.get_block(&target)) {
+                    return Err(BlockEditRejection::InvalidTarget);
+                }
+            },
+        }
+
+        check_reach(world, eye, looked_at, expected_face)
+    }
+    // Survival players have to mine a block for as long as it takes to break and can only place blocks they carry.
+    pub fn validate_survival(&self, world: &ServerWorld, player: &ServerPlayer, now: u64) -> Result<(), BlockEditRejection> {
+        match self {
+            BlockEdit::Break { position } => {
+                let break_ticks = BlockTypes::get_break_ticks(world.get_block(position)).ok_or(BlockEditRejection::InvalidTarget)?;
+                let mined = match &player.breaking {
+                    Some((breaking, started)) if breaking == position => now.saturating_sub(*started),
+                    _ => 0,
+                };
+                if mined + Self::BREAK_TOLERANCE < break_ticks { Err(BlockEditRejection::TooFast) } else { Ok(()) }
+            },
+            BlockEdit::Place { block, .. } => match ItemTypes::get_placing_item(*block) {
+                Some(item) if player.inventory.contains(item) => Ok(()),
+                Some(_) => Err(BlockEditRejection::MissingItem),
+                None => Err(BlockEditRejection::InvalidTarget),
+            },
+        }
+    }
+}
+
+// Blocks can be used from as far away and only as visibly as they can be edited.
+pub fn validate_use(world: &ServerWorld, eye: &Coord, position: &BlockPos) -> Result<(), BlockEditRejection> {
+    if world.get_chunk(&position.to_chunk_pos()).is_none() {
+        return Err(BlockEditRejection::UnloadedChunk);
+    }
+    if !SignalBlock::from_block(world.get_block(position)).is_some_and(|signal| signal.is_usable()) {
+        return Err(BlockEditRejection::InvalidTarget);
+    }
+
+    check_reach(world, eye, position, None)
+}
+// Items are used against a face of a block, so they need to see it like placing does. What the item does there is up to the item.
+pub fn validate_item_use(world: &ServerWorld, eye: &Coord, position: &BlockPos, face: Direction) -> Result<(), BlockEditRejection> {
+    if world.get_chunk(&position.to_chunk_pos()).is_none() {
+        return Err(BlockEditRejection::UnloadedChunk);
+    }
+
+    check_reach(world, eye, position, Some(face))
+}
+
+fn get_face_normal(face: Direction) -> Vector3<CoordAxis> {
+    let (dx, dy, dz) = face.get_offset();
+    Vector3::new(dx as CoordAxis, dy as CoordAxis, dz as CoordAxis)
+}
+// Whether the eye is in front of the plane of the face, only then it can be seen.
+fn is_facing(eye: &Coord, position: &BlockPos, face: Direction) -> bool {
+    let mut face_center = position.get_center();
+    face_center += get_face_normal(face) * 0.5;
+
+    eye.direction_to(&face_center).map(|v| v as CoordAxis).dot(get_face_normal(face)) < 0.0
+}
+// The center of the face and a point near each of its corners.
+fn get_face_points(position: &BlockPos, face: Direction) -> Vec<Coord> {
+    const CORNER_INSET: CoordAxis = 0.1;
+
+    let normal = get_face_normal(face);
+    let tangent = if normal.x != 0.0 { Vector3::unit_y() } else { Vector3::unit_x() };
+    let bitangent = normal.cross(tangent);
+
+    let mut points = vec![Vector3::new(0.0, 0.0, 0.0)];
+    for (u, v) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+        points.push((tangent * u + bitangent * v) * (0.5 - CORNER_INSET));
+    }
+
+    points.into_iter().map(|offset| {
+        let mut point = position.get_center();
+        point += normal * 0.5 + offset;
+        point
+    }).collect()
+}
+
+// The block we are looking at has to be in reach and the first one on the way to some point of a face turned to us.
+// A face we place against has to be that face. Several points are tried, so a ray to a block far down on the floor
+// does not clip the edge of the block in front of it.
+fn check_reach(world: &ServerWorld, eye: &Coord, looked_at: &BlockPos, expected_face: Option<Direction>) -> Result<(), BlockEditRejection> {
+    if eye.distance(&looked_at.get_center()) > MAX_REACH_DISTANCE as CoordAxis + BlockEdit::REACH_TOLERANCE {
+        return Err(BlockEditRejection::TooFar);
+    }
+
+    let faces: Vec<Direction> = match expected_face {
+        Some(face) => vec![face],
+        None => Direction::ALL.to_vec(),
+    };
+    let is_visible = faces.into_iter()
+        .filter(|face| is_facing(eye, looked_at, *face))
+        .flat_map(|face| get_face_points(looked_at, face))
+        .any(|point| {
+            let direction = eye.direction_to(&point);
+            raycast(world, eye, &direction.normalize(), direction.magnitude() + 1.0).is_some_and(|hit| hit.get_block_pos() == *looked_at)
+        });
+
+    if is_visible { Ok(()) } else { Err(BlockEditRejection::NoLineOfSight) }
+}
@@ -0,0 +1,171 @@
+use crate::game::common::{coords::{BlockAxis, BlockPos, Coord, CoordAxis, WORLD_BORDER}, player::PlayerId, world::block_types::BlockTypes};
+
+use super::super::game_server::GameServer;
+
+pub enum ArgumentType {
+    // Three numbers, each either absolute or relative to the source with `~`.
+    Position,
+    Block,
+    Integer { min: i64, max: i64 },
+    // A fixed word, used for subcommands like `/time set`.
+    Literal(&'static str),
+    Player,
+    // Everything that is left of the input.
+    Message,
+}
+
+pub struct Argument {
+    name: &'static str,
+    kind: ArgumentType,
+}
+
+pub enum ArgumentValue {
+    Position(Coord),
+    Block(u8),
+    Integer(i64),
+    Literal,
+    Player(PlayerId),
+    Message(String),
+}
+
+impl Argument {
+    pub fn position(name: &'static str) -> Self {
+        Self { name, kind: ArgumentType::Position }
+    }
+    pub fn block(name: &'static str) -> Self {
+        Self { name, kind: ArgumentType::Block }
+    }
+    pub fn integer(name: &'static str, min: i64, max: i64) -> Self {
+        Self { name, kind: ArgumentType::Integer { min, max } }
+    }
+    pub fn literal(word: &'static str) -> Self {
+        Self { name: word, kind: ArgumentType::Literal(word) }
+    }
+    pub fn player(name: &'static str) -> Self {
+        Self { name, kind: ArgumentType::Player }
+    }
+    pub fn message(name: &'static str) -> Self {
+        Self { name, kind: ArgumentType::Message }
+    }
+
+    pub fn get_usage(&self) -> String {
+        match self.kind {
+            ArgumentType::Literal(word) => word.to_string(),
+            ArgumentType::Position => format!("<{} x y z>", self.name),
+            _ => format!("<{}>", self.name),
+        }
+    }
+
+    // How many tokens the argument takes, None meaning all remaining tokens.
+    pub fn get_token_count(&self) -> Option<usize> {
+        match self.kind {
+            ArgumentType::Position => Some(3),
+            ArgumentType::Message => None,
+            _ => Some(1),
+        }
+    }
+
+    // Coordinates past the world border are moved onto it, so commands working on them can't overflow.
+    fn parse_axis(token: &str, base: CoordAxis) -> Result<CoordAxis, String> {
+        let (relative, number) = match token.strip_prefix('~') {
+            Some(offset) => (true, offset),
+            None => (false, token),
+        };
+
+        let value = if relative && number.is_empty() {
+            0.0
+        } else {
+            match number.parse::<CoordAxis>() {
+                Ok(value) if value.is_finite() => value,
+                _ => return Err(format!("Expected a coordinate, found: {}", token)),
+            }
+        };
+
+        let border = WORLD_BORDER as CoordAxis;
+        Ok(if relative { base + value } else { value }.clamp(-border, border))
+    }
+
+    pub fn parse(&self, server: &GameServer, origin: &Coord, tokens: &[&str]) -> Result<ArgumentValue, String> {
+        match self.kind {
+            ArgumentType::Position => Ok(ArgumentValue::Position(Coord::new(
+                Self::parse_axis(tokens[0], origin.get_world_x())?,
+                Self::parse_axis(tokens[1], origin.get_world_y())?,
+                Self::parse_axis(tokens[2], origin.get_world_z())?,
+            ))),
+            ArgumentType::Block => match BlockTypes::from_name(tokens[0]) {
+                Some(block) => Ok(ArgumentValue::Block(block)),
+                None => Err(format!("Unknown block: {}", tokens[0])),
+            },
+            ArgumentType::Integer { min, max } => match tokens[0].parse::<i64>() {
+                Ok(value) if value >= min && value <= max => Ok(ArgumentValue::Integer(value)),
+                Ok(_) => Err(format!("{} must be between {} and {}", self.name, min, max)),
+                Err(_) => Err(format!("Expected a whole number, found: {}", tokens[0])),
+            },
+            ArgumentType::Literal(word) => {
+                if tokens[0].eq_ignore_ascii_case(word) { Ok(ArgumentValue::Literal) } else { Err(format!("Expected: {}", word)) }
+            },
+            ArgumentType::Player => match server.find_player(tokens[0]) {
+                Some(id) => Ok(ArgumentValue::Player(id)),
+                None => Err(format!("No player named: {}", tokens[0])),
+            },
+            ArgumentType::Message => Ok(ArgumentValue::Message(tokens.join(" "))),
+        }
+    }
+
+    // Suggestions for the token at the given index within this argument, filtered by what was typed so far.
+    pub fn complete(&self, server: &GameServer, index: usize, prefix: &str) -> Vec<String> {
+        let candidates: Vec<String> = match self.kind {
+            ArgumentType::Position if index < 3 => vec![String::from("~")],
+            ArgumentType::Block => BlockTypes::NAMES.iter().skip(1).map(|name| name.to_string()).collect(),
+            ArgumentType::Literal(word) if index == 0 => vec![word.to_string()],
+            ArgumentType::Player if index == 0 => server.get_player_names(),
+            _ => vec![],
+        };
+
+        candidates.into_iter()
+            .filter(|candidate| candidate.to_ascii_lowercase().starts_with(&prefix.to_ascii_lowercase()))
+            .collect()
+    }
+}
+
+// The executor of a usage only ever receives values matching its arguments, so a mismatch is a programming error.
+impl ArgumentValue {
+    pub fn get_position(&self) -> Coord {
+        match self {
+            ArgumentValue::Position(position) => position.clone(),
+            _ => unreachable!("Argument is not a position"),
+        }
+    }
+    pub fn get_block_pos(&self) -> BlockPos {
+        let position = self.get_position();
+        BlockPos::new(
+            position.get_world_x().floor() as BlockAxis,
+            position.get_world_y().floor() as BlockAxis,
+            position.get_world_z().floor() as BlockAxis,
+        )
+    }
+    pub fn get_block(&self) -> u8 {
+        match self {
+            ArgumentValue::Block(block) => *block,
+            _ => unreachable!("Argument is not a block"),
+        }
+    }
+    pub fn get_integer(&self) -> i64 {
+        match self {
+            ArgumentValue::Integer(value) => *value,
+            _ => unreachable!("Argument is not an integer"),
+        }
+    }
+    pub fn get_player(&self) -> PlayerId {
+        match self {
+            ArgumentValue::Player(id) => *id,
+            _ => unreachable!("Argument is not a player"),
+        }
+    }
+    pub fn get_message(&self) -> &str {
+        match self {
+            ArgumentValue::Message(message) => message,
+            _ => unreachable!("Argument is not a message"),
+        }
+    }
+}
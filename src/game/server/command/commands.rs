@@ -0,0 +1,414 @@
+use crate::game::{common::{coords::{BlockAxis, BlockPos, ChunkPos, CoordAxis}, day_cycle, player::GameMode, protocol::packet::ServerPacket, weather::Weather, world::{block_entity::BlockEntity, block_types::BlockTypes, liquid::Liquid}}, server::world::{chunk::Chunk, rules::WorldRules}};
+
+use super::{argument::{Argument, ArgumentValue}, Command, CommandContext, CommandRegistry, CommandSource, PermissionLevel};
+use super::super::{game_server::GameServer, tick_metrics::TickMetrics};
+
+const MAX_FILL_VOLUME: BlockAxis = 32768;
+
+pub fn register_all(registry: &mut CommandRegistry) {
+    registry.register(Command::new("help", "Lists the commands you can use", PermissionLevel::Player)
+        .with_usage(vec![], help));
+    registry.register(Command::new("seed", "Shows the world seed", PermissionLevel::Player)
+        .with_usage(vec![], seed));
+    registry.register(Command::new("tps", "Shows how fast the server is ticking", PermissionLevel::Player)
+        .with_usage(vec![], tps));
+    registry.register(Command::new("say", "Sends a message to every player", PermissionLevel::Operator)
+        .with_usage(vec![Argument::message("message")], say));
+    registry.register(Command::new("tp", "Teleports a player to a position or another player", PermissionLevel::Operator)
+        .with_usage(vec![Argument::position("destination")], teleport_self)
+        .with_usage(vec![Argument::player("target")], teleport_self_to_player)
+        .with_usage(vec![Argument::player("player"), Argument::position("destination")], teleport)
+        .with_usage(vec![Argument::player("player"), Argument::player("target")], teleport_to_player));
+    registry.register(Command::new("gamemode", "Changes the game mode of a player", PermissionLevel::Operator)
+        .with_usage(vec![Argument::literal("survival")], game_mode_survival)
+        .with_usage(vec![Argument::literal("survival"), Argument::player("player")], game_mode_survival)
+        .with_usage(vec![Argument::literal("creative")], game_mode_creative)
+        .with_usage(vec![Argument::literal("creative"), Argument::player("player")], game_mode_creative)
+        .with_usage(vec![Argument::literal("spectator")], game_mode_spectator)
+        .with_usage(vec![Argument::literal("spectator"), Argument::player("player")], game_mode_spectator));
+    registry.register(Command::new("setblock", "Places a block", PermissionLevel::Operator)
+        .with_usage(vec![Argument::position("position"), Argument::block("block")], set_block));
+    registry.register(Command::new("fill", "Fills a box of blocks", PermissionLevel::Operator)
+        .with_usage(vec![Argument::position("from"), Argument::position("to"), Argument::block("block")], fill));
+    registry.register(Command::new("sign", "Writes text on a sign", PermissionLevel::Operator)
+        .with_usage(vec![Argument::position("position"), Argument::message("text")], sign));
+    registry.register(Command::new("time", "Shows or changes the world time in ticks", PermissionLevel::Operator)
+        .with_usage(vec![Argument::literal("query")], time_query)
+        .with_usage(vec![Argument::literal("set"), Argument::integer("ticks", 0, 1_000_000_000)], time_set)
+        .with_usage(vec![Argument::literal("set"), Argument::literal("day")], time_set_day)
+        .with_usage(vec![Argument::literal("set"), Argument::literal("noon")], time_set_noon)
+        .with_usage(vec![Argument::literal("set"), Argument::literal("night")], time_set_night)
+        .with_usage(vec![Argument::literal("set"), Argument::literal("midnight")], time_set_midnight)
+        .with_usage(vec![Argument::literal("add"), Argument::integer("ticks", 0, 1_000_000_000)], time_add));
+    registry.register(Command::new("flowdistance", "Shows or changes how far liquids flow from their source", PermissionLevel::Operator)
+        .with_usage(vec![Argument::literal("water")], water_flow_distance)
+        .with_usage(vec![Argument::literal("water"), Argument::integer("distance", 1, Liquid::MAX_FLOW_LEVEL as i64)], water_flow_distance)
+        .with_usage(vec![Argument::literal("lava")], lava_flow_distance)
+        .with_usage(vec![Argument::literal("lava"), Argument::integer("distance", 1, Liquid::MAX_FLOW_LEVEL as i64)], lava_flow_distance));
+    registry.register(Command::new("randomtickspeed", "Shows or changes how many random blocks per section tick every tick", PermissionLevel::Operator)
+        .with_usage(vec![], random_tick_speed)
+        .with_usage(vec![Argument::integer("speed", 0, WorldRules::MAX_RANDOM_TICK_SPEED as i64)], random_tick_speed));
+    registry.register(Command::new("weather", "Shows or changes the weather, optionally for a number of ticks", PermissionLevel::Operator)
+        .with_usage(vec![Argument::literal("query")], weather_query)
+        .with_usage(vec![Argument::literal("clear")], weather_clear)
+        .with_usage(vec![Argument::literal("clear"), Argument::integer("ticks", 1, 1_000_000)], weather_clear)
+        .with_usage(vec![Argument::literal("rain")], weather_rain)
+        .with_usage(vec![Argument::literal("rain"), Argument::integer("ticks", 1, 1_000_000)], weather_rain)
+        .with_usage(vec![Argument::literal("thunder")], weather_thunder)
+        .with_usage(vec![Argument::literal("thunder"), Argument::integer("ticks", 1, 1_000_000)], weather_thunder));
+    registry.register(Command::new("save", "Saves the world to disk", PermissionLevel::Operator)
+        .with_usage(vec![], save));
+    registry.register(Command::new("forceload", "Keeps the chunk at a position loaded even without players nearby", PermissionLevel::Operator)
+        .with_usage(vec![Argument::literal("add"), Argument::position("position")], force_load_add)
+        .with_usage(vec![Argument::literal("remove"), Argument::position("position")], force_load_remove)
+        .with_usage(vec![Argument::literal("query")], force_load_query));
+    registry.register(Command::new("netstats", "Shows how well each packet type compresses", PermissionLevel::Operator)
+        .with_usage(vec![], network_stats));
+    registry.register(Command::new("stop", "Saves the world and stops the server", PermissionLevel::Console)
+        .with_usage(vec![], stop));
+    registry.register(Command::new("op", "Lets a player use operator commands", PermissionLevel::Operator)
+        .with_usage(vec![Argument::player("player")], op));
+    registry.register(Command::new("deop", "Takes operator commands away from a player", PermissionLevel::Operator)
+        .with_usage(vec![Argument::player("player")], deop));
+}
+
+fn help(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let permission = server.get_permission_level(context.source);
+    let lines: Vec<String> = server.get_commands().get_commands(permission)
+        .map(|command| format!("{} - {}", command.get_usages().join(", "), command.get_description()))
+        .collect();
+
+    for line in lines {
+        context.reply(line);
+    }
+    Ok(())
+}
+
+fn seed(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    context.reply(format!("Seed: {}", server.get_world().get_seed()));
+    Ok(())
+}
+
+fn tps(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let metrics = server.get_tick_metrics();
+    context.reply(format!(
+        "TPS: {:.1}, MSPT: {:.2} average and {:.2} max over the last {} ticks",
+        metrics.get_tps(GameServer::TICKS_PER_SECOND),
+        metrics.get_average_mspt(),
+        metrics.get_max_mspt(),
+        TickMetrics::SAMPLE_COUNT,
+    ));
+    context.reply(format!("{} ticks run, {} skipped to catch up", metrics.get_total_ticks(), metrics.get_skipped_ticks()));
+    Ok(())
+}
+
+fn say(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let sender = match context.source {
+        CommandSource::Console => String::from("Server"),
+        CommandSource::Player(id) => server.get_player_name(id).unwrap_or_default(),
+    };
+
+    server.broadcast_message(&format!("[{}] {}", sender, arguments[0].get_message()));
+    Ok(())
+}
+
+fn teleport_self(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let CommandSource::Player(id) = context.source else {
+        return Err(String::from("Only players can teleport themselves, use /tp <player> <destination x y z>"));
+    };
+
+    teleport(server, context, &[ArgumentValue::Player(id), ArgumentValue::Position(arguments[0].get_position())])
+}
+fn teleport_self_to_player(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let CommandSource::Player(id) = context.source else {
+        return Err(String::from("Only players can teleport themselves, use /tp <player> <target>"));
+    };
+
+    teleport_to_player(server, context, &[ArgumentValue::Player(id), ArgumentValue::Player(arguments[0].get_player())])
+}
+fn teleport(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let id = arguments[0].get_player();
+    let position = arguments[1].get_position();
+
+    if position.get_world_y().abs() > Chunk::HEIGHT as CoordAxis * 2.0 {
+        return Err(String::from("Destination is too far outside of the world"));
+    }
+
+    server.teleport(id, position.clone());
+    context.reply(format!(
+        "Teleported {} to {:.1}, {:.1}, {:.1}",
+        server.get_player_name(id).unwrap_or_default(),
+        position.get_world_x(), position.get_world_y(), position.get_world_z(),
+    ));
+    Ok(())
+}
+fn teleport_to_player(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let id = arguments[0].get_player();
+    let target = arguments[1].get_player();
+    let Some(position) = server.get_player_position(target) else {
+        return Err(String::from("Target player left the game"));
+    };
+
+    server.teleport(id, position);
+    context.reply(format!(
+        "Teleported {} to {}",
+        server.get_player_name(id).unwrap_or_default(),
+        server.get_player_name(target).unwrap_or_default(),
+    ));
+    Ok(())
+}
+
+fn game_mode_survival(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_game_mode(server, context, GameMode::Survival, arguments)
+}
+fn game_mode_creative(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_game_mode(server, context, GameMode::Creative, arguments)
+}
+fn game_mode_spectator(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_game_mode(server, context, GameMode::Spectator, arguments)
+}
+fn set_game_mode(server: &mut GameServer, context: &mut CommandContext, mode: GameMode, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let id = match (arguments.get(1), context.source) {
+        (Some(player), _) => player.get_player(),
+        (None, CommandSource::Player(id)) => id,
+        (None, CommandSource::Console) => return Err(format!("Only players can change their own game mode, use /gamemode {} <player>", mode.get_name())),
+    };
+
+    server.set_game_mode(id, mode);
+    context.reply(format!("Set the game mode of {} to {}", server.get_player_name(id).unwrap_or_default(), mode.get_name()));
+    Ok(())
+}
+
+fn set_block(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let position = arguments[0].get_block_pos();
+    let block = arguments[1].get_block();
+
+    if position.y < 0 || position.y >= Chunk::HEIGHT as BlockAxis {
+        return Err(format!("Can not place a block at height {}, it is outside of the world", position.y));
+    }
+    if !server.set_block(&position, block) {
+        return Err(format!("Can not place a block at {}, {}, {}: it is not in a loaded chunk", position.x, position.y, position.z));
+    }
+
+    context.reply(format!("Placed {} at {}, {}, {}", BlockTypes::get_name(block), position.x, position.y, position.z));
+    Ok(())
+}
+
+fn fill(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let from = arguments[0].get_block_pos();
+    let to = arguments[1].get_block_pos();
+    let block = arguments[2].get_block();
+
+    let min = BlockPos::new(from.x.min(to.x), from.y.min(to.y).max(0), from.z.min(to.z));
+    let max = BlockPos::new(from.x.max(to.x), from.y.max(to.y).min(Chunk::HEIGHT as BlockAxis - 1), from.z.max(to.z));
+
+    let volume = (max.x - min.x + 1).saturating_mul(max.y - min.y + 1).saturating_mul(max.z - min.z + 1);
+    if volume > MAX_FILL_VOLUME {
+        return Err(format!("Can not fill {} blocks, the limit is {}", volume, MAX_FILL_VOLUME));
+    }
+
+    let mut filled = 0;
+    let mut skipped = 0;
+    for y in min.y..max.y + 1 {
+        for z in min.z..max.z + 1 {
+            for x in min.x..max.x + 1 {
+                if server.set_block(&BlockPos::new(x, y, z), block) { filled += 1; } else { skipped += 1; }
+            }
+        }
+    }
+
+    if skipped > 0 {
+        context.reply(format!("Filled {} blocks with {}, {} blocks were skipped in unloaded chunks", filled, BlockTypes::get_name(block), skipped));
+    } else {
+        context.reply(format!("Filled {} blocks with {}", filled, BlockTypes::get_name(block)));
+    }
+    Ok(())
+}
+
+fn sign(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let position = arguments[0].get_block_pos();
+    let text = arguments[1].get_message();
+
+    if text.chars().count() > BlockEntity::MAX_SIGN_LENGTH {
+        return Err(format!("Signs hold at most {} characters", BlockEntity::MAX_SIGN_LENGTH));
+    }
+    if !server.get_world_mut().set_block_entity(&position, BlockEntity::Sign { text: text.to_string() }) {
+        return Err(format!("There is no sign at {}, {}, {}", position.x, position.y, position.z));
+    }
+
+    context.reply(format!("Wrote \"{}\" on the sign at {}, {}, {}", text, position.x, position.y, position.z));
+    Ok(())
+}
+
+fn time_query(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let time = server.get_world().get_time();
+    context.reply(format!("The time is {}, day {} at {}", time, time / day_cycle::DAY_LENGTH + 1, day_cycle::get_time_of_day(time)));
+    Ok(())
+}
+fn time_set(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_time(server, context, arguments[1].get_integer() as u64)
+}
+fn time_add(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let time = server.get_world().get_time().saturating_add(arguments[1].get_integer() as u64);
+    set_time(server, context, time)
+}
+fn time_set_day(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    set_time_of_day(server, context, day_cycle::SUNRISE + 1000)
+}
+fn time_set_noon(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    set_time_of_day(server, context, day_cycle::NOON)
+}
+fn time_set_night(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    set_time_of_day(server, context, day_cycle::SUNSET + 1000)
+}
+fn time_set_midnight(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    set_time_of_day(server, context, day_cycle::MIDNIGHT)
+}
+// Named times keep the day, so setting it to night and back to day does not jump back in time.
+fn set_time_of_day(server: &mut GameServer, context: &mut CommandContext, time_of_day: u64) -> Result<(), String> {
+    let day_start = server.get_world().get_time() - day_cycle::get_time_of_day(server.get_world().get_time());
+    set_time(server, context, day_start + time_of_day)
+}
+fn set_time(server: &mut GameServer, context: &mut CommandContext, time: u64) -> Result<(), String> {
+    server.get_world_mut().set_time(time);
+    server.sync_time();
+    context.reply(format!("Set the time to {}", time));
+    Ok(())
+}
+
+fn water_flow_distance(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    flow_distance(server, context, Liquid::Water, arguments)
+}
+fn lava_flow_distance(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    flow_distance(server, context, Liquid::Lava, arguments)
+}
+// Liquids that already flowed keep their extent until something next to them changes.
+fn flow_distance(server: &mut GameServer, context: &mut CommandContext, liquid: Liquid, arguments: &[ArgumentValue]) -> Result<(), String> {
+    if let Some(distance) = arguments.get(1) {
+        server.get_world_mut().get_rules_mut().set_flow_distance(liquid, distance.get_integer() as u8);
+        context.reply(format!("Set the flow distance of {} to {}", liquid.get_name(), server.get_world().get_rules().get_flow_distance(liquid)));
+    } else {
+        context.reply(format!("The flow distance of {} is {}", liquid.get_name(), server.get_world().get_rules().get_flow_distance(liquid)));
+    }
+    Ok(())
+}
+
+fn random_tick_speed(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    if let Some(speed) = arguments.first() {
+        server.get_world_mut().get_rules_mut().set_random_tick_speed(speed.get_integer() as u32);
+        context.reply(format!("Set the random tick speed to {}", server.get_world().get_rules().random_tick_speed));
+    } else {
+        context.reply(format!("The random tick speed is {}", server.get_world().get_rules().random_tick_speed));
+    }
+    Ok(())
+}
+
+fn weather_query(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let world = server.get_world();
+    context.reply(format!("The weather is {} for {} more ticks", world.get_weather().get_name(), world.get_weather_duration()));
+    Ok(())
+}
+fn weather_clear(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_weather(server, context, Weather::Clear, arguments)
+}
+fn weather_rain(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_weather(server, context, Weather::Rain, arguments)
+}
+fn weather_thunder(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    set_weather(server, context, Weather::Thunder, arguments)
+}
+fn set_weather(server: &mut GameServer, context: &mut CommandContext, weather: Weather, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let duration = arguments.get(1).map(|ticks| ticks.get_integer() as u64);
+    server.get_world_mut().set_weather(weather, duration);
+    context.reply(format!("Set the weather to {} for {} ticks", weather.get_name(), server.get_world().get_weather_duration()));
+    Ok(())
+}
+
+fn save(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    if server.get_world().get_storage().is_none() {
+        return Err(String::from("This world is not stored on disk"));
+    }
+
+    server.save_players();
+    match server.get_world_mut().save() {
+        Ok(chunks) => {
+            context.reply(format!("Saved the world ({} modified chunks)", chunks));
+            Ok(())
+        },
+        Err(error) => Err(format!("Failed to save the world: {}", error)),
+    }
+}
+
+fn force_load_add(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let position = arguments[1].get_block_pos().to_chunk_pos();
+    if !server.get_world_mut().set_chunk_forced(&position, true) {
+        return Err(format!("Chunk ({}, {}) is already force loaded", position.x, position.z));
+    }
+
+    context.reply(format!("Chunk ({}, {}) is now force loaded", position.x, position.z));
+    Ok(())
+}
+fn force_load_remove(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let position = arguments[1].get_block_pos().to_chunk_pos();
+    if !server.get_world_mut().set_chunk_forced(&position, false) {
+        return Err(format!("Chunk ({}, {}) is not force loaded", position.x, position.z));
+    }
+
+    context.reply(format!("Chunk ({}, {}) is no longer force loaded", position.x, position.z));
+    Ok(())
+}
+fn force_load_query(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let forced = server.get_world().get_forced_chunks();
+    if forced.is_empty() {
+        context.reply("No chunks are force loaded");
+    } else {
+        let list: Vec<String> = forced.iter().map(|position| format!("({}, {})", position.x, position.z)).collect();
+        context.reply(format!("Force loaded chunks: {}", list.join(", ")));
+    }
+
+    let position = ChunkPos::new(context.origin.get_chunk_x(), context.origin.get_chunk_z());
+    match server.get_world().get_chunk_level(&position) {
+        Some(level) => context.reply(format!("Chunk ({}, {}) is loaded at level {:?}", position.x, position.z, level)),
+        None => context.reply(format!("Chunk ({}, {}) has no tickets", position.x, position.z)),
+    }
+    Ok(())
+}
+
+fn network_stats(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    let stats = server.get_compression_stats();
+    if stats.get_packets().is_empty() {
+        context.reply("No packets were sent yet");
+        return Ok(());
+    }
+
+    for (id, packet) in stats.get_packets() {
+        context.reply(format!(
+            "{}: {} packets, {:.1} KiB -> {:.1} KiB ({:.1}%)",
+            ServerPacket::get_name(*id),
+            packet.count,
+            packet.raw_bytes as f64 / 1024.0,
+            packet.sent_bytes as f64 / 1024.0,
+            packet.sent_bytes as f64 / packet.raw_bytes.max(1) as f64 * 100.0,
+        ));
+    }
+    Ok(())
+}
+
+fn stop(server: &mut GameServer, context: &mut CommandContext, _: &[ArgumentValue]) -> Result<(), String> {
+    context.reply("Stopping the server");
+    server.stop();
+    Ok(())
+}
+
+fn op(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let name = server.get_player_name(arguments[0].get_player()).unwrap_or_default();
+    server.add_operator(&name);
+    context.reply(format!("{} is now an operator", name));
+    Ok(())
+}
+fn deop(server: &mut GameServer, context: &mut CommandContext, arguments: &[ArgumentValue]) -> Result<(), String> {
+    let name = server.get_player_name(arguments[0].get_player()).unwrap_or_default();
+    server.remove_operator(&name);
+    context.reply(format!("{} is no longer an operator", name));
+    Ok(())
+}
@@ -0,0 +1,192 @@
+pub mod argument;
+pub mod commands;
+
+use std::collections::BTreeMap;
+
+use argument::{Argument, ArgumentValue};
+
+use crate::game::common::{coords::Coord, player::PlayerId};
+
+use super::game_server::GameServer;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    Player,
+    Operator,
+    Console,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    Console,
+    Player(PlayerId),
+}
+
+pub struct CommandContext {
+    pub source: CommandSource,
+    // The position `~` coordinates are relative to.
+    pub origin: Coord,
+
+    feedback: Vec<String>,
+}
+
+impl CommandContext {
+    pub fn create(source: CommandSource, origin: Coord) -> Self {
+        Self { source, origin, feedback: vec![] }
+    }
+
+    pub fn reply<S: Into<String>>(&mut self, message: S) {
+        self.feedback.push(message.into());
+    }
+    pub fn into_feedback(self) -> Vec<String> {
+        self.feedback
+    }
+}
+
+pub type CommandExecutor = fn(&mut GameServer, &mut CommandContext, &[ArgumentValue]) -> Result<(), String>;
+
+struct CommandUsage {
+    arguments: Vec<Argument>,
+    executor: CommandExecutor,
+}
+
+pub struct Command {
+    name: &'static str,
+    description: &'static str,
+    permission: PermissionLevel,
+    usages: Vec<CommandUsage>,
+}
+
+impl Command {
+    pub fn new(name: &'static str, description: &'static str, permission: PermissionLevel) -> Self {
+        Self { name, description, permission, usages: vec![] }
+    }
+
+    // Usages are tried in the order they were added, the first one that parses is executed.
+    pub fn with_usage(mut self, arguments: Vec<Argument>, executor: CommandExecutor) -> Self {
+        self.usages.push(CommandUsage { arguments, executor });
+        self
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        self.name
+    }
+    pub fn get_description(&self) -> &'static str {
+        self.description
+    }
+    pub fn get_usages(&self) -> Vec<String> {
+        self.usages.iter().map(|usage| {
+            let mut text = format!("/{}", self.name);
+            for argument in &usage.arguments {
+                text.push(' ');
+                text.push_str(&argument.get_usage());
+            }
+            text
+        }).collect()
+    }
+}
+
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl CommandRegistry {
+    pub fn create() -> Self {
+        Self { commands: BTreeMap::new() }
+    }
+
+    pub fn register(&mut self, command: Command) {
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn get_commands(&self, permission: PermissionLevel) -> impl Iterator<Item = &Command> {
+        self.commands.values().filter(move |command| command.permission <= permission)
+    }
+
+    // Splits the arguments into tokens per argument, or returns None if the count does not fit the usage.
+    fn split_tokens<'a, 'b>(arguments: &[Argument], tokens: &'b [&'a str]) -> Option<Vec<&'b [&'a str]>> {
+        let mut groups = vec![];
+        let mut cursor = 0;
+
+        for argument in arguments {
+            let count = argument.get_token_count().unwrap_or(tokens.len().saturating_sub(cursor).max(1));
+            groups.push(tokens.get(cursor..cursor + count)?);
+            cursor += count;
+        }
+
+        if cursor == tokens.len() { Some(groups) } else { None }
+    }
+
+    // Resolves the input (without the leading slash) into an executor and its parsed arguments.
+    pub fn parse(&self, server: &GameServer, context: &CommandContext, permission: PermissionLevel, input: &str) -> Result<(CommandExecutor, Vec<ArgumentValue>), String> {
+        let tokens: Vec<&str> = input.split_whitespace().collect();
+        let Some(name) = tokens.first() else {
+            return Err(String::from("Type /help for a list of commands"));
+        };
+
+        let command = match self.commands.get(name.to_ascii_lowercase().as_str()) {
+            Some(command) if command.permission <= permission => command,
+            Some(_) => return Err(String::from("You do not have permission to use this command")),
+            None => return Err(format!("Unknown command: {}. Type /help for a list of commands", name)),
+        };
+
+        let mut error = None;
+        for usage in &command.usages {
+            let Some(groups) = Self::split_tokens(&usage.arguments, &tokens[1..]) else { continue; };
+
+            let values: Result<Vec<ArgumentValue>, String> = usage.arguments.iter().zip(groups)
+                .map(|(argument, group)| argument.parse(server, &context.origin, group))
+                .collect();
+
+            match values {
+                Ok(values) => return Ok((usage.executor, values)),
+                Err(message) => { error.get_or_insert(message); },
+            }
+        }
+
+        Err(error.unwrap_or_else(|| format!("Usage: {}", command.get_usages().join(" or "))))
+    }
+
+    // Returns replacements for the last token of the input (without the leading slash).
+    pub fn complete(&self, server: &GameServer, permission: PermissionLevel, input: &str) -> Vec<String> {
+        let mut tokens: Vec<&str> = input.split_whitespace().collect();
+        if input.is_empty() || input.ends_with(char::is_whitespace) {
+            tokens.push("");
+        }
+        let prefix = tokens[tokens.len() - 1];
+
+        if tokens.len() == 1 {
+            return self.get_commands(permission)
+                .map(|command| command.get_name())
+                .filter(|name| name.starts_with(&prefix.to_ascii_lowercase()))
+                .map(String::from)
+                .collect();
+        }
+
+        let Some(command) = self.commands.get(tokens[0].to_ascii_lowercase().as_str()) else { return vec![]; };
+        if command.permission > permission {
+            return vec![];
+        }
+
+        let token_index = tokens.len() - 2;
+        let mut completions: Vec<String> = vec![];
+
+        for usage in &command.usages {
+            let mut first_token: usize = 0;
+            for argument in &usage.arguments {
+                let count = argument.get_token_count().unwrap_or(usize::MAX);
+                if token_index < first_token.saturating_add(count) {
+                    for completion in argument.complete(server, token_index - first_token, prefix) {
+                        if !completions.contains(&completion) {
+                            completions.push(completion);
+                        }
+                    }
+                    break;
+                }
+                first_token = first_token.saturating_add(count);
+            }
+        }
+
+        completions
+    }
+}
@@ -0,0 +1,27 @@
+use std::{io::BufRead, sync::mpsc::{self, Receiver}};
+
+// Reads lines from stdin on a separate thread, so the server loop never blocks on input.
+pub struct Console {
+    receiver: Receiver<String>,
+}
+
+impl Console {
+    pub fn create() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else { break; };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { receiver }
+    }
+
+    pub fn poll(&self) -> Option<String> {
+        self.receiver.try_recv().ok()
+    }
+}
@@ -0,0 +1,82 @@
+use cgmath::Vector3;
+
+use crate::game::common::{coords::{BlockAxis, BlockPos, ChunkPos, Coord, CoordAxis}, entity::EntityKind, protocol::buffer::PacketWriter, world::{block_types::BlockTypes, block_view::BlockView}};
+
+pub struct Entity {
+    pub kind: EntityKind,
+    // The bottom center of the entity.
+    pub position: Coord,
+    velocity_y: CoordAxis,
+    is_on_ground: bool,
+    // Ticks since it was spawned.
+    pub age: u64,
+    // Set when the position changed since players were last told.
+    pub has_moved: bool,
+}
+
+impl Entity {
+    // Blocks per tick squared and the part of the velocity kept every tick, like falling blocks in Minecraft.
+    const GRAVITY: CoordAxis = 0.04;
+    const DRAG: CoordAxis = 0.98;
+    // Items lying around for five minutes disappear.
+    pub const ITEM_LIFETIME: u64 = 6000;
+
+    pub fn create(kind: EntityKind, position: Coord) -> Self {
+        Self {
+            kind,
+            position,
+            velocity_y: 0.0,
+            is_on_ground: false,
+            age: 0,
+            has_moved: false,
+        }
+    }
+
+    // Everything about the entity, for the state hash of the world.
+    pub fn write(&self, writer: &mut PacketWriter) {
+        writer.write_u8(self.kind.get_id());
+        writer.write_u16(self.kind.get_data());
+        writer.write_coord(&self.position);
+        writer.write_f64(self.velocity_y);
+        writer.write_u8(self.is_on_ground as u8);
+        writer.write_u64(self.age);
+    }
+
+    pub fn get_chunk_pos(&self) -> ChunkPos {
+        ChunkPos::new(self.position.get_chunk_x(), self.position.get_chunk_z())
+    }
+    pub fn get_block_pos(&self) -> BlockPos {
+        BlockPos::new(self.position.get_block_x(), self.position.get_world_y().floor() as BlockAxis, self.position.get_block_z())
+    }
+
+    // Falls until the bottom touches a block, returns true on the tick it lands.
+    pub fn fall(&mut self, world: &dyn BlockView) -> bool {
+        self.age += 1;
+        self.velocity_y = (self.velocity_y - Self::GRAVITY) * Self::DRAG;
+
+        let y = self.position.get_world_y();
+        let target_y = y + self.velocity_y;
+
+        // The first solid block below whose top is passed on the way down stops the fall, liquids are fallen through.
+        let (x, z) = (self.position.get_block_x(), self.position.get_block_z());
+        let mut block_y = y.ceil() as BlockAxis - 1;
+        while block_y as CoordAxis + 1.0 > target_y {
+            let is_below = block_y as CoordAxis + 1.0 <= y + 1e-6;
+            if is_below && BlockTypes::is_solid(world.get_block(&BlockPos::new(x, block_y, z))) {
+                let was_on_ground = self.is_on_ground;
+                self.is_on_ground = true;
+                self.velocity_y = 0.0;
+                self.has_moved |= block_y as CoordAxis + 1.0 != y;
+                self.position += Vector3::new(0.0, block_y as CoordAxis + 1.0 - y, 0.0);
+
+                return !was_on_ground;
+            }
+            block_y -= 1;
+        }
+
+        self.is_on_ground = false;
+        self.has_moved = true;
+        self.position += Vector3::new(0.0, self.velocity_y, 0.0);
+        false
+    }
+}
@@ -0,0 +1,1113 @@
+use std::{collections::{BTreeMap, HashMap}, net::{SocketAddr, TcpListener, ToSocketAddrs}, time::{Duration, Instant}};
+
+use cgmath::Vector3;
+
+use crate::game::common::{coords::{BlockAxis, BlockPos, ChunkAxis, ChunkPos, Coord, CoordAxis, Direction, LocalBlockAxis, LocalBlockPos, WORLD_BORDER}, entity::{EntityId, EntityKind}, item::{ItemId, ItemTypes}, physics::PlayerBody, player::{GameMode, PlayerId, MAX_HEALTH}, protocol::{buffer::PacketWriter, connection::{ChannelConnection, Connection, TcpConnection}, packet::{BlockEditRejection, ClientPacket, ServerPacket}, stream::{CompressionStats, PacketStream}, PROTOCOL_VERSION}, weather::Weather, world::{block_types::BlockTypes, liquid::Liquid}};
+
+use super::{block_edit::{self, BlockEdit}, command::{commands, CommandContext, CommandRegistry, CommandSource, PermissionLevel}, lan_announcer::LanAnnouncer, player::ServerPlayer, recording::{SessionEvent, SessionHeader, SessionRecorder}, remote_console::RemoteConsole, tick_metrics::TickMetrics, world::{block_behaviour, chunk::Chunk, farming, storage::{PlayerData, WorldStorage}, ticket::{ChunkLevel, Ticket, TicketHolder}}, ServerWorld};
+
+// A connection that has not logged in yet.
+struct PendingConnection {
+    stream: PacketStream,
+    connection_id: u32,
+    compression_threshold: usize,
+    is_handshaken: bool,
+}
+
+pub struct GameServer {
+    world: ServerWorld,
+    listener: Option<TcpListener>,
+    lan_announcer: Option<LanAnnouncer>,
+    remote_console: Option<RemoteConsole>,
+
+    pending_connections: Vec<PendingConnection>,
+    next_connection_id: u32,
+    // Sorted, so players are always handled in the same order and a replay matches the original.
+    players: BTreeMap<PlayerId, ServerPlayer>,
+    next_player_id: PlayerId,
+    // Player data a replay took from the recording, used instead of the storage when they join.
+    stored_players: HashMap<String, PlayerData>,
+
+    commands: CommandRegistry,
+    operators: Vec<String>,
+    // The connection of whoever hosts the world, it can use every command but is never saved as an operator.
+    host_connection: Option<u32>,
+    running: bool,
+    // The weather players were last told about.
+    sent_weather: Weather,
+
+    // Stats of players that already left, the rest is still on their streams.
+    compression_stats: CompressionStats,
+    recorder: Option<SessionRecorder>,
+
+    // Everything that depends on time uses the server time, which a replay can control.
+    started_at: Instant,
+    time: Duration,
+    last_tick: Duration,
+    tick_metrics: TickMetrics,
+}
+
+impl GameServer {
+    pub const TICKS_PER_SECOND: u32 = 20;
+    pub const TICK_DURATION: Duration = Duration::from_millis(1000 / Self::TICKS_PER_SECOND as u64);
+    // After a stall the server runs at most this many ticks in one update to catch up, the rest are skipped.
+    const MAX_CATCH_UP_TICKS: u32 = 10;
+    pub const MAX_VIEW_DISTANCE: usize = 32;
+    pub const MAX_CHAT_LENGTH: usize = 256;
+    const MAX_CHUNKS_PER_UPDATE: usize = 4;
+    // Past this many changes in a section, sending the whole section is smaller than listing them.
+    const FULL_SECTION_THRESHOLD: usize = 512;
+    // Clients advance the time themselves, it is sent this often so they don't drift.
+    const TIME_SYNC_INTERVAL: u64 = Self::TICKS_PER_SECOND as u64;
+
+    // A move further than this in one packet is not believed, not even flying fast or falling during a long frame.
+    const MAX_MOVE_DISTANCE: CoordAxis = 10.0;
+
+    // Falls up to this many blocks do no harm, every block further costs one health.
+    const SAFE_FALL_DISTANCE: CoordAxis = 3.0;
+    // Items this close to the feet of a survival player are picked up, once they lay for a moment.
+    const PICKUP_RANGE: CoordAxis = 1.5;
+    const PICKUP_DELAY: u64 = 10;
+    // Lava and fire hurt every this many ticks, health comes back one point at a time.
+    const HAZARD_INTERVAL: u64 = 10;
+    const LAVA_DAMAGE: u8 = 4;
+    const FIRE_DAMAGE: u8 = 1;
+    const REGENERATION_INTERVAL: u64 = 80;
+
+    pub fn create(world: ServerWorld) -> Self {
+        let mut commands = CommandRegistry::create();
+        commands::register_all(&mut commands);
+
+        let operators = world.get_storage().map(|storage| storage.load_operators()).unwrap_or_default();
+        let sent_weather = world.get_weather();
+
+        Self {
+            world,
+            listener: None,
+            lan_announcer: None,
+            remote_console: None,
+
+            pending_connections: vec![],
+            next_connection_id: 0,
+            players: BTreeMap::new(),
+            next_player_id: 0,
+            stored_players: HashMap::new(),
+
+            commands,
+            operators,
+            host_connection: None,
+            running: true,
+            sent_weather,
+
+            compression_stats: CompressionStats::default(),
+            recorder: None,
+
+            started_at: Instant::now(),
+            time: Duration::ZERO,
+            last_tick: Duration::ZERO,
+            tick_metrics: TickMetrics::create(),
+        }
+    }
+
+    pub fn bind<A: ToSocketAddrs>(&mut self, address: A) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        let address = listener.local_addr()?;
+        self.listener = Some(listener);
+
+        Ok(address)
+    }
+
+    // Starts broadcasting the server to clients on the local network, it needs to be bound first.
+    pub fn announce_on_lan(&mut self, name: String) -> std::io::Result<()> {
+        self.lan_announcer = Some(LanAnnouncer::create(name)?);
+        Ok(())
+    }
+
+    // Lets admins run commands over a password protected socket, see RemoteConsole.
+    pub fn enable_remote_console<A: ToSocketAddrs>(&mut self, address: A, password: String) -> std::io::Result<SocketAddr> {
+        let console = RemoteConsole::bind(address, password)?;
+        let address = console.get_address()?;
+        self.remote_console = Some(console);
+
+        Ok(address)
+    }
+
+    fn handle_remote_commands(&mut self) {
+        let Some(console) = &mut self.remote_console else { return; };
+
+        for request in console.poll() {
+            println!("Remote console ({}) issued command: {}", request.address, request.command);
+            let feedback = self.execute_command(CommandSource::Console, &request.command);
+
+            // The console stays open after /stop, so whoever stopped the server still gets the answer.
+            if let Some(console) = &mut self.remote_console {
+                console.reply(request.client_id, &feedback);
+            }
+        }
+
+        if let Some(console) = &mut self.remote_console {
+            console.flush();
+        }
+    }
+
+    // Records every input and output of the server from now on, so the session can be replayed.
+    // It has to start before the first update, a replay begins with a fresh server.
+    pub fn start_recording<P: AsRef<std::path::Path>>(&mut self, path: P) -> std::io::Result<()> {
+        let header = self.get_session_header();
+        self.set_recorder(SessionRecorder::create(path, &header)?);
+        Ok(())
+    }
+    pub fn get_session_header(&self) -> SessionHeader {
+        SessionHeader {
+            seed: self.world.get_seed(),
+            time: self.world.get_time(),
+            rules: self.world.get_rules().clone(),
+            weather: self.world.get_weather(),
+            weather_duration: self.world.get_weather_duration(),
+            operators: self.operators.clone(),
+            forced_chunks: self.world.get_forced_chunks(),
+        }
+    }
+    pub fn set_recorder(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(recorder);
+    }
+    pub fn get_recorder_mut(&mut self) -> Option<&mut SessionRecorder> {
+        self.recorder.as_mut()
+    }
+    fn record(&mut self, event: SessionEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(event);
+        }
+    }
+    fn record_sent(recorder: &mut Option<SessionRecorder>, connection: u32, stream: &mut PacketStream) {
+        if let Some(recorder) = recorder {
+            for packet in stream.take_sent() {
+                recorder.record(SessionEvent::Sent { connection, packet });
+            }
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.update_at(self.started_at.elapsed());
+    }
+    // Runs an update as if the given time passed since the server started, replays use it to repeat the original timing.
+    pub fn update_at(&mut self, time: Duration) {
+        if !self.running {
+            return;
+        }
+        self.time = time;
+        self.record(SessionEvent::UpdateStart { time });
+
+        self.handle_remote_commands();
+        if self.running {
+            self.update_world();
+        }
+
+        self.record(SessionEvent::UpdateEnd);
+    }
+    fn update_world(&mut self) {
+        self.accept_connections();
+        self.handle_logins();
+        self.handle_packets();
+        self.remove_disconnected_players();
+
+        self.run_ticks();
+
+        for player in self.players.values() {
+            let ticket = Ticket::new(player.get_chunk_pos(), player.view_distance / 2, ChunkLevel::Ticking);
+            self.world.set_ticket(TicketHolder::Player(player.get_id()), ticket);
+        }
+        for (position, from_storage) in self.world.update_chunks(self.time) {
+            if let Some(recorder) = &mut self.recorder {
+                let data = self.world.get_chunk(&position).map(|chunk| WorldStorage::encode_chunk(chunk, self.world.get_tick_count()));
+                recorder.record_chunk_load(&position, if from_storage { data.as_deref() } else { None });
+            }
+        }
+        self.stream_chunks();
+
+        for player in self.players.values_mut() {
+            if let Some(sequence) = player.acknowledged_edit.take() {
+                player.send(ServerPacket::BlockEditAcknowledged { sequence });
+            }
+            player.flush();
+            Self::record_sent(&mut self.recorder, player.get_connection_id(), player.get_stream_mut());
+        }
+        for pending in &mut self.pending_connections {
+            Self::record_sent(&mut self.recorder, pending.connection_id, &mut pending.stream);
+        }
+
+        let port = self.listener.as_ref().and_then(|listener| listener.local_addr().ok()).map(|address| address.port());
+        if let (Some(announcer), Some(port)) = (&mut self.lan_announcer, port) {
+            announcer.update(self.players.len(), port);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+    // Disconnects every player and saves the world, the server does nothing after this.
+    pub fn stop(&mut self) {
+        if !self.running {
+            return;
+        }
+        self.running = false;
+        // Taken before the players are gone, their state is part of it.
+        let world_hash = self.get_state_hash();
+
+        self.save_players();
+        for player in self.players.values_mut() {
+            player.send(ServerPacket::Disconnect { reason: String::from("Server closed") });
+            player.flush();
+            Self::record_sent(&mut self.recorder, player.get_connection_id(), player.get_stream_mut());
+        }
+        self.players.clear();
+        self.listener = None;
+        self.lan_announcer = None;
+
+        match self.world.save() {
+            Ok(chunks) => if self.world.get_storage().is_some() { println!("Saved the world ({} modified chunks).", chunks) },
+            Err(error) => println!("Failed to save the world. Error: {}.", error),
+        }
+
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(SessionEvent::End { world_hash });
+            recorder.finish();
+        }
+    }
+
+    // The state hash of the world with where every player is and what they have added to it.
+    pub fn get_state_hash(&self) -> u64 {
+        let mut writer = PacketWriter::create();
+        for player in self.players.values() {
+            writer.write_u32(player.get_id());
+            writer.write_string(player.get_name());
+            writer.write_coord(&player.position);
+            writer.write_f32(player.yaw);
+            writer.write_f32(player.pitch);
+            writer.write_f64(player.fall_distance);
+            writer.write_bytes(&WorldStorage::encode_player(&player.get_data()));
+        }
+        self.world.get_state_hash(&writer.into_bytes())
+    }
+
+    pub fn get_compression_stats(&self) -> CompressionStats {
+        let mut stats = self.compression_stats.clone();
+        for player in self.players.values() {
+            stats.merge(player.get_compression_stats());
+        }
+
+        stats
+    }
+
+    pub fn get_world(&self) -> &ServerWorld {
+        &self.world
+    }
+    pub fn get_world_mut(&mut self) -> &mut ServerWorld {
+        &mut self.world
+    }
+    pub fn get_commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    pub fn find_player(&self, name: &str) -> Option<PlayerId> {
+        self.players.values().find(|player| player.get_name().eq_ignore_ascii_case(name)).map(|player| player.get_id())
+    }
+    pub fn get_player_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.players.values().map(|player| player.get_name().to_string()).collect();
+        names.sort();
+        names
+    }
+    pub fn get_player_name(&self, id: PlayerId) -> Option<String> {
+        self.players.get(&id).map(|player| player.get_name().to_string())
+    }
+    pub fn get_player_position(&self, id: PlayerId) -> Option<Coord> {
+        self.players.get(&id).map(|player| player.position.clone())
+    }
+
+    pub fn teleport(&mut self, id: PlayerId, position: Coord) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.position = position.clone();
+            player.has_moved = true;
+            player.fall_distance = 0.0;
+            player.send(ServerPacket::Teleport { position });
+        }
+    }
+    pub fn set_game_mode(&mut self, id: PlayerId, mode: GameMode) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.game_mode = mode;
+            player.fall_distance = 0.0;
+            player.breaking = None;
+            player.send(ServerPacket::GameModeChanged { mode });
+        }
+    }
+    // Changes a block, every player that can see it is told at the next tick.
+    pub fn set_block(&mut self, position: &BlockPos, block: u8) -> bool {
+        self.world.set_block(position, block)
+    }
+
+    pub fn broadcast_message(&mut self, message: &str) {
+        println!("{}", message);
+        self.broadcast(ServerPacket::ChatMessage { message: message.to_string() });
+    }
+
+    // Data from a replay comes first, the storage is read when the player joins so a recording can hold what was read.
+    fn load_player_data(&mut self, name: &str) -> PlayerData {
+        if let Some(data) = self.stored_players.remove(&name.to_ascii_lowercase()) {
+            return data;
+        }
+
+        let Some(data) = self.world.get_storage().and_then(|storage| storage.load_player(name)) else { return PlayerData::create(); };
+        self.record(SessionEvent::StoredPlayer { name: name.to_string(), data: WorldStorage::encode_player(&data) });
+        data
+    }
+    pub fn insert_stored_player(&mut self, name: &str, data: PlayerData) {
+        self.stored_players.insert(name.to_ascii_lowercase(), data);
+    }
+    fn save_player(&self, player: &ServerPlayer) {
+        if let Some(Err(error)) = self.world.get_storage().map(|storage| storage.save_player(player.get_name(), &player.get_data())) {
+            println!("Failed to save player {}. Error: {}.", player.get_name(), error);
+        }
+    }
+    // Players that left were saved when they did.
+    pub fn save_players(&self) {
+        for player in self.players.values() {
+            self.save_player(player);
+        }
+    }
+
+    pub fn is_operator(&self, name: &str) -> bool {
+        self.operators.iter().any(|operator| operator.eq_ignore_ascii_case(name))
+    }
+    pub fn add_operator(&mut self, name: &str) {
+        if !self.is_operator(name) {
+            self.operators.push(name.to_string());
+            self.save_operators();
+        }
+    }
+    pub fn remove_operator(&mut self, name: &str) {
+        self.operators.retain(|operator| !operator.eq_ignore_ascii_case(name));
+        self.save_operators();
+    }
+    fn save_operators(&self) {
+        if let Some(Err(error)) = self.world.get_storage().map(|storage| storage.save_operators(&self.operators)) {
+            println!("Failed to save operators. Error: {}.", error);
+        }
+    }
+
+    pub fn get_permission_level(&self, source: CommandSource) -> PermissionLevel {
+        match source {
+            CommandSource::Console => PermissionLevel::Console,
+            CommandSource::Player(id) => match self.players.get(&id) {
+                Some(player) if self.host_connection == Some(player.get_connection_id()) || self.is_operator(player.get_name()) => PermissionLevel::Operator,
+                _ => PermissionLevel::Player,
+            },
+        }
+    }
+
+    // Runs a command line (the leading slash is optional) and returns the lines of feedback for the source.
+    pub fn execute_command(&mut self, source: CommandSource, input: &str) -> Vec<String> {
+        // Commands from players are recorded as the packets they came in.
+        if source == CommandSource::Console {
+            self.record(SessionEvent::ConsoleCommand { command: input.to_string() });
+        }
+
+        let input = input.trim().strip_prefix('/').unwrap_or(input.trim());
+        let origin = match source {
+            CommandSource::Console => self.world.get_spawn_position(),
+            CommandSource::Player(id) => self.get_player_position(id).unwrap_or_else(|| self.world.get_spawn_position()),
+        };
+        let mut context = CommandContext::create(source, origin);
+
+        let result = self.commands.parse(self, &context, self.get_permission_level(source), input)
+            .and_then(|(executor, arguments)| executor(self, &mut context, &arguments));
+        if let Err(message) = result {
+            context.reply(message);
+        }
+
+        context.into_feedback()
+    }
+    pub fn complete_command(&self, source: CommandSource, input: &str) -> Vec<String> {
+        let input = input.trim_start().strip_prefix('/').unwrap_or(input.trim_start());
+        self.commands.complete(self, self.get_permission_level(source), input)
+    }
+
+    fn handle_chat(&mut self, id: PlayerId, message: String) {
+        let message: String = message.chars().filter(|character| !character.is_control()).take(Self::MAX_CHAT_LENGTH).collect();
+        let message = message.trim();
+        if message.is_empty() {
+            return;
+        }
+
+        if message.starts_with('/') {
+            let name = self.get_player_name(id).unwrap_or_default();
+            println!("{} issued command: {}", name, message);
+
+            for line in self.execute_command(CommandSource::Player(id), message) {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player.send(ServerPacket::ChatMessage { message: line });
+                }
+            }
+            return;
+        }
+
+        let name = self.get_player_name(id).unwrap_or_default();
+        self.broadcast_message(&format!("<{}> {}", name, message));
+    }
+
+    fn accept_connections(&mut self) {
+        let Some(listener) = &self.listener else { return; };
+
+        let mut connections = vec![];
+        while let Ok((stream, _)) = listener.accept() {
+            match TcpConnection::create(stream) {
+                Ok(connection) => connections.push(connection),
+                Err(error) => println!("Failed to accept connection. Error: {}.", error),
+            }
+        }
+
+        for connection in connections {
+            self.add_connection(Box::new(connection), PacketStream::DEFAULT_COMPRESSION_THRESHOLD, false);
+        }
+    }
+
+    // Opens a connection from the same process. It goes through the same handshake and login as a TCP client,
+    // only compression is pointless without a network in between.
+    // It is the connection of whoever hosts the world, which can use every command for as long as the server runs.
+    // That isn't tied to a name, so somebody joining over the network under the host's name gets nothing.
+    pub fn connect_host(&mut self) -> ChannelConnection {
+        let (client, server) = ChannelConnection::pair();
+        self.add_connection(Box::new(server), u32::MAX as usize, true);
+
+        client
+    }
+    // Starts the handshake with a new client, connections are numbered in the order they are added.
+    pub fn add_connection(&mut self, connection: Box<dyn Connection>, compression_threshold: usize, is_host: bool) {
+        let connection_id = self.next_connection_id;
+        self.next_connection_id += 1;
+        self.record(SessionEvent::Connect { connection: connection_id, compression_threshold: compression_threshold as u32, is_host });
+        if is_host {
+            self.host_connection = Some(connection_id);
+        }
+
+        let mut stream = PacketStream::create(connection);
+        if self.recorder.is_some() {
+            stream.log_sent_packets();
+        }
+
+        self.pending_connections.push(PendingConnection { stream, connection_id, compression_threshold, is_handshaken: false });
+    }
+
+    fn handle_logins(&mut self) {
+        let mut still_pending = vec![];
+
+        for mut pending in std::mem::take(&mut self.pending_connections) {
+            let Some(bytes) = pending.stream.receive() else {
+                if pending.stream.is_open() {
+                    still_pending.push(pending);
+                } else {
+                    self.record(SessionEvent::Disconnect { connection: pending.connection_id });
+                }
+                continue;
+            };
+            self.record(SessionEvent::Received { connection: pending.connection_id, packet: bytes.clone() });
+
+            match (pending.is_handshaken, ClientPacket::decode(&bytes)) {
+                (false, Some(ClientPacket::Handshake { protocol_version, registry_hash })) => {
+                    if let Err(reason) = Self::validate_handshake(protocol_version, registry_hash) {
+                        self.reject(pending, reason);
+                        continue;
+                    }
+
+                    pending.stream.send(ServerPacket::Handshake {
+                        protocol_version: PROTOCOL_VERSION,
+                        registry_hash: BlockTypes::get_registry_hash(),
+                        compression_threshold: pending.compression_threshold as u32,
+                    }.encode());
+                    pending.stream.flush();
+                    pending.stream.enable_compression(pending.compression_threshold);
+
+                    pending.is_handshaken = true;
+                    still_pending.push(pending);
+                },
+                (true, Some(ClientPacket::Login { name, view_distance })) => {
+                    if let Err(reason) = self.validate_name(&name) {
+                        self.reject(pending, reason);
+                        continue;
+                    }
+
+                    self.join(name, pending, view_distance as usize);
+                },
+                (false, _) => self.reject(pending, String::from("Expected a handshake packet")),
+                (true, _) => self.reject(pending, String::from("Expected a login packet")),
+            }
+        }
+
+        self.pending_connections.extend(still_pending);
+    }
+
+    fn reject(&mut self, mut pending: PendingConnection, reason: String) {
+        pending.stream.send(ServerPacket::Disconnect { reason }.encode());
+        pending.stream.flush();
+        Self::record_sent(&mut self.recorder, pending.connection_id, &mut pending.stream);
+    }
+
+    fn validate_handshake(protocol_version: u32, registry_hash: u64) -> Result<(), String> {
+        if protocol_version < PROTOCOL_VERSION {
+            return Err(format!("Outdated client: the server uses protocol version {}, but you use {}", PROTOCOL_VERSION, protocol_version));
+        }
+        if protocol_version > PROTOCOL_VERSION {
+            return Err(format!("Outdated server: the server uses protocol version {}, but you use {}", PROTOCOL_VERSION, protocol_version));
+        }
+        if registry_hash != BlockTypes::get_registry_hash() {
+            return Err(String::from("Block registry mismatch: the server has different blocks than you, use the same game version"));
+        }
+
+        Ok(())
+    }
+
+    fn validate_name(&self, name: &str) -> Result<(), String> {
+        if name.is_empty() || name.len() > 16 || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(String::from("Name must be 1 to 16 letters, digits or underscores"));
+        }
+        if self.players.values().any(|player| player.get_name().eq_ignore_ascii_case(name)) {
+            return Err(format!("A player named {} is already playing", name));
+        }
+
+        Ok(())
+    }
+
+    fn join(&mut self, name: String, pending: PendingConnection, view_distance: usize) {
+        let id = self.next_player_id;
+        self.next_player_id += 1;
+
+        let position = self.world.get_spawn_position();
+        let data = self.load_player_data(&name);
+        let mut player = ServerPlayer::create(id, name, pending.stream, pending.connection_id, position.clone(), view_distance.min(Self::MAX_VIEW_DISTANCE), data);
+        player.send(ServerPacket::LoginSuccess { player_id: id, position });
+        player.send(ServerPacket::GameModeChanged { mode: player.game_mode });
+        player.send(ServerPacket::HealthChanged { health: player.health });
+        player.send(ServerPacket::InventoryChanged { inventory: player.inventory.clone() });
+        player.send(ServerPacket::TimeUpdate { time: self.world.get_time() });
+        player.send(ServerPacket::WeatherChanged { weather: self.world.get_weather() });
+
+        for other in self.players.values_mut() {
+            player.send(ServerPacket::PlayerJoined {
+                player_id: other.get_id(),
+                name: other.get_name().to_string(),
+                position: other.position.clone(),
+                yaw: other.yaw,
+                pitch: other.pitch,
+            });
+            other.send(ServerPacket::PlayerJoined {
+                player_id: id,
+                name: player.get_name().to_string(),
+                position: player.position.clone(),
+                yaw: player.yaw,
+                pitch: player.pitch,
+            });
+        }
+
+        println!("{} joined the game.", player.get_name());
+        self.players.insert(id, player);
+    }
+
+    fn remove_disconnected_players(&mut self) {
+        let disconnected: Vec<PlayerId> = self.players.values()
+            .filter(|player| !player.is_connected())
+            .map(|player| player.get_id())
+            .collect();
+
+        for id in disconnected {
+            self.world.remove_ticket(&TicketHolder::Player(id));
+            if let Some(mut player) = self.players.remove(&id) {
+                println!("{} left the game.", player.get_name());
+                self.save_player(&player);
+                Self::record_sent(&mut self.recorder, player.get_connection_id(), player.get_stream_mut());
+                self.record(SessionEvent::Disconnect { connection: player.get_connection_id() });
+                self.compression_stats.merge(player.get_compression_stats());
+            }
+            self.broadcast(ServerPacket::PlayerLeft { player_id: id });
+        }
+    }
+
+    fn broadcast(&mut self, packet: ServerPacket) {
+        for player in self.players.values_mut() {
+            player.send(packet.clone());
+        }
+    }
+    fn broadcast_to_chunk_viewers(&mut self, position: &ChunkPos, packet: ServerPacket) {
+        for player in self.players.values_mut() {
+            if player.sent_chunks.contains(position) {
+                player.send(packet.clone());
+            }
+        }
+    }
+
+    // Runs every tick that is due, so the simulation keeps its rate however often the server is updated.
+    fn run_ticks(&mut self) {
+        let mut ticks = 0;
+        while self.time.saturating_sub(self.last_tick) >= Self::TICK_DURATION {
+            if ticks >= Self::MAX_CATCH_UP_TICKS {
+                let behind = self.time - self.last_tick;
+                let skipped = (behind.as_nanos() / Self::TICK_DURATION.as_nanos()) as u32;
+                println!("Can't keep up! The server is {} ms behind, skipping {} ticks.", behind.as_millis(), skipped);
+
+                self.last_tick += Self::TICK_DURATION * skipped;
+                self.tick_metrics.record_skipped(skipped as u64);
+                break;
+            }
+
+            self.last_tick += Self::TICK_DURATION;
+            self.tick();
+            ticks += 1;
+        }
+    }
+    fn tick(&mut self) {
+        let started_at = Instant::now();
+
+        self.world.tick();
+        self.tick_players();
+        self.broadcast_movement();
+        self.broadcast_block_changes();
+        self.broadcast_block_entities();
+        self.broadcast_entities();
+        if self.world.get_tick_count().is_multiple_of(Self::TIME_SYNC_INTERVAL) {
+            self.sync_time();
+        }
+        if self.world.get_weather() != self.sent_weather {
+            self.sent_weather = self.world.get_weather();
+            self.broadcast(ServerPacket::WeatherChanged { weather: self.sent_weather });
+        }
+
+        self.tick_metrics.record_tick(started_at.elapsed());
+    }
+    // Tells every player the world time, commands that change it call this.
+    pub fn sync_time(&mut self) {
+        self.broadcast(ServerPacket::TimeUpdate { time: self.world.get_time() });
+    }
+    pub fn get_tick_metrics(&self) -> &TickMetrics {
+        &self.tick_metrics
+    }
+
+    // The bottom center of a player, positions are sent as where the eyes are.
+    fn get_feet(eye: &Coord) -> Coord {
+        let mut feet = eye.clone();
+        feet += Vector3::new(0.0, -PlayerBody::EYE_HEIGHT, 0.0);
+        feet
+    }
+    // The blocks a player's feet and head are in.
+    fn get_body_blocks(&self, eye: &Coord) -> [u8; 2] {
+        let feet = Self::get_feet(eye);
+        [
+            self.world.get_block(&BlockPos::new(feet.get_block_x(), feet.get_world_y().floor() as BlockAxis, feet.get_block_z())),
+            self.world.get_block(&BlockPos::new(eye.get_block_x(), eye.get_world_y().floor() as BlockAxis, eye.get_block_z())),
+        ]
+    }
+
+    // Survival players pick up items near them, burn in lava and fire and slowly heal.
+    fn tick_players(&mut self) {
+        let tick = self.world.get_tick_count();
+        let survivors: Vec<PlayerId> = self.players.values()
+            .filter(|player| player.game_mode == GameMode::Survival)
+            .map(|player| player.get_id())
+            .collect();
+
+        for id in survivors {
+            self.pick_up_items(id);
+
+            if tick.is_multiple_of(Self::HAZARD_INTERVAL) {
+                let blocks = self.get_body_blocks(&self.players[&id].position);
+                if blocks.iter().any(|block| matches!(Liquid::from_block(*block), Some((Liquid::Lava, _)))) {
+                    self.damage(id, Self::LAVA_DAMAGE, "tried to swim in lava");
+                } else if blocks.iter().any(|block| BlockTypes::get_fire_age(*block).is_some()) {
+                    self.damage(id, Self::FIRE_DAMAGE, "went up in flames");
+                }
+            }
+
+            let player = self.players.get_mut(&id).unwrap();
+            if tick.is_multiple_of(Self::REGENERATION_INTERVAL) && player.health < MAX_HEALTH {
+                player.health += 1;
+                player.send(ServerPacket::HealthChanged { health: player.health });
+            }
+        }
+    }
+    fn pick_up_items(&mut self, id: PlayerId) {
+        let player = self.players.get_mut(&id).unwrap();
+        let feet = Self::get_feet(&player.position);
+
+        let nearby: Vec<(EntityId, ItemId)> = self.world.get_entities().iter()
+            .filter_map(|(entity_id, entity)| match entity.kind {
+                EntityKind::Item { item } if entity.age >= Self::PICKUP_DELAY && entity.position.distance(&feet) <= Self::PICKUP_RANGE => Some((*entity_id, item)),
+                _ => None,
+            })
+            .collect();
+
+        let mut has_picked_up = false;
+        for (entity_id, item) in nearby {
+            if player.inventory.add_item(item) {
+                self.world.get_entities_mut().remove(&entity_id);
+                has_picked_up = true;
+            }
+        }
+        if has_picked_up {
+            player.send(ServerPacket::InventoryChanged { inventory: player.inventory.clone() });
+        }
+    }
+    // Survival players add up how far they fall and are hurt when they land, water breaks the fall.
+    // Landing is worked out from the blocks under the feet, the client's own claim isn't trusted.
+    fn update_fall(&mut self, id: PlayerId, fallen: CoordAxis) {
+        let is_in_water = self.get_body_blocks(&self.players[&id].position).iter().any(|block| matches!(Liquid::from_block(*block), Some((Liquid::Water, _))));
+        let player = self.players.get_mut(&id).unwrap();
+        if player.game_mode != GameMode::Survival || is_in_water {
+            player.fall_distance = 0.0;
+            return;
+        }
+
+        player.fall_distance += fallen.max(0.0);
+        if !PlayerBody::is_standing_at(&self.world, &Self::get_feet(&player.position)) {
+            return;
+        }
+
+        let damage = (player.fall_distance - Self::SAFE_FALL_DISTANCE).ceil();
+        player.fall_distance = 0.0;
+        if damage > 0.0 {
+            self.damage(id, damage.min(MAX_HEALTH as CoordAxis) as u8, "fell from a high place");
+        }
+    }
+    // A player without health drops everything they carry and starts over at the spawn.
+    fn damage(&mut self, id: PlayerId, amount: u8, cause: &str) {
+        let Some(player) = self.players.get_mut(&id) else { return; };
+        player.health = player.health.saturating_sub(amount);
+        if player.health > 0 {
+            player.send(ServerPacket::HealthChanged { health: player.health });
+            return;
+        }
+
+        let feet = Self::get_feet(&player.position);
+        let items = player.inventory.take_all();
+        let name = player.get_name().to_string();
+        player.health = MAX_HEALTH;
+        player.send(ServerPacket::HealthChanged { health: player.health });
+        player.send(ServerPacket::InventoryChanged { inventory: player.inventory.clone() });
+
+        for item in items {
+            self.world.spawn_entity(EntityKind::Item { item }, feet.clone());
+        }
+        self.teleport(id, self.world.get_spawn_position());
+        self.broadcast_message(&format!("{} {}", name, cause));
+    }
+
+    // Block changes since the last tick are grouped by chunk section and sent together, sorted so a replay sends the same.
+    fn broadcast_block_changes(&mut self) {
+        let mut sections: BTreeMap<(ChunkAxis, ChunkAxis, u8), BTreeMap<u16, u8>> = BTreeMap::new();
+        for (position, block) in self.world.take_changed_blocks() {
+            self.record(SessionEvent::BlockChange { position: position.clone(), block });
+
+            let chunk_pos = position.to_chunk_pos();
+            let local_pos = LocalBlockPos::new(
+                position.x.rem_euclid(Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+                position.y as LocalBlockAxis,
+                position.z.rem_euclid(Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+            );
+            let section = (local_pos.y / Chunk::SECTION_HEIGHT) as u8;
+
+            sections
+                .entry((chunk_pos.x, chunk_pos.z, section))
+                .or_default()
+                .insert(Chunk::get_section_index(&local_pos), block);
+        }
+
+        for ((chunk_x, chunk_z, section), changes) in sections {
+            let position = ChunkPos::new(chunk_x, chunk_z);
+            let section_blocks = self.world.get_chunk(&position).and_then(|chunk| chunk.get_section(section as usize));
+
+            let packet = match section_blocks {
+                Some(blocks) if changes.len() > Self::FULL_SECTION_THRESHOLD => {
+                    ServerPacket::SectionData { position: position.clone(), section, blocks: blocks.to_vec() }
+                },
+                _ => ServerPacket::MultiBlockChange { position: position.clone(), section, changes: changes.into_iter().collect() },
+            };
+            self.broadcast_to_chunk_viewers(&position, packet);
+        }
+    }
+
+    // Sent after the block changes, so clients already have the block a block entity belongs to.
+    fn broadcast_block_entities(&mut self) {
+        for position in self.world.take_changed_block_entities() {
+            let Some(entity) = self.world.get_block_entity(&position).cloned() else { continue; };
+            self.broadcast_to_chunk_viewers(&position.to_chunk_pos(), ServerPacket::BlockEntityData { position, entity });
+        }
+    }
+
+    // Spawns entities for players that can see them, moves them and removes the ones that are gone or out of sight.
+    fn broadcast_entities(&mut self) {
+        let entities = self.world.get_entities();
+
+        for player in self.players.values_mut() {
+            let hidden: Vec<EntityId> = player.known_entities.iter()
+                .filter(|id| entities.get(id).is_none_or(|entity| !player.sent_chunks.contains(&entity.get_chunk_pos())))
+                .copied()
+                .collect();
+            for entity_id in hidden {
+                player.known_entities.remove(&entity_id);
+                player.send(ServerPacket::RemoveEntity { entity_id });
+            }
+
+            for (entity_id, entity) in entities {
+                if !player.sent_chunks.contains(&entity.get_chunk_pos()) {
+                    continue;
+                }
+
+                if player.known_entities.insert(*entity_id) {
+                    player.send(ServerPacket::SpawnEntity { entity_id: *entity_id, kind: entity.kind, position: entity.position.clone() });
+                } else if entity.has_moved {
+                    player.send(ServerPacket::EntityMoved { entity_id: *entity_id, position: entity.position.clone() });
+                }
+            }
+        }
+
+        for entity in self.world.get_entities_mut().values_mut() {
+            entity.has_moved = false;
+        }
+    }
+
+    fn broadcast_movement(&mut self) {
+        let moved: Vec<(PlayerId, ServerPacket)> = self.players.values_mut()
+            .filter(|player| player.has_moved)
+            .map(|player| {
+                player.has_moved = false;
+                (player.get_id(), ServerPacket::PlayerMoved {
+                    player_id: player.get_id(),
+                    position: player.position.clone(),
+                    yaw: player.yaw,
+                    pitch: player.pitch,
+                })
+            })
+            .collect();
+
+        for (id, packet) in moved {
+            for player in self.players.values_mut() {
+                if player.get_id() != id {
+                    player.send(packet.clone());
+                }
+            }
+        }
+    }
+
+    fn stream_chunks(&mut self) {
+        for player in self.players.values_mut() {
+            let center = player.get_chunk_pos();
+            let radius = player.view_distance as ChunkAxis / 2;
+            let distance = |position: &ChunkPos| {
+                let difference = position - &center;
+                (difference.x as CoordAxis).hypot(difference.z as CoordAxis)
+            };
+
+            let chunks_to_unload: Vec<ChunkPos> = player.sent_chunks.iter()
+                .filter(|position| distance(position) > (radius + 1) as CoordAxis)
+                .cloned()
+                .collect();
+            for position in chunks_to_unload {
+                player.sent_chunks.remove(&position);
+                player.send(ServerPacket::UnloadChunk { position });
+            }
+
+            let mut chunks_to_send: Vec<ChunkPos> = vec![];
+            for x in -radius..radius + 1 {
+                for z in -radius..radius + 1 {
+                    let position = &center + ChunkPos::new(x, z);
+                    if distance(&position) <= radius as CoordAxis && !player.sent_chunks.contains(&position) && self.world.get_chunk(&position).is_some() {
+                        chunks_to_send.push(position);
+                    }
+                }
+            }
+            chunks_to_send.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+            for position in chunks_to_send.into_iter().take(Self::MAX_CHUNKS_PER_UPDATE) {
+                let chunk = self.world.get_chunk(&position).unwrap();
+                let blocks = chunk.get_blocks().to_vec();
+                let block_entities = chunk.get_block_entities().map(|(local, entity)| (local, entity.clone())).collect();
+                player.sent_chunks.insert(position.clone());
+                player.send(ServerPacket::ChunkData { position, blocks, block_entities });
+            }
+        }
+    }
+
+    fn handle_packets(&mut self) {
+        let ids: Vec<PlayerId> = self.players.keys().copied().collect();
+
+        for id in ids {
+            while let Some(bytes) = self.players.get_mut(&id).and_then(|player| player.receive()) {
+                let connection = self.players[&id].get_connection_id();
+                self.record(SessionEvent::Received { connection, packet: bytes.clone() });
+
+                match ClientPacket::decode(&bytes) {
+                    Some(packet) => self.handle_packet(id, packet),
+                    None => println!("Received a malformed packet from player #{}.", id),
+                }
+            }
+        }
+    }
+
+    fn handle_packet(&mut self, id: PlayerId, packet: ClientPacket) {
+        if !self.players.contains_key(&id) {
+            return;
+        }
+
+        match packet {
+            ClientPacket::Handshake { .. } | ClientPacket::Login { .. } => {},
+            ClientPacket::PlayerMove { position, yaw, pitch, .. } => {
+                let player = self.players.get_mut(&id).unwrap();
+                if !Self::is_valid_move(&player.position, &position) {
+                    // Puts the client back where the server has it.
+                    player.send(ServerPacket::Teleport { position: player.position.clone() });
+                    return;
+                }
+
+                let fallen = player.position.get_world_y() - position.get_world_y();
+                let is_in_void = position.get_world_y() < 0.0;
+                player.position = position;
+                player.yaw = yaw;
+                player.pitch = pitch;
+                player.has_moved = true;
+                if is_in_void {
+                    self.fall_out_of_world(id);
+                } else {
+                    self.update_fall(id, fallen);
+                }
+            },
+            ClientPacket::StartBreaking { position } => {
+                let tick = self.world.get_tick_count();
+                self.players.get_mut(&id).unwrap().breaking = Some((position, tick));
+            },
+            ClientPacket::BreakBlock { sequence, position } => {
+                self.handle_block_edit(id, sequence, BlockEdit::Break { position });
+            },
+            ClientPacket::PlaceBlock { sequence, position, face, block } => {
+                self.handle_block_edit(id, sequence, BlockEdit::Place { position, face, block });
+            },
+            ClientPacket::UseBlock { position } => {
+                self.handle_block_use(id, position);
+            },
+            ClientPacket::UseItem { position, face, item } => {
+                self.handle_item_use(id, position, face, item);
+            },
+            ClientPacket::ChatMessage { message } => {
+                self.handle_chat(id, message);
+            },
+            ClientPacket::TabComplete { text } => {
+                let completions = self.complete_command(CommandSource::Player(id), &text);
+                self.players.get_mut(&id).unwrap().send(ServerPacket::TabCompletions { completions });
+            },
+        }
+    }
+
+    // Positions that are not numbers, outside of the world or too far from the last one are rejected.
+    // Falling below the world is allowed, the player is taken out of the void when they get there.
+    fn is_valid_move(from: &Coord, to: &Coord) -> bool {
+        let (x, y, z) = (to.get_world_x(), to.get_world_y(), to.get_world_z());
+        [x, y, z].iter().all(|axis| axis.is_finite())
+            && x.abs() <= WORLD_BORDER as CoordAxis && z.abs() <= WORLD_BORDER as CoordAxis
+            && y <= Chunk::HEIGHT as CoordAxis * 2.0
+            && from.distance(to) <= Self::MAX_MOVE_DISTANCE
+    }
+    // Nothing stops a fall below the world, so survival players die there and everyone else goes back to the spawn.
+    fn fall_out_of_world(&mut self, id: PlayerId) {
+        if self.players[&id].game_mode == GameMode::Survival {
+            self.damage(id, MAX_HEALTH, "fell out of the world");
+        } else {
+            self.teleport(id, self.world.get_spawn_position());
+        }
+    }
+
+    fn handle_block_edit(&mut self, id: PlayerId, sequence: u32, edit: BlockEdit) {
+        let player = self.players.get_mut(&id).unwrap();
+        let target = edit.get_target();
+
+        let result = if !player.game_mode.can_interact() {
+            Err(BlockEditRejection::NotAllowed)
+        } else if player.edit_limiter.try_acquire(self.time) {
+            edit.validate(&self.world, &player.position).and_then(|()| match player.game_mode {
+                GameMode::Survival => edit.validate_survival(&self.world, player, self.world.get_tick_count()),
+                _ => Ok(()),
+            })
+        } else {
+            Err(BlockEditRejection::RateLimited)
+        };
+
+        match result {
+            Ok(()) => {
+                player.acknowledged_edit = Some(sequence);
+                let is_survival = player.game_mode == GameMode::Survival;
+                match edit {
+                    // Only survival players get the drops, in creative they would pile up.
+                    BlockEdit::Break { .. } if is_survival => {
+                        player.breaking = None;
+                        self.world.break_block(&target);
+                    },
+                    BlockEdit::Break { .. } => { self.set_block(&target, BlockTypes::Air as u8); },
+                    BlockEdit::Place { block, .. } => {
+                        if let Some(item) = ItemTypes::get_block_item(block).filter(|_| is_survival) {
+                            player.inventory.remove_item(item);
+                            player.send(ServerPacket::InventoryChanged { inventory: player.inventory.clone() });
+                        }
+                        self.set_block(&target, block);
+                    },
+                }
+            },
+            Err(reason) => {
+                let block = self.world.get_block(&target);
+                player.send(ServerPacket::BlockEditRejected { sequence, position: target, block, reason });
+            },
+        }
+    }
+    // The client does not predict uses, so a rejected one has nothing to roll back.
+    fn handle_block_use(&mut self, id: PlayerId, position: BlockPos) {
+        let player = self.players.get_mut(&id).unwrap();
+
+        let result = if !player.game_mode.can_interact() {
+            Err(BlockEditRejection::NotAllowed)
+        } else if player.edit_limiter.try_acquire(self.time) {
+            block_edit::validate_use(&self.world, &player.position, &position)
+        } else {
+            Err(BlockEditRejection::RateLimited)
+        };
+
+        match result {
+            Ok(()) => {
+                block_behaviour::on_use(&mut self.world, &position);
+            },
+            Err(reason) => {
+                println!("Player #{} could not use the block at ({}, {}, {}): {}.", id, position.x, position.y, position.z, reason.get_reason());
+            },
+        }
+    }
+    fn handle_item_use(&mut self, id: PlayerId, position: BlockPos, face: Direction, item: ItemId) {
+        let player = self.players.get_mut(&id).unwrap();
+
+        let is_survival = player.game_mode == GameMode::Survival;
+        let result = if !player.game_mode.can_interact() {
+            Err(BlockEditRejection::NotAllowed)
+        } else if is_survival && !player.inventory.contains(item) {
+            Err(BlockEditRejection::MissingItem)
+        } else if player.edit_limiter.try_acquire(self.time) {
+            block_edit::validate_item_use(&self.world, &player.position, &position, face)
+        } else {
+            Err(BlockEditRejection::RateLimited)
+        };
+
+        match result {
+            Ok(()) => {
+                if farming::use_item(&mut self.world, &position, face, item) && is_survival && ItemTypes::is_used_up(item) {
+                    player.inventory.remove_item(item);
+                    player.send(ServerPacket::InventoryChanged { inventory: player.inventory.clone() });
+                }
+            },
+            Err(reason) => {
+                println!("Player #{} could not use {} on the block at ({}, {}, {}): {}.", id, ItemTypes::get_name(item), position.x, position.y, position.z, reason.get_reason());
+            },
+        }
+    }
+}
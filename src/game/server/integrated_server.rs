@@ -0,0 +1,44 @@
+use std::{sync::{atomic::{AtomicBool, Ordering}, Arc}, thread::JoinHandle, time::Duration};
+
+use crate::game::common::protocol::connection::ChannelConnection;
+
+use super::game_server::GameServer;
+
+// Runs the server that hosts a singleplayer world on its own thread, so worldgen and saving never stall rendering.
+// The local player talks to it through a channel connection, other players can still join over TCP.
+pub struct IntegratedServer {
+    thread: Option<JoinHandle<()>>,
+    stopping: Arc<AtomicBool>,
+}
+
+impl IntegratedServer {
+    // Starts the server and returns the connection the local player joins through.
+    pub fn start(mut server: GameServer) -> (Self, ChannelConnection) {
+        let connection = server.connect_host();
+        let stopping = Arc::new(AtomicBool::new(false));
+
+        let thread_stopping = stopping.clone();
+        let thread = std::thread::Builder::new()
+            .name(String::from("Server"))
+            .spawn(move || {
+                while server.is_running() && !thread_stopping.load(Ordering::Relaxed) {
+                    server.update();
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                server.stop();
+            })
+            .expect("Failed to start the integrated server thread.");
+
+        (Self { thread: Some(thread), stopping }, connection)
+    }
+
+    // Stops the server and waits until the world is saved.
+    pub fn stop(&mut self) {
+        self.stopping.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                println!("The integrated server crashed.");
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::{net::{Ipv4Addr, UdpSocket}, time::{Duration, Instant}};
+
+use crate::game::common::protocol::{discovery::{LanAnnouncement, DISCOVERY_PORT}, PROTOCOL_VERSION};
+
+// Periodically tells clients on the local network that the server exists.
+pub struct LanAnnouncer {
+    socket: UdpSocket,
+    name: String,
+    last_announcement: Option<Instant>,
+}
+
+impl LanAnnouncer {
+    const INTERVAL: Duration = Duration::from_millis(1500);
+    // The second address is the loopback broadcast, so clients on the same machine hear us without a network.
+    const TARGETS: [Ipv4Addr; 2] = [Ipv4Addr::BROADCAST, Ipv4Addr::new(127, 255, 255, 255)];
+
+    pub fn create(name: String) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self { socket, name, last_announcement: None })
+    }
+
+    pub fn update(&mut self, player_count: usize, port: u16) {
+        if self.last_announcement.is_some_and(|time| time.elapsed() < Self::INTERVAL) {
+            return;
+        }
+        self.last_announcement = Some(Instant::now());
+
+        let announcement = LanAnnouncement {
+            protocol_version: PROTOCOL_VERSION,
+            name: self.name.clone(),
+            player_count: player_count as u32,
+            port,
+        }.encode();
+
+        // Without a network the global broadcast fails, that is fine as long as one target works.
+        for target in Self::TARGETS {
+            let _ = self.socket.send_to(&announcement, (target, DISCOVERY_PORT));
+        }
+    }
+}
@@ -1,74 +1,546 @@
 pub mod world;
+pub mod player;
+pub mod block_edit;
+pub mod entity;
+pub mod game_server;
+pub mod integrated_server;
+pub mod command;
+pub mod console;
+pub mod lan_announcer;
+pub mod remote_console;
+pub mod recording;
+pub mod replay;
+pub mod tick_metrics;
 
-use std::collections::HashMap;
-use world::{chunk::Chunk, worldgen::WorldGen};
+use std::{collections::{BTreeMap, HashMap, VecDeque}, time::Duration};
+use entity::Entity;
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use world::{block_behaviour, chunk::Chunk, rules::WorldRules, storage::{LevelData, WorldStorage}, ticket::{ChunkLevel, ChunkTickets, Ticket, TicketHolder}, worldgen::WorldGen};
 
-use super::common::{coords::{BlockAxis, BlockPos, ChunkAxis, ChunkPos, CoordAxis, LocalBlockAxis, LocalBlockPos}, world::{block_types::BlockTypes, mapping::ChunkPosHasherBuilder}};
+use super::common::{day_cycle, coords::{BlockAxis, BlockPos, ChunkPos, Coord, Direction, LocalBlockAxis, LocalBlockPos}, entity::{EntityId, EntityKind}, item::ItemTypes, protocol::buffer::PacketWriter, weather::Weather, world::{block_entity::BlockEntity, block_types::BlockTypes, block_view::BlockView, light::{LightChannel, MAX_LIGHT}, lighting, mapping::ChunkPosHasherBuilder}};
 
 pub struct ServerWorld {
     chunks: HashMap<ChunkPos, Chunk, ChunkPosHasherBuilder>,
     worldgen: WorldGen,
+    storage: Option<WorldStorage>,
+    // Without storage, modified chunks are kept here when they unload so their edits are not lost.
+    unloaded_chunks: HashMap<ChunkPos, Chunk, ChunkPosHasherBuilder>,
+    tickets: ChunkTickets,
+    // Every block change since the game server last collected them, so it can tell the players.
+    changed_blocks: Vec<(BlockPos, u8)>,
+    // Positions of block entities that were created or changed since then.
+    changed_block_entities: Vec<BlockPos>,
+    // Blocks next to a change that still have to be told about it, and the block that changed.
+    neighbour_updates: VecDeque<(BlockPos, BlockPos)>,
+    // Entities are not saved, the ones in a chunk that unloads are gone.
+    entities: BTreeMap<EntityId, Entity>,
+    next_entity_id: EntityId,
+    // Seeded from the world seed, so a replay picks the same random blocks.
+    random: SmallRng,
+
+    seed: u32,
+    time: u64,
+    rules: WorldRules,
+    weather: Weather,
+    // Ticks until the weather changes by itself.
+    weather_duration: u64,
+    // Ticks since the world was opened, unlike the time it can not be changed by commands. Scheduled ticks use it.
+    tick_count: u64,
 }
 
 impl ServerWorld {
+    // How many ticks a kind of weather lasts, like in Minecraft: clear for 10 to 150 minutes,
+    // rain for 10 to 20 minutes and thunder for 3 to 13 minutes.
+    const CLEAR_DURATION: (u64, u64) = (12000, 180000);
+    const RAIN_DURATION: (u64, u64) = (12000, 24000);
+    const THUNDER_DURATION: (u64, u64) = (3600, 15600);
+    // The chunks around spawn stay loaded even when nobody is there.
+    pub const SPAWN_RADIUS: usize = 3;
+    // A long chain of updates continues next tick instead of stalling this one.
+    const MAX_NEIGHBOUR_UPDATES_PER_TICK: usize = 65536;
+
     pub fn create(seed: u32) -> Self {
-        Self {
+        let mut tickets = ChunkTickets::new();
+        tickets.set(TicketHolder::Spawn, Ticket::new(ChunkPos::new(0, 0), Self::SPAWN_RADIUS, ChunkLevel::Ticking));
+
+        let mut world = Self {
             chunks: HashMap::default(),
-            worldgen: WorldGen::create(seed)
+            worldgen: WorldGen::create(seed),
+            storage: None,
+            unloaded_chunks: HashMap::default(),
+            tickets,
+            changed_blocks: vec![],
+            changed_block_entities: vec![],
+            neighbour_updates: VecDeque::new(),
+            entities: BTreeMap::new(),
+            next_entity_id: 0,
+            random: SmallRng::seed_from_u64(seed as u64),
+
+            seed,
+            time: 0,
+            rules: WorldRules::create(),
+            weather: Weather::Clear,
+            weather_duration: 0,
+            tick_count: 0,
+        };
+        // A new world starts out clear.
+        world.set_weather(Weather::Clear, None);
+
+        world
+    }
+    // Opens a saved world, or starts a new one with a random seed if the storage is empty.
+    pub fn open(storage: WorldStorage) -> Self {
+        let level = storage.load_level().unwrap_or_else(|| LevelData { seed: WorldGen::random_seed(), time: 0, rules: WorldRules::create(), weather: None });
+
+        let mut world = Self::create(level.seed);
+        world.time = level.time;
+        world.rules = level.rules;
+        if let Some((weather, duration)) = level.weather {
+            world.set_weather(weather, Some(duration));
+        }
+        for position in storage.load_forced_chunks() {
+            world.tickets.set(TicketHolder::Forced(position.clone()), Ticket::new(position, 0, ChunkLevel::Ticking));
+        }
+        world.storage = Some(storage);
+
+        if let Err(error) = world.save_level() {
+            println!("Failed to save level data. Error: {}.", error);
         }
+
+        world
     }
 
-    // TODO: Move "load_region" to client side and just ask for server to load certain chunk.
-    pub fn load_region(&mut self, position: &ChunkPos, radius: usize) {
-        let iradius = radius as ChunkAxis;
+    pub fn get_storage(&self) -> Option<&WorldStorage> {
+        self.storage.as_ref()
+    }
+    pub fn get_seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+    pub fn set_time(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    pub fn get_rules(&self) -> &WorldRules {
+        &self.rules
+    }
+    pub fn get_rules_mut(&mut self) -> &mut WorldRules {
+        &mut self.rules
+    }
+
+    // Advances the world by one tick, every part of the simulation runs from here.
+    pub fn tick(&mut self) {
+        self.time += 1;
+        self.tick_count += 1;
+
+        self.update_weather();
+        self.run_scheduled_ticks();
+        self.run_random_ticks();
+        self.run_neighbour_updates();
+        self.tick_entities();
+        self.tick_block_entities();
+    }
+    pub fn get_tick_count(&self) -> u64 {
+        self.tick_count
+    }
+
+    // Asks for an update of the block after the given number of ticks, if it does not have one scheduled already.
+    pub fn schedule_tick(&mut self, position: &BlockPos, delay: u64) -> bool {
+        let chunk_pos = position.to_chunk_pos();
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else { return false; };
+        let (block_x, block_z) = chunk_pos.to_block_xz_pos();
+
+        let local_pos = LocalBlockPos::new((position.x - block_x) as LocalBlockAxis, position.y as LocalBlockAxis, (position.z - block_z) as LocalBlockAxis);
+        chunk.schedule_tick(&local_pos, self.tick_count + delay)
+    }
+
+    pub fn get_weather(&self) -> Weather {
+        self.weather
+    }
+    pub fn get_weather_duration(&self) -> u64 {
+        self.weather_duration
+    }
+    // Without a duration the weather lasts a random time like it would after changing by itself.
+    pub fn set_weather(&mut self, weather: Weather, duration: Option<u64>) {
+        let (min, max) = match weather {
+            Weather::Clear => Self::CLEAR_DURATION,
+            Weather::Rain => Self::RAIN_DURATION,
+            Weather::Thunder => Self::THUNDER_DURATION,
+        };
+
+        self.weather = weather;
+        self.weather_duration = duration.unwrap_or_else(|| self.random.random_range(min..=max));
+    }
+    // A clear sky turns into rain, sometimes into a thunderstorm, and back again.
+    fn update_weather(&mut self) {
+        if self.weather_duration > 0 {
+            self.weather_duration -= 1;
+            return;
+        }
+
+        let next = match self.weather {
+            Weather::Clear if self.random.random_bool(0.25) => Weather::Thunder,
+            Weather::Clear => Weather::Rain,
+            Weather::Rain | Weather::Thunder => Weather::Clear,
+        };
+        self.set_weather(next, None);
+    }
+
+    pub fn get_random_mut(&mut self) -> &mut SmallRng {
+        &mut self.random
+    }
 
-        let mut chunks_to_remove: Vec<ChunkPos> = vec![];
-        for chunk in self.chunks.keys() {
-            let difference = chunk - position;
-            if ((difference.x * difference.x + difference.z * difference.z) as CoordAxis).sqrt() > radius as CoordAxis {
-                chunks_to_remove.push(chunk.clone());
+    // Loaded ticking chunks in order of their position, so replays go through them alike.
+    fn get_ticking_chunks(&self) -> Vec<ChunkPos> {
+        let mut positions: Vec<ChunkPos> = self.chunks.keys()
+            .filter(|position| self.tickets.get_level(position) == Some(ChunkLevel::Ticking))
+            .cloned()
+            .collect();
+        positions.sort_by_key(|position| (position.x, position.z));
+
+        positions
+    }
+
+    // Ticks within a chunk go by when they are due.
+    fn run_scheduled_ticks(&mut self) {
+        let mut due = vec![];
+        for chunk_pos in self.get_ticking_chunks() {
+            let Some(chunk) = self.chunks.get_mut(&chunk_pos) else { continue; };
+            let (block_x, block_z) = chunk_pos.to_block_xz_pos();
+
+            for position in chunk.take_due_ticks(self.tick_count) {
+                due.push(BlockPos::new(block_x + position.x as BlockAxis, position.y as BlockAxis, block_z + position.z as BlockAxis));
             }
         }
 
-        for chunk_pos in chunks_to_remove {
-            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x - 1, chunk_pos.z)) { chunk.mark_dirty(); }
-            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x + 1, chunk_pos.z)) { chunk.mark_dirty(); }
-            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x, chunk_pos.z - 1)) { chunk.mark_dirty(); }
-            if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x, chunk_pos.z + 1)) { chunk.mark_dirty(); }
-            
-            self.chunks.remove(&chunk_pos);
+        for position in due {
+            block_behaviour::on_scheduled_tick(self, &position);
+        }
+    }
+    // Blocks picked at random in every section, for slow changes like grass spreading.
+    fn run_random_ticks(&mut self) {
+        let speed = self.rules.random_tick_speed;
+        if speed == 0 {
+            return;
         }
 
-        for x in -iradius..iradius + 1 {
-            for z in -iradius..iradius + 1 {
-                if ((x * x + z * z) as f32).sqrt() > radius as f32 {
-                    continue;
+        let mut picked = vec![];
+        for chunk_pos in self.get_ticking_chunks() {
+            let (block_x, block_z) = chunk_pos.to_block_xz_pos();
+            for section in 0..Chunk::SECTION_COUNT {
+                for _ in 0..speed {
+                    let position = Chunk::from_section_index(section, self.random.random_range(0..Chunk::SECTION_VOLUME) as u16);
+                    picked.push(BlockPos::new(block_x + position.x as BlockAxis, position.y as BlockAxis, block_z + position.z as BlockAxis));
                 }
+            }
+        }
 
-                let chunk_pos = position + ChunkPos::new(x, z);
-                if self.chunks.contains_key(&chunk_pos) {
-                    continue;
-                }
+        for position in picked {
+            block_behaviour::on_random_tick(self, &position);
+        }
+    }
+    fn run_neighbour_updates(&mut self) {
+        for _ in 0..Self::MAX_NEIGHBOUR_UPDATES_PER_TICK {
+            let Some((position, neighbour)) = self.neighbour_updates.pop_front() else { break; };
+
+            let chunk_pos = position.to_chunk_pos();
+            if self.chunks.contains_key(&chunk_pos) && self.tickets.get_level(&chunk_pos) == Some(ChunkLevel::Ticking) {
+                block_behaviour::on_neighbour_changed(self, &position, &neighbour);
+            }
+        }
+    }
+
+    // Block entities tick after entities moved, so a chest sees the items that just landed on it.
+    fn tick_block_entities(&mut self) {
+        let mut positions = vec![];
+        for chunk_pos in self.get_ticking_chunks() {
+            let Some(chunk) = self.chunks.get(&chunk_pos) else { continue; };
+            let (block_x, block_z) = chunk_pos.to_block_xz_pos();
+
+            for (position, _) in chunk.get_block_entities() {
+                positions.push(BlockPos::new(block_x + position.x as BlockAxis, position.y as BlockAxis, block_z + position.z as BlockAxis));
+            }
+        }
+
+        for position in positions {
+            block_behaviour::on_block_entity_tick(self, &position);
+        }
+    }
+
+    pub fn take_changed_blocks(&mut self) -> Vec<(BlockPos, u8)> {
+        std::mem::take(&mut self.changed_blocks)
+    }
+    pub fn take_changed_block_entities(&mut self) -> Vec<BlockPos> {
+        std::mem::take(&mut self.changed_block_entities)
+    }
+
+    fn save_level(&self) -> std::io::Result<()> {
+        match &self.storage {
+            Some(storage) => storage.save_level(&LevelData {
+                seed: self.seed,
+                time: self.time,
+                rules: self.rules.clone(),
+                weather: Some((self.weather, self.weather_duration)),
+            }),
+            None => Ok(()),
+        }
+    }
+    fn save_chunk(&mut self, position: &ChunkPos) -> std::io::Result<bool> {
+        let (Some(storage), Some(chunk)) = (&self.storage, self.chunks.get_mut(position)) else { return Ok(false); };
+        if !chunk.is_modified() {
+            return Ok(false);
+        }
+
+        storage.save_chunk(position, chunk, self.tick_count)?;
+        chunk.mark_saved();
+
+        Ok(true)
+    }
+    // Saves the level data and every modified chunk, returning how many chunks were written.
+    pub fn save(&mut self) -> std::io::Result<usize> {
+        self.save_level()?;
+
+        let positions: Vec<ChunkPos> = self.chunks.keys().cloned().collect();
+        let mut saved_chunks = 0;
+        for position in positions {
+            if self.save_chunk(&position)? {
+                saved_chunks += 1;
+            }
+        }
+
+        Ok(saved_chunks)
+    }
+
+    pub fn set_ticket(&mut self, holder: TicketHolder, ticket: Ticket) {
+        self.tickets.set(holder, ticket);
+    }
+    pub fn remove_ticket(&mut self, holder: &TicketHolder) {
+        self.tickets.remove(holder);
+    }
+    pub fn get_chunk_level(&self, position: &ChunkPos) -> Option<ChunkLevel> {
+        self.tickets.get_level(position)
+    }
 
-                if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x - 1,        chunk_pos.z)) { chunk.mark_dirty(); }
-                if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x + 1,        chunk_pos.z)) { chunk.mark_dirty(); }
-                if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(       chunk_pos.x, chunk_pos.z - 1)) { chunk.mark_dirty(); }
-                if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(       chunk_pos.x, chunk_pos.z + 1)) { chunk.mark_dirty(); }
+    pub fn get_forced_chunks(&self) -> Vec<ChunkPos> {
+        self.tickets.get_tickets()
+            .filter_map(|(holder, _)| match holder { TicketHolder::Forced(position) => Some(position.clone()), _ => None })
+            .collect()
+    }
+    // Keeps a chunk loaded and ticking until it is unforced again, also across restarts. Returns false if it already was.
+    pub fn set_chunk_forced(&mut self, position: &ChunkPos, forced: bool) -> bool {
+        let holder = TicketHolder::Forced(position.clone());
+        if self.tickets.get_ticket(&holder).is_some() == forced {
+            return false;
+        }
 
-                self.chunks.insert(chunk_pos.clone(), Chunk::create(&self.worldgen, &chunk_pos));
+        if forced {
+            self.tickets.set(holder, Ticket::new(position.clone(), 0, ChunkLevel::Ticking));
+        } else {
+            self.tickets.remove(&holder);
+        }
+
+        if let Some(storage) = &self.storage {
+            if let Err(error) = storage.save_forced_chunks(&self.get_forced_chunks()) {
+                println!("Failed to save forced chunks. Error: {}.", error);
             }
         }
+        true
+    }
+
+    // Puts a chunk where it would be loaded from instead of being generated, used by replays.
+    pub fn insert_stored_chunk(&mut self, position: ChunkPos, chunk: Chunk) {
+        self.unloaded_chunks.insert(position, chunk);
+    }
+
+    // Loads chunks that got a ticket and unloads the ones whose tickets are gone for long enough.
+    // Returns the chunks that were loaded, and whether they came from storage.
+    pub fn update_chunks(&mut self, time: Duration) -> Vec<(ChunkPos, bool)> {
+        let mut loaded = vec![];
+        for position in self.tickets.take_added() {
+            if self.chunks.contains_key(&position) {
+                continue;
+            }
+
+            let mut from_storage = false;
+            let chunk = match self.unloaded_chunks.remove(&position) {
+                Some(chunk) => chunk,
+                None => match self.storage.as_ref().and_then(|storage| storage.load_chunk(&position, self.tick_count)) {
+                    Some(chunk) => {
+                        from_storage = true;
+                        chunk
+                    },
+                    None => Chunk::create(&self.worldgen, &position),
+                },
+            };
+            self.chunks.insert(position.clone(), chunk);
+            lighting::light_chunk(&mut self.chunks, &position);
+            loaded.push((position, from_storage));
+        }
+
+        self.tickets.set_time(time);
+        for position in self.tickets.take_expired() {
+            if let Err(error) = self.save_chunk(&position) {
+                println!("Failed to save chunk at: ({}, {}). Error: {}.", position.x, position.z, error);
+            }
+
+            let Some(chunk) = self.chunks.remove(&position) else { continue; };
+            self.entities.retain(|_, entity| entity.get_chunk_pos() != position);
+            if self.storage.is_none() && chunk.is_modified() {
+                self.unloaded_chunks.insert(position, chunk);
+            }
+        }
+
+        loaded
+    }
+
+    // A hash of the time, weather, rules, entities, every loaded chunk and the given state of the players,
+    // which live in the game server. Two worlds that match here are the same for anyone playing in them.
+    pub fn get_state_hash(&self, players: &[u8]) -> u64 {
+        let mut positions: Vec<&ChunkPos> = self.chunks.keys().collect();
+        positions.sort_by_key(|position| (position.x, position.z));
+
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut add = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        };
+
+        let mut writer = PacketWriter::create();
+        writer.write_u64(self.time);
+        writer.write_u8(self.weather.get_id());
+        writer.write_u64(self.weather_duration);
+        self.rules.write(&mut writer);
+        for (id, entity) in &self.entities {
+            writer.write_u32(*id);
+            entity.write(&mut writer);
+        }
+        add(&writer.into_bytes());
+        add(players);
+
+        for position in positions {
+            add(&position.x.to_be_bytes());
+            add(&position.z.to_be_bytes());
+            add(self.chunks[position].get_blocks());
+            for (block, entity) in self.chunks[position].get_block_entities() {
+                let mut writer = PacketWriter::create();
+                entity.write(&mut writer);
+                add(&[block.x as u8, block.y as u8, block.z as u8]);
+                add(&writer.into_bytes());
+            }
+            for (due, block) in self.chunks[position].get_scheduled_ticks() {
+                add(&due.saturating_sub(self.tick_count).to_be_bytes());
+                add(&[block.x as u8, block.y as u8, block.z as u8]);
+            }
+        }
+        hash
+    }
+
+    pub fn spawn_entity(&mut self, kind: EntityKind, position: Coord) -> EntityId {
+        let id = self.next_entity_id;
+        self.next_entity_id += 1;
+        self.entities.insert(id, Entity::create(kind, position));
+
+        id
+    }
+    pub fn get_entities(&self) -> &BTreeMap<EntityId, Entity> {
+        &self.entities
+    }
+    pub fn get_entities_mut(&mut self) -> &mut BTreeMap<EntityId, Entity> {
+        &mut self.entities
+    }
+
+    // Entities only move in ticking chunks, like blocks only update there.
+    fn tick_entities(&mut self) {
+        let ids: Vec<EntityId> = self.entities.keys().copied().collect();
+        for id in ids {
+            let Some(mut entity) = self.entities.remove(&id) else { continue; };
+            if self.tickets.get_level(&entity.get_chunk_pos()) != Some(ChunkLevel::Ticking) {
+                self.entities.insert(id, entity);
+                continue;
+            }
+
+            let has_landed = entity.fall(self);
+            if entity.position.get_world_y() < 0.0 {
+                continue;
+            }
+
+            match entity.kind {
+                EntityKind::FallingBlock { block } if has_landed => self.land_falling_block(&entity, block),
+                EntityKind::Item { .. } if entity.age >= Entity::ITEM_LIFETIME => {},
+                _ => { self.entities.insert(id, entity); },
+            }
+        }
+    }
+    // A falling block turns back into a block where it lands, or into an item if something is in the way.
+    fn land_falling_block(&mut self, entity: &Entity, block: u8) {
+        let position = entity.get_block_pos();
+        if position.y < Chunk::HEIGHT as BlockAxis && BlockTypes::is_replaceable(self.get_block(&position)) {
+            self.set_block(&position, block);
+        } else {
+            self.spawn_entity(EntityKind::Item { item: ItemTypes::from_block(block) }, entity.position.clone());
+        }
+    }
+
+    pub fn get_spawn_position(&self) -> Coord {
+        Coord::new(0.5, self.worldgen.get_height(0, 0).max(0.0).floor() + 2.5, 0.5)
     }
 
     pub fn get_chunk(&self, position: &ChunkPos) -> Option<&Chunk> {
         self.chunks.get(position)
     }
-    pub fn get_chunk_mut(&mut self, position: &ChunkPos) -> Option<&mut Chunk> {
-        self.chunks.get_mut(position)
+
+    fn to_local(position: &BlockPos) -> (ChunkPos, LocalBlockPos) {
+        let chunk_pos = position.to_chunk_pos();
+        let local_pos = LocalBlockPos::new(
+            (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis,
+            position.y as LocalBlockAxis,
+            (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis,
+        );
+        (chunk_pos, local_pos)
+    }
+
+    pub fn get_block_entity(&self, position: &BlockPos) -> Option<&BlockEntity> {
+        let (chunk_pos, local_pos) = Self::to_local(position);
+        self.chunks.get(&chunk_pos)?.get_block_entity(&local_pos)
+    }
+    // Returns false if the block at the position does not take this kind of block entity.
+    pub fn set_block_entity(&mut self, position: &BlockPos, entity: BlockEntity) -> bool {
+        let (chunk_pos, local_pos) = Self::to_local(position);
+        let Some(chunk) = self.chunks.get_mut(&chunk_pos) else { return false; };
+        if !chunk.set_block_entity(&local_pos, entity) {
+            return false;
+        }
+
+        if !self.changed_block_entities.contains(position) {
+            self.changed_block_entities.push(position.clone());
+        }
+        true
+    }
+    // Whatever was stored in a block entity that went away with its block is dropped as items.
+    fn drop_contents(&mut self, position: &BlockPos, entity: BlockEntity) {
+        let BlockEntity::Chest { mut inventory } = entity else { return; };
+
+        for item in inventory.take_all() {
+            self.spawn_entity(EntityKind::Item { item }, position.get_bottom_center());
+        }
     }
 
-    pub fn get_all_chunks(&self) -> &HashMap<ChunkPos, Chunk, ChunkPosHasherBuilder> {
-        &self.chunks
+    // Replaces the block with air and drops what it leaves behind.
+    pub fn break_block(&mut self, position: &BlockPos) -> bool {
+        let block = self.get_block(position);
+        if !self.set_block(position, BlockTypes::Air as u8) {
+            return false;
+        }
+
+        for item in block_behaviour::get_drops(self, block) {
+            self.spawn_entity(EntityKind::Item { item }, position.get_bottom_center());
+        }
+        true
     }
 
     pub fn set_block(&mut self, position: &BlockPos, block: u8) -> bool {
@@ -79,10 +551,21 @@ impl ServerWorld {
                 let local_x = (position.x - chunk_pos.x as BlockAxis * Chunk::WIDTH as BlockAxis) as LocalBlockAxis;
                 let local_z = (position.z - chunk_pos.z as BlockAxis * Chunk::LENGTH as BlockAxis) as LocalBlockAxis;
 
-                if !chunk.set_block(&LocalBlockPos::new(local_x, position.y as LocalBlockAxis, local_z), block) {
+                let local_pos = LocalBlockPos::new(local_x, position.y as LocalBlockAxis, local_z);
+                let old_entity = chunk.get_block_entity(&local_pos).cloned();
+                if !chunk.set_block(&local_pos, block) {
                     return false;
                 }
 
+                // The chunk creates and removes block entities with their blocks, only a changed one has to be sent.
+                let new_entity = chunk.get_block_entity(&local_pos);
+                if new_entity.is_some() && new_entity != old_entity.as_ref() && !self.changed_block_entities.contains(position) {
+                    self.changed_block_entities.push(position.clone());
+                }
+                if let Some(old_entity) = old_entity.filter(|entity| !entity.fits(block)) {
+                    self.drop_contents(position, old_entity);
+                }
+
                 if local_x == 0 {
                     if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x - 1, chunk_pos.z)) { chunk.mark_dirty(); }
                 } else if local_x >= Chunk::WIDTH - 1 {
@@ -95,11 +578,32 @@ impl ServerWorld {
                     if let Some(chunk) = self.chunks.get_mut(&ChunkPos::new(chunk_pos.x, chunk_pos.z + 1)) { chunk.mark_dirty(); }
                 }
 
+                lighting::update_block(&mut self.chunks, position);
+                self.changed_blocks.push((position.clone(), block));
+                // The block itself is told too, so a block can react to being placed.
+                self.neighbour_updates.push_back((position.clone(), position.clone()));
+                for direction in Direction::ALL {
+                    self.neighbour_updates.push_back((position.offset(direction), position.clone()));
+                }
                 true
             },
             None => { false },
         }
     }
+    // How light the block is: the brighter of its block light and its sky light, which is dimmer at night.
+    pub fn get_light_level(&self, position: &BlockPos) -> u8 {
+        let (chunk_pos, local_pos) = Self::to_local(position);
+        let Some(chunk) = self.chunks.get(&chunk_pos) else { return 0; };
+
+        let darkness = MAX_LIGHT - day_cycle::get_sky_light(self.time);
+        let sky_light = chunk.get_light(&local_pos, LightChannel::Sky).saturating_sub(darkness);
+        sky_light.max(chunk.get_light(&local_pos, LightChannel::Block))
+    }
+    // The height above the highest block of a column, None while its chunk is not loaded.
+    pub fn get_height(&self, x: BlockAxis, z: BlockAxis) -> Option<BlockAxis> {
+        let (chunk_pos, local_pos) = Self::to_local(&BlockPos::new(x, 0, z));
+        Some(self.chunks.get(&chunk_pos)?.get_height(local_pos.x, local_pos.z) as BlockAxis)
+    }
     pub fn get_block(&self, position: &BlockPos) -> u8 {
         let chunk_pos = position.to_chunk_pos();
 
@@ -114,4 +618,10 @@ impl ServerWorld {
             None => { BlockTypes::Air as u8 },
         }
     }
+}
+
+impl BlockView for ServerWorld {
+    fn get_block(&self, position: &BlockPos) -> u8 {
+        ServerWorld::get_block(self, position)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+use std::{collections::{BTreeSet, HashSet}, time::Duration};
+
+use crate::game::common::{coords::{BlockPos, ChunkPos, Coord, CoordAxis}, entity::EntityId, inventory::Inventory, player::{GameMode, PlayerId}, protocol::{packet::ServerPacket, stream::{CompressionStats, PacketStream}}, world::mapping::ChunkPosHasherBuilder};
+
+use super::world::storage::PlayerData;
+
+pub struct EditRateLimiter {
+    tokens: f32,
+    last_refill: Option<Duration>,
+}
+
+impl EditRateLimiter {
+    pub const MAX_EDITS_PER_SECOND: f32 = 10.0;
+    pub const MAX_BURST: f32 = 10.0;
+
+    pub fn create() -> Self {
+        Self {
+            tokens: Self::MAX_BURST,
+            last_refill: None,
+        }
+    }
+
+    // Takes the server time, so a replayed session is limited exactly like the original.
+    pub fn try_acquire(&mut self, now: Duration) -> bool {
+        let elapsed = self.last_refill.map(|last_refill| now.saturating_sub(last_refill)).unwrap_or_default();
+        self.tokens = (self.tokens + elapsed.as_secs_f32() * Self::MAX_EDITS_PER_SECOND).min(Self::MAX_BURST);
+        self.last_refill = Some(now);
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+pub struct ServerPlayer {
+    id: PlayerId,
+    name: String,
+    stream: PacketStream,
+    connection_id: u32,
+
+    pub position: Coord,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub has_moved: bool,
+
+    pub game_mode: GameMode,
+    pub health: u8,
+    pub inventory: Inventory,
+    // How far the player fell since last standing on the ground.
+    pub fall_distance: CoordAxis,
+    // The block a survival player is mining and the tick it started.
+    pub breaking: Option<(BlockPos, u64)>,
+
+    pub view_distance: usize,
+    pub sent_chunks: HashSet<ChunkPos, ChunkPosHasherBuilder>,
+    // Entities the client was told about, they are only sent in chunks it has.
+    pub known_entities: BTreeSet<EntityId>,
+
+    pub edit_limiter: EditRateLimiter,
+    // The last accepted edit, one acknowledgement at the end of the update covers all edits before it.
+    pub acknowledged_edit: Option<u32>,
+}
+
+impl ServerPlayer {
+    pub fn create(id: PlayerId, name: String, stream: PacketStream, connection_id: u32, position: Coord, view_distance: usize, data: PlayerData) -> Self {
+        Self {
+            id,
+            name,
+            stream,
+            connection_id,
+
+            position,
+            yaw: 0.0,
+            pitch: 0.0,
+            has_moved: false,
+
+            game_mode: data.game_mode,
+            health: data.health,
+            inventory: data.inventory,
+            fall_distance: 0.0,
+            breaking: None,
+
+            view_distance,
+            sent_chunks: HashSet::default(),
+            known_entities: BTreeSet::new(),
+
+            edit_limiter: EditRateLimiter::create(),
+            acknowledged_edit: None,
+        }
+    }
+
+    pub fn get_id(&self) -> PlayerId {
+        self.id
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_connection_id(&self) -> u32 {
+        self.connection_id
+    }
+    // What is kept of the player when they leave.
+    pub fn get_data(&self) -> PlayerData {
+        PlayerData { game_mode: self.game_mode, health: self.health, inventory: self.inventory.clone() }
+    }
+    pub fn get_chunk_pos(&self) -> ChunkPos {
+        ChunkPos::new(self.position.get_chunk_x(), self.position.get_chunk_z())
+    }
+
+    pub fn send(&mut self, packet: ServerPacket) {
+        self.stream.send(packet.encode());
+    }
+    pub fn receive(&mut self) -> Option<Vec<u8>> {
+        self.stream.receive()
+    }
+    pub fn flush(&mut self) {
+        self.stream.flush();
+    }
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_open()
+    }
+
+    pub fn get_compression_stats(&self) -> &CompressionStats {
+        self.stream.get_stats()
+    }
+    pub fn get_stream_mut(&mut self) -> &mut PacketStream {
+        &mut self.stream
+    }
+}
@@ -0,0 +1,322 @@
+use std::{collections::HashSet, fs::File, io::{self, BufWriter, Read, Write}, path::Path, time::{Duration, Instant}};
+
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+use crate::game::common::{coords::{BlockPos, ChunkPos}, protocol::buffer::{PacketReader, PacketWriter}, weather::Weather};
+
+use super::world::rules::WorldRules;
+
+// Everything the server needs to be started again exactly like it was when the recording began.
+#[derive(Clone)]
+pub struct SessionHeader {
+    pub seed: u32,
+    pub time: u64,
+    pub rules: WorldRules,
+    pub weather: Weather,
+    pub weather_duration: u64,
+    pub operators: Vec<String>,
+    pub forced_chunks: Vec<ChunkPos>,
+}
+
+// Connections are numbered in the order the server accepted them, packets are stored as they were before compression.
+#[derive(Clone, PartialEq)]
+pub enum SessionEvent {
+    // Everything between these two happened during one GameServer::update, time is since the server started.
+    UpdateStart { time: Duration },
+    UpdateEnd,
+    Connect { connection: u32, compression_threshold: u32, is_host: bool },
+    Disconnect { connection: u32 },
+    Received { connection: u32, packet: Vec<u8> },
+    Sent { connection: u32, packet: Vec<u8> },
+    ConsoleCommand { command: String },
+    BlockChange { position: BlockPos, block: u8 },
+    // A chunk read from disk as it is stored, a replay has no storage and needs its content.
+    StoredChunk { position: ChunkPos, data: Vec<u8> },
+    // The same for the data of a player that joined.
+    StoredPlayer { name: String, data: Vec<u8> },
+    End { world_hash: u64 },
+}
+
+impl SessionEvent {
+    // Inputs are fed to a replay, outputs are what it has to produce again.
+    pub fn is_output(&self) -> bool {
+        matches!(self, SessionEvent::Sent { .. } | SessionEvent::BlockChange { .. } | SessionEvent::End { .. })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+
+        match self {
+            SessionEvent::UpdateStart { time } => {
+                writer.write_u8(0);
+                writer.write_u64(time.as_micros() as u64);
+            },
+            SessionEvent::UpdateEnd => {
+                writer.write_u8(1);
+            },
+            SessionEvent::Connect { connection, compression_threshold, is_host } => {
+                writer.write_u8(2);
+                writer.write_u32(*connection);
+                writer.write_u32(*compression_threshold);
+                writer.write_u8(*is_host as u8);
+            },
+            SessionEvent::Disconnect { connection } => {
+                writer.write_u8(3);
+                writer.write_u32(*connection);
+            },
+            SessionEvent::Received { connection, packet } => {
+                writer.write_u8(4);
+                writer.write_u32(*connection);
+                writer.write_bytes(packet);
+            },
+            SessionEvent::Sent { connection, packet } => {
+                writer.write_u8(5);
+                writer.write_u32(*connection);
+                writer.write_bytes(packet);
+            },
+            SessionEvent::ConsoleCommand { command } => {
+                writer.write_u8(6);
+                writer.write_string(command);
+            },
+            SessionEvent::BlockChange { position, block } => {
+                writer.write_u8(7);
+                writer.write_block_pos(position);
+                writer.write_u8(*block);
+            },
+            SessionEvent::StoredChunk { position, data } => {
+                writer.write_u8(8);
+                writer.write_chunk_pos(position);
+                writer.write_bytes(data);
+            },
+            SessionEvent::End { world_hash } => {
+                writer.write_u8(9);
+                writer.write_u64(*world_hash);
+            },
+            SessionEvent::StoredPlayer { name, data } => {
+                writer.write_u8(10);
+                writer.write_string(name);
+                writer.write_bytes(data);
+            },
+        }
+
+        writer.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let mut reader = PacketReader::create(bytes);
+
+        let event = match reader.read_u8()? {
+            0 => SessionEvent::UpdateStart { time: Duration::from_micros(reader.read_u64()?) },
+            1 => SessionEvent::UpdateEnd,
+            2 => SessionEvent::Connect { connection: reader.read_u32()?, compression_threshold: reader.read_u32()?, is_host: reader.read_u8()? != 0 },
+            3 => SessionEvent::Disconnect { connection: reader.read_u32()? },
+            4 => SessionEvent::Received { connection: reader.read_u32()?, packet: reader.read_bytes()?.to_vec() },
+            5 => SessionEvent::Sent { connection: reader.read_u32()?, packet: reader.read_bytes()?.to_vec() },
+            6 => SessionEvent::ConsoleCommand { command: reader.read_string()? },
+            7 => SessionEvent::BlockChange { position: reader.read_block_pos()?, block: reader.read_u8()? },
+            8 => SessionEvent::StoredChunk { position: reader.read_chunk_pos()?, data: reader.read_bytes()?.to_vec() },
+            9 => SessionEvent::End { world_hash: reader.read_u64()? },
+            10 => SessionEvent::StoredPlayer { name: reader.read_string()?, data: reader.read_bytes()?.to_vec() },
+            _ => return None,
+        };
+
+        if reader.is_empty() { Some(event) } else { None }
+    }
+}
+
+fn encode_header(header: &SessionHeader) -> Vec<u8> {
+    let mut writer = PacketWriter::create();
+    writer.write_u32(header.seed);
+    writer.write_u64(header.time);
+    header.rules.write(&mut writer);
+    writer.write_u8(header.weather.get_id());
+    writer.write_u64(header.weather_duration);
+
+    writer.write_u32(header.operators.len() as u32);
+    for operator in &header.operators {
+        writer.write_string(operator);
+    }
+    writer.write_u32(header.forced_chunks.len() as u32);
+    for position in &header.forced_chunks {
+        writer.write_chunk_pos(position);
+    }
+
+    writer.into_bytes()
+}
+fn decode_header(bytes: &[u8]) -> Option<SessionHeader> {
+    let mut reader = PacketReader::create(bytes);
+    let seed = reader.read_u32()?;
+    let time = reader.read_u64()?;
+    let rules = WorldRules::read(&mut reader)?;
+    let weather = Weather::from_id(reader.read_u8()?)?;
+    let weather_duration = reader.read_u64()?;
+
+    let operator_count = reader.read_u32()?;
+    let operators = (0..operator_count).map(|_| reader.read_string()).collect::<Option<Vec<String>>>()?;
+    let forced_count = reader.read_u32()?;
+    let forced_chunks = (0..forced_count).map(|_| reader.read_chunk_pos()).collect::<Option<Vec<ChunkPos>>>()?;
+
+    Some(SessionHeader { seed, time, rules, weather, weather_duration, operators, forced_chunks })
+}
+
+const MAGIC: &[u8; 4] = b"MFGR";
+const FORMAT_VERSION: u32 = 7;
+
+enum RecorderSink {
+    File(ZlibEncoder<BufWriter<File>>),
+    Memory(Vec<SessionEvent>),
+    Finished,
+}
+
+// Writes events to a zlib compressed file as they happen, or keeps them in memory for a replay to compare against.
+// The file is a magic and version, then the compressed header and events, each prefixed with its length.
+pub struct SessionRecorder {
+    sink: RecorderSink,
+
+    loaded_chunks: HashSet<ChunkPos>,
+    last_flush: Instant,
+}
+
+impl SessionRecorder {
+    // The compressed stream is flushed this often, so a crash loses at most this much of the session.
+    const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn create<P: AsRef<Path>>(path: P, header: &SessionHeader) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&FORMAT_VERSION.to_be_bytes())?;
+
+        let mut recorder = Self::with_sink(RecorderSink::File(ZlibEncoder::new(file, Compression::default())));
+        recorder.write_frame(&encode_header(header))?;
+
+        Ok(recorder)
+    }
+    pub fn in_memory() -> Self {
+        Self::with_sink(RecorderSink::Memory(vec![]))
+    }
+    fn with_sink(sink: RecorderSink) -> Self {
+        Self {
+            sink,
+
+            loaded_chunks: HashSet::new(),
+            last_flush: Instant::now(),
+        }
+    }
+
+    fn write_frame(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let RecorderSink::File(writer) = &mut self.sink else { return Ok(()); };
+        writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+        writer.write_all(bytes)
+    }
+
+    pub fn record(&mut self, event: SessionEvent) {
+        match &mut self.sink {
+            RecorderSink::File(_) => {},
+            RecorderSink::Memory(events) => {
+                events.push(event);
+                return;
+            },
+            RecorderSink::Finished => return,
+        }
+
+        let is_update_end = event == SessionEvent::UpdateEnd;
+        let mut result = self.write_frame(&event.encode());
+        if let (true, true, RecorderSink::File(writer)) = (result.is_ok(), is_update_end, &mut self.sink) {
+            if self.last_flush.elapsed() >= Self::FLUSH_INTERVAL {
+                self.last_flush = Instant::now();
+                result = writer.flush();
+            }
+        }
+
+        if let Err(error) = result {
+            println!("Failed to write session recording, recording stopped. Error: {}.", error);
+            self.sink = RecorderSink::Finished;
+        }
+    }
+    // Only the first time a chunk is loaded matters, after that a replay has its own copy.
+    pub fn record_chunk_load(&mut self, position: &ChunkPos, stored_data: Option<&[u8]>) {
+        if !self.loaded_chunks.insert(position.clone()) {
+            return;
+        }
+        if let Some(data) = stored_data {
+            self.record(SessionEvent::StoredChunk { position: position.clone(), data: data.to_vec() });
+        }
+    }
+
+    // Events of an in memory recorder since the last call.
+    pub fn take_events(&mut self) -> Vec<SessionEvent> {
+        match &mut self.sink {
+            RecorderSink::Memory(events) => std::mem::take(events),
+            _ => vec![],
+        }
+    }
+
+    // Completes the file, nothing is recorded to it afterwards. An in memory recorder keeps recording.
+    pub fn finish(&mut self) {
+        if !matches!(self.sink, RecorderSink::File(_)) {
+            return;
+        }
+
+        if let RecorderSink::File(writer) = std::mem::replace(&mut self.sink, RecorderSink::Finished) {
+            if let Err(error) = writer.finish().and_then(|mut file| file.flush()) {
+                println!("Failed to finish session recording. Error: {}.", error);
+            }
+        }
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+pub struct SessionRecording {
+    pub header: SessionHeader,
+    pub events: Vec<SessionEvent>,
+}
+
+impl SessionRecording {
+    // A recording cut short by a crash is read up to the last complete event.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            return Err(String::from("Not a session recording"));
+        }
+        let version = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+        if version != FORMAT_VERSION {
+            return Err(format!("Recording has format version {}, but we read version {}", version, FORMAT_VERSION));
+        }
+
+        let mut data = vec![];
+        let truncated = ZlibDecoder::new(&bytes[8..]).read_to_end(&mut data).is_err();
+        if truncated {
+            println!("Session recording is incomplete, replaying the part that was written.");
+        }
+
+        let mut frames = vec![];
+        let mut cursor = 0;
+        while cursor + 4 <= data.len() {
+            let length = u32::from_be_bytes(data[cursor..cursor + 4].try_into().unwrap()) as usize;
+            let Some(frame) = data.get(cursor + 4..cursor + 4 + length) else { break; };
+            frames.push(frame);
+            cursor += 4 + length;
+        }
+
+        let Some(header) = frames.first().and_then(|frame| decode_header(frame)) else {
+            return Err(String::from("Recording has no valid header"));
+        };
+        let mut events = frames[1..].iter()
+            .map(|frame| SessionEvent::decode(frame))
+            .collect::<Option<Vec<SessionEvent>>>()
+            .ok_or_else(|| String::from("Recording contains an invalid event"))?;
+
+        // The last update may be cut off halfway, which a replay can not reproduce.
+        if truncated {
+            let complete = events.iter().rposition(|event| *event == SessionEvent::UpdateEnd).map(|index| index + 1).unwrap_or(0);
+            events.truncate(complete);
+        }
+
+        Ok(Self { header, events })
+    }
+}
@@ -0,0 +1,190 @@
+use std::{io::{ErrorKind, Read, Write}, net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs}, time::{Duration, Instant}};
+
+// An admin connection that runs server commands, like RCON. The protocol is line based so it is easy to script:
+// the first line a client sends is the password, which is answered with "OK" or an error after which the
+// connection is closed. Every following line is a command, its output is sent back followed by an empty line.
+struct RemoteClient {
+    id: u32,
+    stream: TcpStream,
+    address: SocketAddr,
+
+    read_buffer: Vec<u8>,
+    write_buffer: Vec<u8>,
+
+    is_authenticated: bool,
+    connected_at: Instant,
+    open: bool,
+}
+
+impl RemoteClient {
+    fn fill_read_buffer(&mut self) {
+        let mut chunk = [0u8; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    self.open = false;
+                    break;
+                },
+                Ok(length) => self.read_buffer.extend_from_slice(&chunk[..length]),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => {
+                    self.open = false;
+                    break;
+                },
+            }
+        }
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        let end = self.read_buffer.iter().position(|byte| *byte == b'\n')?;
+        let line: Vec<u8> = self.read_buffer.drain(..end + 1).collect();
+
+        Some(String::from_utf8_lossy(&line).trim_end_matches(['\r', '\n']).to_string())
+    }
+
+    fn write_line(&mut self, line: &str) {
+        self.write_buffer.extend_from_slice(line.as_bytes());
+        self.write_buffer.push(b'\n');
+    }
+
+    fn flush(&mut self) {
+        while self.open && !self.write_buffer.is_empty() {
+            match self.stream.write(&self.write_buffer) {
+                Ok(0) => self.open = false,
+                Ok(length) => { self.write_buffer.drain(..length); },
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => self.open = false,
+            }
+        }
+    }
+}
+
+pub struct RemoteCommand {
+    pub client_id: u32,
+    pub address: SocketAddr,
+    pub command: String,
+}
+
+pub struct RemoteConsole {
+    listener: TcpListener,
+    password: String,
+
+    clients: Vec<RemoteClient>,
+    next_client_id: u32,
+}
+
+impl RemoteConsole {
+    pub const DEFAULT_ADDRESS: &str = "127.0.0.1:25575";
+
+    const MAX_CLIENTS: usize = 4;
+    const MAX_LINE_LENGTH: usize = 4096;
+    const LOGIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+    pub fn bind<A: ToSocketAddrs>(address: A, password: String) -> std::io::Result<Self> {
+        if password.is_empty() {
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, "the remote console needs a password"));
+        }
+
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self { listener, password, clients: vec![], next_client_id: 0 })
+    }
+
+    pub fn get_address(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Compares every byte, so the time it takes does not tell how much of the password was right.
+    fn is_password(&self, attempt: &str) -> bool {
+        let expected = self.password.as_bytes();
+        let attempt = attempt.as_bytes();
+
+        let difference = expected.iter().zip(attempt).fold(0u8, |difference, (a, b)| difference | (a ^ b));
+        difference == 0 && expected.len() == attempt.len()
+    }
+
+    fn accept_clients(&mut self) {
+        while let Ok((stream, address)) = self.listener.accept() {
+            if self.clients.len() >= Self::MAX_CLIENTS || stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+
+            self.clients.push(RemoteClient {
+                id: self.next_client_id,
+                stream,
+                address,
+
+                read_buffer: vec![],
+                write_buffer: vec![],
+
+                is_authenticated: false,
+                connected_at: Instant::now(),
+                open: true,
+            });
+            self.next_client_id += 1;
+        }
+    }
+
+    // Accepts connections, handles logins and returns the commands authenticated clients sent.
+    pub fn poll(&mut self) -> Vec<RemoteCommand> {
+        self.accept_clients();
+
+        let mut commands = vec![];
+        for index in 0..self.clients.len() {
+            self.clients[index].fill_read_buffer();
+
+            while let Some(line) = self.clients[index].read_line() {
+                let client = &self.clients[index];
+                if client.is_authenticated {
+                    commands.push(RemoteCommand { client_id: client.id, address: client.address, command: line });
+                    continue;
+                }
+
+                let is_password = self.is_password(&line);
+                let client = &mut self.clients[index];
+                if is_password {
+                    client.is_authenticated = true;
+                    client.write_line("OK");
+                    println!("Remote console logged in from: {}.", client.address);
+                } else {
+                    client.write_line("ERROR Wrong password");
+                    client.flush();
+                    client.open = false;
+                    println!("Remote console login from: {} failed.", client.address);
+                    break;
+                }
+            }
+
+            let client = &mut self.clients[index];
+            if client.read_buffer.len() > Self::MAX_LINE_LENGTH {
+                client.open = false;
+            }
+            if !client.is_authenticated && client.connected_at.elapsed() > Self::LOGIN_TIMEOUT {
+                client.open = false;
+            }
+        }
+
+        self.clients.retain(|client| client.open);
+        commands
+    }
+
+    pub fn reply(&mut self, client_id: u32, lines: &[String]) {
+        let Some(client) = self.clients.iter_mut().find(|client| client.id == client_id) else { return; };
+
+        for line in lines {
+            // An empty line ends the response, so it can not be part of it.
+            client.write_line(if line.is_empty() { " " } else { line });
+        }
+        client.write_line("");
+        client.flush();
+    }
+
+    pub fn flush(&mut self) {
+        for client in &mut self.clients {
+            client.flush();
+        }
+    }
+}
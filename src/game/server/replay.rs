@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+
+use crate::game::common::{protocol::{connection::{ChannelConnection, Connection}, packet::ServerPacket}, world::block_types::BlockTypes};
+
+use super::{command::CommandSource, game_server::GameServer, recording::{SessionEvent, SessionRecorder, SessionRecording}, world::storage::WorldStorage, ServerWorld};
+
+pub struct ReplayReport {
+    pub updates: usize,
+    pub packets: usize,
+    pub block_changes: usize,
+    // The first output that differs from the recording, if any.
+    pub mismatch: Option<String>,
+}
+
+// The client side of a replayed connection, it sends the recorded packets and ignores the answers.
+struct ReplayClient {
+    connection: ChannelConnection,
+    is_handshaken: bool,
+}
+
+impl ReplayClient {
+    fn send(&mut self, packet: Vec<u8>) {
+        // After the handshake the server expects compression frames, a zero length marks one as uncompressed.
+        let frame = if self.is_handshaken { [0u8; 4].iter().copied().chain(packet).collect() } else { packet };
+        self.connection.send(frame);
+        self.is_handshaken = true;
+    }
+    fn discard_received(&mut self) {
+        while self.connection.receive().is_some() {}
+    }
+}
+
+fn describe(event: &SessionEvent) -> String {
+    match event {
+        SessionEvent::Sent { connection, packet } => {
+            format!("{} packet to connection #{}", ServerPacket::get_name(packet.first().copied().unwrap_or_default()), connection)
+        },
+        SessionEvent::BlockChange { position, block } => {
+            format!("{} placed at {}, {}, {}", BlockTypes::get_name(*block), position.x, position.y, position.z)
+        },
+        SessionEvent::End { world_hash } => format!("end with world hash {:016x}", world_hash),
+        _ => String::from("input event"),
+    }
+}
+
+// Runs a fresh server with the recorded inputs at the recorded times and checks it sends the same packets,
+// makes the same block changes and ends in the same world.
+pub fn replay(recording: &SessionRecording) -> ReplayReport {
+    let header = &recording.header;
+    let mut world = ServerWorld::create(header.seed);
+    world.set_time(header.time);
+    *world.get_rules_mut() = header.rules.clone();
+    world.set_weather(header.weather, Some(header.weather_duration));
+    for position in &header.forced_chunks {
+        world.set_chunk_forced(position, true);
+    }
+
+    let mut server = GameServer::create(world);
+    for operator in &header.operators {
+        server.add_operator(operator);
+    }
+    server.set_recorder(SessionRecorder::in_memory());
+
+    let mut report = ReplayReport { updates: 0, packets: 0, block_changes: 0, mismatch: None };
+    let mut expected = recording.events.iter().filter(|event| event.is_output());
+    let mut clients: HashMap<u32, ReplayClient> = HashMap::new();
+
+    let mut events = recording.events.iter();
+    while let Some(event) = events.next() {
+        match event {
+            SessionEvent::UpdateStart { time } => {
+                // Everything the server read during the update is handed to it before it runs.
+                for event in events.by_ref() {
+                    match event {
+                        SessionEvent::UpdateEnd => break,
+                        event if event.is_output() => {},
+                        event => apply_input(&mut server, &mut clients, event),
+                    }
+                }
+
+                server.update_at(*time);
+                report.updates += 1;
+            },
+            SessionEvent::End { .. } => server.stop(),
+            event if event.is_output() => {},
+            event => apply_input(&mut server, &mut clients, event),
+        }
+
+        for client in clients.values_mut() {
+            client.discard_received();
+        }
+
+        let produced = server.get_recorder_mut().map(|recorder| recorder.take_events()).unwrap_or_default();
+        for actual in produced.into_iter().filter(|event| event.is_output()) {
+            match &actual {
+                SessionEvent::Sent { .. } => report.packets += 1,
+                SessionEvent::BlockChange { .. } => report.block_changes += 1,
+                _ => {},
+            }
+
+            match expected.next() {
+                Some(event) if *event == actual => {},
+                Some(event) => report.mismatch = Some(format!("Expected {}, but the replay produced {}", describe(event), describe(&actual))),
+                None => report.mismatch = Some(format!("The replay produced {} after the recording ended", describe(&actual))),
+            }
+            if report.mismatch.is_some() {
+                return report;
+            }
+        }
+    }
+
+    if let Some(event) = expected.next() {
+        report.mismatch = Some(format!("Expected {}, but the replay ended", describe(event)));
+    }
+    report
+}
+
+fn apply_input(server: &mut GameServer, clients: &mut HashMap<u32, ReplayClient>, event: &SessionEvent) {
+    match event {
+        SessionEvent::Connect { connection, compression_threshold, is_host } => {
+            let (client, server_side) = ChannelConnection::pair();
+            server.add_connection(Box::new(server_side), *compression_threshold as usize, *is_host);
+            clients.insert(*connection, ReplayClient { connection: client, is_handshaken: false });
+        },
+        SessionEvent::Disconnect { connection } => {
+            clients.remove(connection);
+        },
+        SessionEvent::Received { connection, packet } => {
+            if let Some(client) = clients.get_mut(connection) {
+                client.send(packet.clone());
+            }
+        },
+        SessionEvent::ConsoleCommand { command } => {
+            server.execute_command(CommandSource::Console, command);
+        },
+        SessionEvent::StoredChunk { position, data } => {
+            if let Some(chunk) = WorldStorage::decode_chunk(data, server.get_world().get_tick_count()) {
+                server.get_world_mut().insert_stored_chunk(position.clone(), chunk);
+            }
+        },
+        SessionEvent::StoredPlayer { name, data } => {
+            if let Some(data) = WorldStorage::decode_player(data) {
+                server.insert_stored_player(name, data);
+            }
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Vector3;
+
+    use crate::game::common::{protocol::{connection::{ChannelConnection, Connection}, packet::ClientPacket, PROTOCOL_VERSION}, world::block_types::BlockTypes};
+
+    use super::{super::{command::CommandSource, game_server::GameServer, recording::{SessionEvent, SessionRecorder, SessionRecording}, ServerWorld}, replay};
+
+    // After the handshake the server expects compression frames, a zero length marks one as uncompressed.
+    fn send(client: &mut ChannelConnection, packet: ClientPacket) {
+        client.send([0u8; 4].iter().copied().chain(packet.encode()).collect());
+    }
+
+    // A player joins, walks, chats and changes the weather, and a replay of that has to end in the same state.
+    #[test]
+    fn replay_matches_recording() {
+        let mut server = GameServer::create(ServerWorld::create(1));
+        let header = server.get_session_header();
+        server.set_recorder(SessionRecorder::in_memory());
+
+        let (mut client, server_side) = ChannelConnection::pair();
+        server.add_connection(Box::new(server_side), u32::MAX as usize, false);
+        client.send(ClientPacket::Handshake { protocol_version: PROTOCOL_VERSION, registry_hash: BlockTypes::get_registry_hash() }.encode());
+
+        let mut position = server.get_world().get_spawn_position();
+        for tick in 0..100 {
+            match tick {
+                1 => send(&mut client, ClientPacket::Login { name: String::from("Alice"), view_distance: 4 }),
+                10 => send(&mut client, ClientPacket::ChatMessage { message: String::from("Hello") }),
+                20 => { server.execute_command(CommandSource::Console, "weather rain"); },
+                30..40 => {
+                    position += Vector3::new(0.25, 0.0, 0.0);
+                    send(&mut client, ClientPacket::PlayerMove { position: position.clone(), yaw: 90.0, pitch: 0.0, is_on_ground: true });
+                },
+                _ => {},
+            }
+            server.update_at(GameServer::TICK_DURATION * tick);
+            while client.receive().is_some() {}
+        }
+        server.stop();
+
+        let events = server.get_recorder_mut().unwrap().take_events();
+        assert!(events.iter().any(|event| matches!(event, SessionEvent::End { .. })));
+        assert!(events.iter().any(|event| matches!(event, SessionEvent::Sent { .. })));
+
+        let report = replay(&SessionRecording { header, events });
+        assert_eq!(report.mismatch, None);
+        assert_eq!(report.updates, 100);
+    }
+}
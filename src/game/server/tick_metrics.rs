@@ -0,0 +1,55 @@
+use std::{collections::VecDeque, time::Duration};
+
+// How long recent ticks took, like the MSPT (milliseconds per tick) other servers report.
+pub struct TickMetrics {
+    durations: VecDeque<Duration>,
+    total_ticks: u64,
+    skipped_ticks: u64,
+}
+
+impl TickMetrics {
+    pub const SAMPLE_COUNT: usize = 100;
+
+    pub fn create() -> Self {
+        Self {
+            durations: VecDeque::with_capacity(Self::SAMPLE_COUNT),
+            total_ticks: 0,
+            skipped_ticks: 0,
+        }
+    }
+
+    pub fn record_tick(&mut self, duration: Duration) {
+        if self.durations.len() >= Self::SAMPLE_COUNT {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(duration);
+        self.total_ticks += 1;
+    }
+    pub fn record_skipped(&mut self, ticks: u64) {
+        self.skipped_ticks += ticks;
+    }
+
+    pub fn get_average_mspt(&self) -> f64 {
+        let total: Duration = self.durations.iter().sum();
+        total.as_secs_f64() * 1000.0 / self.durations.len().max(1) as f64
+    }
+    pub fn get_max_mspt(&self) -> f64 {
+        self.durations.iter().max().copied().unwrap_or_default().as_secs_f64() * 1000.0
+    }
+    // The ticks per second the server could run at, capped at the rate it is meant to tick at.
+    pub fn get_tps(&self, ticks_per_second: u32) -> f64 {
+        let mspt = self.get_average_mspt();
+        if mspt <= 0.0 {
+            return ticks_per_second as f64;
+        }
+
+        (1000.0 / mspt).min(ticks_per_second as f64)
+    }
+
+    pub fn get_total_ticks(&self) -> u64 {
+        self.total_ticks
+    }
+    pub fn get_skipped_ticks(&self) -> u64 {
+        self.skipped_ticks
+    }
+}
@@ -0,0 +1,139 @@
+use rand::Rng;
+
+use crate::game::common::{coords::{BlockPos, Direction}, entity::{EntityId, EntityKind}, item::{ItemId, ItemTypes}, world::{block_entity::BlockEntity, block_types::BlockTypes, liquid::Liquid, signal::SignalBlock}};
+
+use super::{super::ServerWorld, farming, fire, liquid, signal};
+
+// What blocks do on their own. The world calls these from its tick, so they only run in ticking chunks.
+
+// Falling blocks wait this many ticks after losing their support, so a collapsing column falls one block at a time.
+const FALL_DELAY: u64 = 2;
+// Grass tries this many random blocks around it to spread to on every random tick.
+const GRASS_SPREAD_ATTEMPTS: usize = 4;
+// Grass only spreads to dirt with at least this much light above it, like in Minecraft.
+const MIN_GRASS_LIGHT: u8 = 9;
+
+fn has_gravity(block: u8) -> bool {
+    block == BlockTypes::Sand as u8 || block == BlockTypes::Gravel as u8
+}
+
+// One of the six blocks next to the position changed, or the block itself did.
+pub fn on_neighbour_changed(world: &mut ServerWorld, position: &BlockPos, _neighbour: &BlockPos) {
+    let block = world.get_block(position);
+    if has_gravity(block) {
+        world.schedule_tick(position, FALL_DELAY);
+    } else if let Some((liquid, level)) = Liquid::from_block(block) {
+        liquid::on_neighbour_changed(world, position, liquid, level);
+    } else if let Some(signal) = SignalBlock::from_block(block) {
+        signal::on_neighbour_changed(world, position, signal);
+    } else if BlockTypes::get_fire_age(block).is_some() {
+        fire::on_neighbour_changed(world, position);
+    } else if BlockTypes::is_farmland(block) {
+        farming::on_farmland_neighbour_changed(world, position);
+    } else if BlockTypes::get_wheat_age(block).is_some() {
+        farming::on_wheat_neighbour_changed(world, position);
+    }
+}
+
+// A block picked by the random ticks of its section.
+pub fn on_random_tick(world: &mut ServerWorld, position: &BlockPos) {
+    let block = world.get_block(position);
+    if block == BlockTypes::GrassBlock as u8 {
+        grow_grass(world, position);
+    } else if let Some(age) = BlockTypes::get_fire_age(block) {
+        fire::on_random_tick(world, position, age);
+    } else if BlockTypes::is_farmland(block) {
+        farming::on_farmland_random_tick(world, position, block);
+    } else if let Some(age) = BlockTypes::get_wheat_age(block) {
+        farming::on_wheat_random_tick(world, position, age);
+    }
+}
+
+// Grass can grow over dirt with no solid block or liquid on top of it and enough light above it.
+fn can_grass_spread_to(world: &ServerWorld, position: &BlockPos) -> bool {
+    let above = position.offset(Direction::Top);
+    let block = world.get_block(&above);
+    !BlockTypes::is_solid(block) && !BlockTypes::is_liquid(block) && world.get_light_level(&above) >= MIN_GRASS_LIGHT
+}
+
+// Grass buried under a solid block dies back to dirt, otherwise it spreads to lit dirt next to it,
+// from a block above down to three below like in Minecraft.
+fn grow_grass(world: &mut ServerWorld, position: &BlockPos) {
+    if BlockTypes::is_solid(world.get_block(&position.offset(Direction::Top))) {
+        world.set_block(position, BlockTypes::Dirt as u8);
+        return;
+    }
+
+    for _ in 0..GRASS_SPREAD_ATTEMPTS {
+        let random = world.get_random_mut();
+        let target = BlockPos::new(
+            position.x + random.random_range(-1..=1),
+            position.y + random.random_range(-3..=1),
+            position.z + random.random_range(-1..=1),
+        );
+
+        if world.get_block(&target) == BlockTypes::Dirt as u8 && can_grass_spread_to(world, &target) {
+            world.set_block(&target, BlockTypes::GrassBlock as u8);
+        }
+    }
+}
+
+// A tick the block scheduled earlier is due.
+pub fn on_scheduled_tick(world: &mut ServerWorld, position: &BlockPos) {
+    let block = world.get_block(position);
+    if has_gravity(block) && position.y > 0 && !BlockTypes::is_solid(world.get_block(&position.offset(Direction::Bottom))) {
+        world.set_block(position, BlockTypes::Air as u8);
+        world.spawn_entity(EntityKind::FallingBlock { block }, position.get_bottom_center());
+    } else if let Some((liquid, level)) = Liquid::from_block(block) {
+        liquid::on_scheduled_tick(world, position, liquid, level);
+    } else if let Some(signal) = SignalBlock::from_block(block) {
+        signal::on_scheduled_tick(world, position, signal);
+    } else if let Some(age) = BlockTypes::get_fire_age(block) {
+        fire::on_scheduled_tick(world, position, age);
+    }
+}
+
+// The items a block leaves behind when it is broken in survival or pops off.
+pub fn get_drops(world: &mut ServerWorld, block: u8) -> Vec<ItemId> {
+    match BlockTypes::get_wheat_age(block) {
+        Some(age) => farming::get_wheat_drops(world, age),
+        None => ItemTypes::get_block_item(block).into_iter().collect(),
+    }
+}
+
+// A player right clicked the block. Returns false if nothing happened.
+pub fn on_use(world: &mut ServerWorld, position: &BlockPos) -> bool {
+    match SignalBlock::from_block(world.get_block(position)) {
+        Some(signal) => signal::use_block(world, position, signal),
+        None => false,
+    }
+}
+
+// Every tick of a block with a block entity. Chests take in the items lying on top of them until they are full.
+pub fn on_block_entity_tick(world: &mut ServerWorld, position: &BlockPos) {
+    let Some(BlockEntity::Chest { inventory }) = world.get_block_entity(position) else { return; };
+    let mut inventory = inventory.clone();
+
+    let above = position.offset(Direction::Top);
+    let items: Vec<(EntityId, ItemId)> = world.get_entities().iter()
+        .filter_map(|(id, entity)| match entity.kind {
+            EntityKind::Item { item } if entity.get_block_pos() == above => Some((*id, item)),
+            _ => None,
+        })
+        .collect();
+    if items.is_empty() {
+        return;
+    }
+
+    let mut has_changed = false;
+    for (id, item) in items {
+        if !inventory.add_item(item) {
+            break;
+        }
+        world.get_entities_mut().remove(&id);
+        has_changed = true;
+    }
+    if has_changed {
+        world.set_block_entity(position, BlockEntity::Chest { inventory });
+    }
+}
@@ -1,10 +1,26 @@
-use crate::game::common::{coords::{BlockAxis, ChunkPos, LocalBlockPos}, world::block_types::BlockTypes};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::game::common::{coords::{BlockAxis, ChunkPos, LocalBlockPos}, world::{block_entity::BlockEntity, block_types::BlockTypes, light::LightChannel}};
 use super::worldgen::WorldGen;
 
 // TODO: Move common chunk content to common module and make a ServerChunk class here that will inherit common Chunk class.
 pub struct Chunk {
     blocks: Box<[u8; Self::VOLUME]>,
+    // For every column, the height right above its highest block that is not air.
+    heights: Box<[u16; Self::WIDTH * Self::LENGTH]>,
+    // Sky light in the high and block light in the low four bits of every block, per section.
+    // Worked out again whenever the chunk is loaded, a section gets its array when light is first set in it.
+    light: Vec<Option<Box<[u8; Self::SECTION_VOLUME]>>>,
     is_dirty: bool,
+    // Set when the chunk differs from what is stored on disk.
+    is_modified: bool,
+    // Blocks that asked to be updated at a later tick, by the world tick they are due and their index.
+    // Sorted, so the ticks that are due run in the same order every time.
+    scheduled_ticks: BTreeSet<(u64, usize)>,
+    // The same ticks by index, to find out quickly whether a block already has one.
+    scheduled_indices: BTreeMap<usize, u64>,
+    // By block index, every block that has a block entity has one here.
+    block_entities: BTreeMap<usize, BlockEntity>,
 }
 
 impl Chunk {
@@ -14,10 +30,21 @@ impl Chunk {
 
     pub const VOLUME: usize = Self::WIDTH * Self::HEIGHT * Self::LENGTH;
 
+    // Block changes are sent per section, a 16 block high slice of the chunk.
+    pub const SECTION_HEIGHT: usize = 16;
+    pub const SECTION_COUNT: usize = Self::HEIGHT / Self::SECTION_HEIGHT;
+    pub const SECTION_VOLUME: usize = Self::WIDTH * Self::SECTION_HEIGHT * Self::LENGTH;
+
     pub fn create(worldgen: &WorldGen, position: &ChunkPos) -> Self {
         let mut chunk = Self {
             blocks: Box::new([0; Self::VOLUME]),
+            heights: Box::new([0; Self::WIDTH * Self::LENGTH]),
+            light: vec![None; Self::SECTION_COUNT],
             is_dirty: true,
+            is_modified: false,
+            scheduled_ticks: BTreeSet::new(),
+            scheduled_indices: BTreeMap::new(),
+            block_entities: BTreeMap::new(),
         };
 
         for x in 0..Self::WIDTH {
@@ -51,26 +78,212 @@ impl Chunk {
             }
         }
 
+        // Generated terrain can always be generated again from the seed, so it does not need saving.
+        chunk.is_modified = false;
         chunk
     }
+    pub fn from_blocks(blocks: &[u8]) -> Option<Self> {
+        let mut chunk = Self {
+            blocks: Box::new(blocks.try_into().ok()?),
+            heights: Box::new([0; Self::WIDTH * Self::LENGTH]),
+            light: vec![None; Self::SECTION_COUNT],
+            is_dirty: true,
+            is_modified: false,
+            scheduled_ticks: BTreeSet::new(),
+            scheduled_indices: BTreeMap::new(),
+            block_entities: BTreeMap::new(),
+        };
+        chunk.update_heights();
+        (0..Self::VOLUME).for_each(|index| chunk.update_block_entity(index));
+
+        Some(chunk)
+    }
 
-    pub fn set_block(&mut self, position: &LocalBlockPos, block: u8) -> bool {
+    fn get_index(position: &LocalBlockPos) -> Option<usize> {
         if position.x >= Self::WIDTH || position.y >= Self::HEIGHT || position.z >= Self::LENGTH {
-            return false;
+            return None;
         }
 
-        self.blocks[position.x + position.z * Self::WIDTH + position.y * Self::WIDTH * Self::LENGTH] = block;
+        Some(position.x + position.z * Self::WIDTH + position.y * Self::WIDTH * Self::LENGTH)
+    }
+    pub fn from_index(index: usize) -> LocalBlockPos {
+        LocalBlockPos::new(index % Self::WIDTH, index / (Self::WIDTH * Self::LENGTH), index / Self::WIDTH % Self::LENGTH)
+    }
+
+    pub fn set_block(&mut self, position: &LocalBlockPos, block: u8) -> bool {
+        let Some(index) = Self::get_index(position) else { return false; };
+
+        self.blocks[index] = block;
+        self.update_block_entity(index);
         self.mark_dirty();
+        self.is_modified = true;
+
+        let column = position.x + position.z * Self::WIDTH;
+        if block != BlockTypes::Air as u8 {
+            self.heights[column] = self.heights[column].max(position.y as u16 + 1);
+        } else if self.heights[column] == position.y as u16 + 1 {
+            self.update_height(position.x, position.z);
+        }
 
         true
     }
 
+    fn update_height(&mut self, x: usize, z: usize) {
+        let top = (0..Self::HEIGHT).rev().find(|y| self.blocks[x + z * Self::WIDTH + y * Self::WIDTH * Self::LENGTH] != BlockTypes::Air as u8);
+        self.heights[x + z * Self::WIDTH] = top.map_or(0, |y| y as u16 + 1);
+    }
+    fn update_heights(&mut self) {
+        for z in 0..Self::LENGTH {
+            for x in 0..Self::WIDTH {
+                self.update_height(x, z);
+            }
+        }
+    }
+    // The height right above the highest block of the column that is not air, zero if there is none.
+    pub fn get_height(&self, x: usize, z: usize) -> usize {
+        self.heights.get(x + z * Self::WIDTH).map_or(0, |height| *height as usize)
+    }
+
     pub fn get_block(&self, position: &LocalBlockPos) -> u8 {
-        if position.x >= Self::WIDTH || position.y >= Self::HEIGHT || position.z >= Self::LENGTH {
+        match Self::get_index(position) {
+            Some(index) => self.blocks[index],
+            None => 0,
+        }
+    }
+
+    // A block has at most one scheduled tick, returns false if it already had one.
+    pub fn schedule_tick(&mut self, position: &LocalBlockPos, due: u64) -> bool {
+        let Some(index) = Self::get_index(position) else { return false; };
+        if self.scheduled_indices.contains_key(&index) {
+            return false;
+        }
+
+        self.scheduled_ticks.insert((due, index));
+        self.scheduled_indices.insert(index, due);
+        self.is_modified = true;
+        true
+    }
+    pub fn take_due_ticks(&mut self, now: u64) -> Vec<LocalBlockPos> {
+        let mut due = vec![];
+        while let Some((time, index)) = self.scheduled_ticks.first().copied() {
+            if time > now {
+                break;
+            }
+
+            self.scheduled_ticks.pop_first();
+            self.scheduled_indices.remove(&index);
+            due.push(Self::from_index(index));
+        }
+
+        if !due.is_empty() {
+            self.is_modified = true;
+        }
+        due
+    }
+    pub fn get_scheduled_ticks(&self) -> impl Iterator<Item = (u64, LocalBlockPos)> + '_ {
+        self.scheduled_ticks.iter().map(|(due, index)| (*due, Self::from_index(*index)))
+    }
+
+    // Gives the block a fresh block entity if it needs one that it does not have yet, and removes the one it no longer needs.
+    fn update_block_entity(&mut self, index: usize) {
+        let block = self.blocks[index];
+        if self.block_entities.get(&index).is_some_and(|entity| entity.fits(block)) {
+            return;
+        }
+
+        match BlockEntity::create(block) {
+            Some(entity) => { self.block_entities.insert(index, entity); },
+            None => { self.block_entities.remove(&index); },
+        }
+    }
+    pub fn get_block_entity(&self, position: &LocalBlockPos) -> Option<&BlockEntity> {
+        self.block_entities.get(&Self::get_index(position)?)
+    }
+    // Returns false if the block at the position does not take this kind of block entity.
+    pub fn set_block_entity(&mut self, position: &LocalBlockPos, entity: BlockEntity) -> bool {
+        let Some(index) = Self::get_index(position) else { return false; };
+        if !entity.fits(self.blocks[index]) {
+            return false;
+        }
+
+        self.block_entities.insert(index, entity);
+        self.is_modified = true;
+        true
+    }
+    pub fn get_block_entities(&self) -> impl Iterator<Item = (LocalBlockPos, &BlockEntity)> + '_ {
+        self.block_entities.iter().map(|(index, entity)| (Self::from_index(*index), entity))
+    }
+
+    pub fn get_blocks(&self) -> &[u8] {
+        self.blocks.as_slice()
+    }
+
+    // Both light levels of a block, sky light in the high four bits.
+    pub fn get_packed_light(&self, position: &LocalBlockPos) -> u8 {
+        if Self::get_index(position).is_none() {
             return 0;
         }
 
-        self.blocks[position.x + position.z * Self::WIDTH + position.y * Self::WIDTH * Self::LENGTH]
+        match &self.light[position.y / Self::SECTION_HEIGHT] {
+            Some(light) => light[Self::get_section_index(position) as usize],
+            None => 0,
+        }
+    }
+    pub fn get_light(&self, position: &LocalBlockPos, channel: LightChannel) -> u8 {
+        let packed = self.get_packed_light(position);
+        match channel {
+            LightChannel::Sky => packed >> 4,
+            LightChannel::Block => packed & 0xF,
+        }
+    }
+    // Returns false if the position is outside of the chunk.
+    pub fn set_light(&mut self, position: &LocalBlockPos, channel: LightChannel, level: u8) -> bool {
+        if Self::get_index(position).is_none() {
+            return false;
+        }
+
+        let light = self.light[position.y / Self::SECTION_HEIGHT].get_or_insert_with(|| Box::new([0; Self::SECTION_VOLUME]));
+        let packed = &mut light[Self::get_section_index(position) as usize];
+        *packed = match channel {
+            LightChannel::Sky => (*packed & 0xF) | (level.min(15) << 4),
+            LightChannel::Block => (*packed & 0xF0) | level.min(15),
+        };
+
+        true
+    }
+    pub fn clear_light(&mut self) {
+        self.light.iter_mut().for_each(|light| *light = None);
+    }
+
+    // Y is the slowest changing axis, so a section is one continuous part of the blocks.
+    pub fn get_section(&self, section: usize) -> Option<&[u8]> {
+        self.blocks.get(section * Self::SECTION_VOLUME..(section + 1) * Self::SECTION_VOLUME)
+    }
+    pub fn set_section(&mut self, section: usize, blocks: &[u8]) -> bool {
+        let Some(target) = self.blocks.get_mut(section * Self::SECTION_VOLUME..(section + 1) * Self::SECTION_VOLUME) else { return false; };
+        if blocks.len() != target.len() {
+            return false;
+        }
+
+        target.copy_from_slice(blocks);
+        (section * Self::SECTION_VOLUME..(section + 1) * Self::SECTION_VOLUME).for_each(|index| self.update_block_entity(index));
+        self.mark_dirty();
+        self.is_modified = true;
+        self.update_heights();
+
+        true
+    }
+
+    pub fn get_section_index(position: &LocalBlockPos) -> u16 {
+        (position.x + position.z * Self::WIDTH + (position.y % Self::SECTION_HEIGHT) * Self::WIDTH * Self::LENGTH) as u16
+    }
+    pub fn from_section_index(section: usize, index: u16) -> LocalBlockPos {
+        let index = index as usize;
+        LocalBlockPos::new(
+            index % Self::WIDTH,
+            section * Self::SECTION_HEIGHT + index / (Self::WIDTH * Self::LENGTH),
+            index / Self::WIDTH % Self::LENGTH,
+        )
     }
 
     pub fn mark_dirty(&mut self) {
@@ -83,4 +296,11 @@ impl Chunk {
     pub fn is_dirty(&self) -> bool {
         self.is_dirty
     }
+
+    pub fn mark_saved(&mut self) {
+        self.is_modified = false;
+    }
+    pub fn is_modified(&self) -> bool {
+        self.is_modified
+    }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use rand::Rng;
+
+use crate::game::common::{coords::{BlockAxis, BlockPos, Direction}, item::{ItemId, ItemTypes}, world::{block_types::BlockTypes, liquid::Liquid}};
+
+use super::super::ServerWorld;
+
+// Dirt tilled with a hoe turns into farmland, seeds planted on it grow into wheat over random ticks.
+// Farmland is moist next to water or in the rain, crops on it grow faster. Dry farmland without a crop turns back into dirt.
+
+// Water this many blocks away to the sides, on the same level or one above, keeps farmland moist.
+const WATER_RANGE: BlockAxis = 4;
+// Crops only grow with at least this much light.
+const MIN_GROWTH_LIGHT: u8 = 9;
+// One in this many random ticks makes a crop grow on moist and on dry farmland, about Minecraft's odds for a lone crop.
+const MOIST_GROWTH_ODDS: u32 = 3;
+const DRY_GROWTH_ODDS: u32 = 13;
+// Ripe wheat drops up to this many seeds besides the wheat.
+const MAX_RIPE_SEEDS: u32 = 3;
+
+fn has_water_nearby(world: &ServerWorld, position: &BlockPos) -> bool {
+    (-WATER_RANGE..=WATER_RANGE).any(|dx| (-WATER_RANGE..=WATER_RANGE).any(|dz| (0..=1).any(|dy| {
+        let neighbour = BlockPos::new(position.x + dx, position.y + dy, position.z + dz);
+        matches!(Liquid::from_block(world.get_block(&neighbour)), Some((Liquid::Water, _)))
+    })))
+}
+// Nothing but air above the block.
+fn is_open_to_sky(world: &ServerWorld, position: &BlockPos) -> bool {
+    world.get_height(position.x, position.z).is_some_and(|height| position.y + 1 >= height)
+}
+
+pub fn on_farmland_neighbour_changed(world: &mut ServerWorld, position: &BlockPos) {
+    if BlockTypes::is_solid(world.get_block(&position.offset(Direction::Top))) {
+        world.set_block(position, BlockTypes::Dirt as u8);
+    }
+}
+pub fn on_farmland_random_tick(world: &mut ServerWorld, position: &BlockPos, block: u8) {
+    let above = position.offset(Direction::Top);
+    let is_moist = has_water_nearby(world, position) || (world.get_weather().is_raining() && is_open_to_sky(world, &above));
+
+    if is_moist {
+        if block != BlockTypes::MOIST_FARMLAND {
+            world.set_block(position, BlockTypes::MOIST_FARMLAND);
+        }
+    } else if block == BlockTypes::MOIST_FARMLAND {
+        world.set_block(position, BlockTypes::Farmland as u8);
+    } else if BlockTypes::get_wheat_age(world.get_block(&above)).is_none() {
+        world.set_block(position, BlockTypes::Dirt as u8);
+    }
+}
+
+// Crops pop off when the farmland under them is gone.
+pub fn on_wheat_neighbour_changed(world: &mut ServerWorld, position: &BlockPos) {
+    if !BlockTypes::is_farmland(world.get_block(&position.offset(Direction::Bottom))) {
+        world.break_block(position);
+    }
+}
+pub fn on_wheat_random_tick(world: &mut ServerWorld, position: &BlockPos, age: u8) {
+    if age >= BlockTypes::MAX_WHEAT_AGE || world.get_light_level(position) < MIN_GROWTH_LIGHT {
+        return;
+    }
+
+    let odds = if world.get_block(&position.offset(Direction::Bottom)) == BlockTypes::MOIST_FARMLAND { MOIST_GROWTH_ODDS } else { DRY_GROWTH_ODDS };
+    if world.get_random_mut().random_range(0..odds) == 0 {
+        world.set_block(position, BlockTypes::get_wheat(age + 1));
+    }
+}
+
+// Ripe wheat gives wheat and some seeds to plant again, wheat that is still growing only gives its seeds back.
+pub fn get_wheat_drops(world: &mut ServerWorld, age: u8) -> Vec<ItemId> {
+    if age < BlockTypes::MAX_WHEAT_AGE {
+        return vec![ItemTypes::Seeds as ItemId];
+    }
+
+    let seeds = world.get_random_mut().random_range(0..=MAX_RIPE_SEEDS);
+    let mut drops = vec![ItemTypes::Wheat as ItemId];
+    drops.extend((0..seeds).map(|_| ItemTypes::Seeds as ItemId));
+    drops
+}
+
+// A player used an item that is not a block on a face of the block. Returns false if nothing happened.
+pub fn use_item(world: &mut ServerWorld, position: &BlockPos, face: Direction, item: ItemId) -> bool {
+    let block = world.get_block(position);
+    let is_above_clear = world.get_block(&position.offset(Direction::Top)) == BlockTypes::Air as u8;
+
+    match item {
+        item if item == ItemTypes::Hoe as ItemId => {
+            let is_tillable = block == BlockTypes::Dirt as u8 || block == BlockTypes::GrassBlock as u8;
+            is_tillable && is_above_clear && face != Direction::Bottom && world.set_block(position, BlockTypes::Farmland as u8)
+        },
+        item if item == ItemTypes::Seeds as ItemId => {
+            BlockTypes::is_farmland(block) && is_above_clear && face == Direction::Top && world.set_block(&position.offset(Direction::Top), BlockTypes::get_wheat(0))
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,125 @@
+use rand::Rng;
+
+use crate::game::common::{coords::{BlockPos, Direction}, world::{block_types::BlockTypes, liquid::Liquid}};
+
+use super::super::ServerWorld;
+
+// Fire burns on its own scheduled ticks: it grows older, eats the flammable blocks around it, jumps to empty blocks
+// near flammable ones and dies down. Random ticks burn it the same way in between, so it picks up with the random tick speed.
+
+// Ticks between two ticks of a fire, picked at random each time.
+const TICK_DELAY: (u64, u64) = (30, 40);
+// Fire without anything to burn goes out once it is older than this.
+const MAX_UNFUELED_AGE: u8 = 3;
+
+fn schedule(world: &mut ServerWorld, position: &BlockPos) {
+    let delay = world.get_random_mut().random_range(TICK_DELAY.0..=TICK_DELAY.1);
+    world.schedule_tick(position, delay);
+}
+
+fn touches_water(world: &ServerWorld, position: &BlockPos) -> bool {
+    Direction::ALL.into_iter().any(|direction| matches!(Liquid::from_block(world.get_block(&position.offset(direction))), Some((Liquid::Water, _))))
+}
+// The best chance of any block around the position to catch fire.
+fn get_ignite_chance(world: &ServerWorld, position: &BlockPos) -> u8 {
+    Direction::ALL.into_iter()
+        .map(|direction| BlockTypes::get_ignite_chance(world.get_block(&position.offset(direction))))
+        .max()
+        .unwrap_or(0)
+}
+// Fire stays on top of solid blocks and next to blocks it can burn.
+fn can_burn_at(world: &ServerWorld, position: &BlockPos) -> bool {
+    BlockTypes::is_solid(world.get_block(&position.offset(Direction::Bottom))) || get_ignite_chance(world, position) > 0
+}
+
+pub fn on_neighbour_changed(world: &mut ServerWorld, position: &BlockPos) {
+    if touches_water(world, position) || !can_burn_at(world, position) {
+        world.set_block(position, BlockTypes::Air as u8);
+        return;
+    }
+    schedule(world, position);
+}
+
+pub fn on_scheduled_tick(world: &mut ServerWorld, position: &BlockPos, age: u8) {
+    if tick(world, position, age) {
+        schedule(world, position);
+    }
+}
+pub fn on_random_tick(world: &mut ServerWorld, position: &BlockPos, age: u8) {
+    tick(world, position, age);
+}
+
+// Returns false if the fire went out.
+fn tick(world: &mut ServerWorld, position: &BlockPos, age: u8) -> bool {
+    if touches_water(world, position) {
+        world.set_block(position, BlockTypes::Air as u8);
+        return false;
+    }
+
+    let new_age = (age + world.get_random_mut().random_range(0..3) / 2).min(BlockTypes::MAX_FIRE_AGE);
+    let is_fueled = get_ignite_chance(world, position) > 0;
+    let burns_out = if is_fueled {
+        new_age == BlockTypes::MAX_FIRE_AGE && world.get_random_mut().random_bool(0.25)
+    } else {
+        new_age > MAX_UNFUELED_AGE || !can_burn_at(world, position)
+    };
+    if burns_out {
+        world.set_block(position, BlockTypes::Air as u8);
+        return false;
+    }
+    if new_age != age {
+        world.set_block(position, BlockTypes::get_fire(new_age));
+    }
+
+    for direction in Direction::ALL {
+        // Flames reach up and down less easily than sideways.
+        let odds = if direction == Direction::Top || direction == Direction::Bottom { 250 } else { 300 };
+        burn(world, &position.offset(direction), new_age, odds);
+    }
+    spread(world, position, new_age);
+    true
+}
+
+// A flammable block next to fire burns away, young fire may leave fire in its place.
+fn burn(world: &mut ServerWorld, position: &BlockPos, age: u8, odds: u32) {
+    let chance = BlockTypes::get_burn_chance(world.get_block(position));
+    if chance == 0 || world.get_random_mut().random_range(0..odds) >= chance as u32 {
+        return;
+    }
+
+    let random = world.get_random_mut();
+    let block = if random.random_range(0..age as u32 + 10) < 5 {
+        BlockTypes::get_fire((age + random.random_range(0..5) / 4).min(BlockTypes::MAX_FIRE_AGE))
+    } else {
+        BlockTypes::Air as u8
+    };
+    world.set_block(position, block);
+}
+
+// Fire jumps to empty blocks around it, up to four above, more easily next to blocks that catch fire well,
+// less easily upwards and the older it gets. The odds are Minecraft's on normal difficulty.
+fn spread(world: &mut ServerWorld, position: &BlockPos, age: u8) {
+    for dx in -1..=1 {
+        for dz in -1..=1 {
+            for dy in -1..=4 {
+                let target = BlockPos::new(position.x + dx, position.y + dy, position.z + dz);
+                if target == *position || world.get_block(&target) != BlockTypes::Air as u8 {
+                    continue;
+                }
+
+                let ignite_chance = get_ignite_chance(world, &target) as u32;
+                if ignite_chance == 0 {
+                    continue;
+                }
+                let chance = (ignite_chance + 54) / (age as u32 + 30);
+                let odds = if dy > 1 { dy as u32 * 100 } else { 100 };
+
+                let random = world.get_random_mut();
+                if random.random_range(0..odds) < chance {
+                    let new_age = (age + random.random_range(0..5) / 4).min(BlockTypes::MAX_FIRE_AGE);
+                    world.set_block(&target, BlockTypes::get_fire(new_age));
+                }
+            }
+        }
+    }
+}
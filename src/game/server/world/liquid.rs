@@ -0,0 +1,237 @@
+use crate::game::common::{coords::{BlockPos, Direction}, world::{block_types::BlockTypes, liquid::Liquid}};
+
+use super::super::ServerWorld;
+
+// Liquids only change in their scheduled ticks, a neighbour change just asks for one.
+// A flowing block is one level further than the lowest neighbour that feeds it, and dries up when nothing does.
+
+fn get_tick_delay(liquid: Liquid) -> u64 {
+    match liquid {
+        Liquid::Water => 5,
+        Liquid::Lava => 30,
+    }
+}
+
+pub fn on_neighbour_changed(world: &mut ServerWorld, position: &BlockPos, liquid: Liquid, level: u8) {
+    if liquid == Liquid::Lava && harden(world, position, level) {
+        return;
+    }
+    world.schedule_tick(position, get_tick_delay(liquid));
+}
+
+pub fn on_scheduled_tick(world: &mut ServerWorld, position: &BlockPos, liquid: Liquid, level: u8) {
+    let mut level = level;
+    if level != Liquid::SOURCE_LEVEL {
+        match get_fed_level(world, position, liquid) {
+            Some(fed_level) if fed_level != level => {
+                world.set_block(position, liquid.get_block(fed_level));
+                level = fed_level;
+            },
+            Some(_) => {},
+            None => {
+                world.set_block(position, BlockTypes::Air as u8);
+                return;
+            },
+        }
+    }
+
+    spread(world, position, liquid, level);
+}
+
+// Lava touching water turns into stone if it is a source and cobblestone otherwise.
+fn harden(world: &mut ServerWorld, position: &BlockPos, level: u8) -> bool {
+    let touches_water = Direction::ALL.into_iter()
+        .filter(|direction| *direction != Direction::Bottom)
+        .any(|direction| matches!(Liquid::from_block(world.get_block(&position.offset(direction))), Some((Liquid::Water, _))));
+    if !touches_water {
+        return false;
+    }
+
+    let block = if level == Liquid::SOURCE_LEVEL { BlockTypes::Stone } else { BlockTypes::Cobblestone };
+    world.set_block(position, block as u8);
+    true
+}
+
+// Whether the liquid rests on something, only then does it flow sideways instead of just down.
+fn is_supported(world: &ServerWorld, position: &BlockPos, liquid: Liquid) -> bool {
+    if position.y <= 0 {
+        return true;
+    }
+
+    let below = world.get_block(&position.offset(Direction::Bottom));
+    below != BlockTypes::Air as u8 && Liquid::from_block(below).is_none_or(|(other, _)| other != liquid)
+}
+fn spreads_sideways(world: &ServerWorld, position: &BlockPos, liquid: Liquid, level: u8) -> bool {
+    level == Liquid::SOURCE_LEVEL || is_supported(world, position, liquid)
+}
+fn get_sideways_level(level: u8) -> u8 {
+    if level == Liquid::SOURCE_LEVEL || level == Liquid::FALLING_LEVEL { 1 } else { level + 1 }
+}
+
+// The level the neighbours give the position, None if they no longer feed it.
+fn get_fed_level(world: &ServerWorld, position: &BlockPos, liquid: Liquid) -> Option<u8> {
+    if let Some((above, _)) = Liquid::from_block(world.get_block(&position.offset(Direction::Top))) {
+        if above == liquid {
+            return Some(Liquid::FALLING_LEVEL);
+        }
+    }
+
+    let mut sources = 0;
+    let mut lowest: Option<u8> = None;
+    for direction in Direction::HORIZONTAL {
+        let neighbour = position.offset(direction);
+        let Some((other, level)) = Liquid::from_block(world.get_block(&neighbour)) else { continue; };
+        if other != liquid || !spreads_sideways(world, &neighbour, liquid, level) {
+            continue;
+        }
+
+        if level == Liquid::SOURCE_LEVEL {
+            sources += 1;
+        }
+        let fed_level = get_sideways_level(level);
+        lowest = Some(lowest.map_or(fed_level, |lowest| lowest.min(fed_level)));
+    }
+
+    // Water between two sources becomes a source too, if it does not drain away below.
+    if liquid == Liquid::Water && sources >= 2 {
+        let below = world.get_block(&position.offset(Direction::Bottom));
+        if BlockTypes::is_solid(below) || below == liquid.get_source() {
+            return Some(Liquid::SOURCE_LEVEL);
+        }
+    }
+
+    lowest.filter(|level| *level <= world.get_rules().get_flow_distance(liquid))
+}
+
+fn spread(world: &mut ServerWorld, position: &BlockPos, liquid: Liquid, level: u8) {
+    if position.y > 0 {
+        flow_into(world, &position.offset(Direction::Bottom), liquid, Liquid::FALLING_LEVEL);
+    }
+
+    if !spreads_sideways(world, position, liquid, level) {
+        return;
+    }
+    let sideways_level = get_sideways_level(level);
+    if sideways_level > world.get_rules().get_flow_distance(liquid) {
+        return;
+    }
+    for direction in Direction::HORIZONTAL {
+        flow_into(world, &position.offset(direction), liquid, sideways_level);
+    }
+}
+
+// Liquid flows into air, fire, crops and further flowing blocks of itself, what it washes away leaves its drops.
+// Lava flowing into water turns it into stone, water never flows into lava but makes the lava harden next to it.
+fn flow_into(world: &mut ServerWorld, position: &BlockPos, liquid: Liquid, level: u8) {
+    let block = world.get_block(position);
+    let can_flow = match Liquid::from_block(block) {
+        None => !BlockTypes::is_solid(block),
+        Some((other, _)) if other != liquid => {
+            if liquid == Liquid::Lava {
+                world.set_block(position, BlockTypes::Stone as u8);
+            }
+            false
+        },
+        Some((_, current)) => {
+            current != Liquid::SOURCE_LEVEL && current != Liquid::FALLING_LEVEL && (level == Liquid::FALLING_LEVEL || current > level)
+        },
+    };
+
+    if can_flow {
+        if block != BlockTypes::Air as u8 && !BlockTypes::is_liquid(block) {
+            world.break_block(position);
+        }
+        world.set_block(position, liquid.get_block(level));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::game::common::{coords::{BlockAxis, BlockPos, ChunkPos}, entity::EntityKind, item::{ItemId, ItemTypes}, world::{block_types::BlockTypes, liquid::Liquid}};
+
+    use super::super::{super::ServerWorld, ticket::{ChunkLevel, Ticket, TicketHolder}};
+
+    // Deep in the stone, so digging out the cave doesn't darken any sky light.
+    const FLOOR_Y: BlockAxis = 29;
+
+    // A world with a flat cave two blocks high dug out of the chunk at the origin for liquids to flow in.
+    fn create_world() -> ServerWorld {
+        let mut world = ServerWorld::create(1);
+        world.get_rules_mut().set_random_tick_speed(0);
+        world.set_ticket(TicketHolder::Spawn, Ticket::new(ChunkPos::new(0, 0), 0, ChunkLevel::Ticking));
+        world.update_chunks(Duration::ZERO);
+
+        for x in 0..16 {
+            for z in 0..16 {
+                world.set_block(&BlockPos::new(x, FLOOR_Y + 1, z), BlockTypes::Air as u8);
+                world.set_block(&BlockPos::new(x, FLOOR_Y + 2, z), BlockTypes::Air as u8);
+            }
+        }
+        world
+    }
+    fn run_ticks(world: &mut ServerWorld, ticks: usize) {
+        (0..ticks).for_each(|_| world.tick());
+    }
+    fn on_floor(x: BlockAxis, z: BlockAxis) -> BlockPos {
+        BlockPos::new(x, FLOOR_Y + 1, z)
+    }
+
+    #[test]
+    fn source_spreads_as_far_as_the_flow_distance() {
+        let mut world = create_world();
+        world.set_block(&on_floor(8, 8), Liquid::Water.get_source());
+        run_ticks(&mut world, 100);
+
+        let distance = world.get_rules().get_flow_distance(Liquid::Water);
+        assert_eq!(world.get_block(&on_floor(8 - distance as BlockAxis, 8)), Liquid::Water.get_block(distance));
+        assert_eq!(world.get_block(&on_floor(7 - distance as BlockAxis, 8)), BlockTypes::Air as u8);
+        assert_eq!(world.get_block(&on_floor(8, 8 - distance as BlockAxis)), Liquid::Water.get_block(distance));
+    }
+
+    #[test]
+    fn water_drains_without_its_source() {
+        let mut world = create_world();
+        world.set_block(&on_floor(8, 8), Liquid::Water.get_source());
+        run_ticks(&mut world, 100);
+        world.set_block(&on_floor(8, 8), BlockTypes::Air as u8);
+        run_ticks(&mut world, 100);
+
+        for x in 0..16 {
+            for z in 0..16 {
+                assert_eq!(world.get_block(&on_floor(x, z)), BlockTypes::Air as u8);
+            }
+        }
+    }
+
+    #[test]
+    fn lava_hardens_next_to_water() {
+        let mut world = create_world();
+        world.set_block(&on_floor(8, 8), Liquid::Lava.get_source());
+        run_ticks(&mut world, 200);
+        assert_eq!(world.get_block(&on_floor(10, 8)), Liquid::Lava.get_block(2));
+
+        world.set_block(&on_floor(11, 8), Liquid::Water.get_source());
+        world.set_block(&on_floor(8, 9), BlockTypes::Air as u8);
+        world.set_block(&on_floor(8, 10), Liquid::Water.get_source());
+        run_ticks(&mut world, 1);
+        assert_eq!(world.get_block(&on_floor(10, 8)), BlockTypes::Cobblestone as u8);
+
+        world.set_block(&on_floor(8, 9), Liquid::Water.get_source());
+        run_ticks(&mut world, 1);
+        assert_eq!(world.get_block(&on_floor(8, 8)), BlockTypes::Stone as u8);
+    }
+
+    #[test]
+    fn water_washes_crops_away() {
+        let mut world = create_world();
+        world.set_block(&BlockPos::new(6, FLOOR_Y, 8), BlockTypes::Farmland as u8);
+        world.set_block(&on_floor(6, 8), BlockTypes::get_wheat(BlockTypes::MAX_WHEAT_AGE));
+        world.set_block(&on_floor(8, 8), Liquid::Water.get_source());
+        run_ticks(&mut world, 20);
+
+        assert!(Liquid::from_block(world.get_block(&on_floor(6, 8))).is_some());
+        assert!(world.get_entities().values().any(|entity| entity.kind == EntityKind::Item { item: ItemTypes::Wheat as ItemId }));
+    }
+}
@@ -1,2 +1,10 @@
 pub mod chunk;
-pub mod worldgen;
\ No newline at end of file
+pub mod block_behaviour;
+pub mod worldgen;
+pub mod storage;
+pub mod ticket;
+pub mod rules;
+pub mod liquid;
+pub mod signal;
+pub mod fire;
+pub mod farming;
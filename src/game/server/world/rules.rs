@@ -0,0 +1,71 @@
+use crate::game::common::{protocol::buffer::{PacketReader, PacketWriter}, world::liquid::Liquid};
+
+// Settings of a world that commands can change, they are saved with the level.
+#[derive(Clone)]
+pub struct WorldRules {
+    // How many blocks a liquid flows sideways from where it is fed.
+    pub water_flow_distance: u8,
+    pub lava_flow_distance: u8,
+    // Blocks picked in every section of a ticking chunk each tick, zero stops random ticks.
+    pub random_tick_speed: u32,
+}
+
+impl WorldRules {
+    pub const MAX_RANDOM_TICK_SPEED: u32 = 4096;
+
+    pub fn create() -> Self {
+        Self {
+            water_flow_distance: 7,
+            lava_flow_distance: 3,
+            random_tick_speed: 3,
+        }
+    }
+
+    pub fn get_flow_distance(&self, liquid: Liquid) -> u8 {
+        match liquid {
+            Liquid::Water => self.water_flow_distance,
+            Liquid::Lava => self.lava_flow_distance,
+        }
+    }
+    pub fn set_flow_distance(&mut self, liquid: Liquid, distance: u8) {
+        let distance = distance.clamp(1, Liquid::MAX_FLOW_LEVEL);
+        match liquid {
+            Liquid::Water => self.water_flow_distance = distance,
+            Liquid::Lava => self.lava_flow_distance = distance,
+        }
+    }
+    pub fn set_random_tick_speed(&mut self, speed: u32) {
+        self.random_tick_speed = speed.min(Self::MAX_RANDOM_TICK_SPEED);
+    }
+
+    // Rules are stored by name, so ones added later get their default in older data and unknown ones are skipped.
+    pub fn write(&self, writer: &mut PacketWriter) {
+        let values = [
+            ("water_flow_distance", self.water_flow_distance as u32),
+            ("lava_flow_distance", self.lava_flow_distance as u32),
+            ("random_tick_speed", self.random_tick_speed),
+        ];
+
+        writer.write_u32(values.len() as u32);
+        for (name, value) in values {
+            writer.write_string(name);
+            writer.write_u32(value);
+        }
+    }
+    pub fn read(reader: &mut PacketReader) -> Option<Self> {
+        let mut rules = Self::create();
+        for _ in 0..reader.read_u32()? {
+            let name = reader.read_string()?;
+            let value = reader.read_u32()?;
+
+            match name.as_str() {
+                "water_flow_distance" => rules.set_flow_distance(Liquid::Water, value.min(u8::MAX as u32) as u8),
+                "lava_flow_distance" => rules.set_flow_distance(Liquid::Lava, value.min(u8::MAX as u32) as u8),
+                "random_tick_speed" => rules.set_random_tick_speed(value),
+                _ => {},
+            }
+        }
+
+        Some(rules)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::game::common::{coords::{BlockPos, Direction}, world::signal::SignalBlock};
+
+use super::super::ServerWorld;
+
+// Power spreads through neighbour updates: a block that changes tells its neighbours, and they work out
+// what they receive now. Wire loses one level per block, repeaters and inverters react after a delay.
+// Only the blocks right next to a power source are powered, power does not pass through other blocks.
+
+// A pressed button turns off again after this many ticks.
+const BUTTON_DURATION: u64 = 20;
+// Lamps wait a moment before going dark, so short gaps in a signal don't make them flicker.
+const LAMP_OFF_DELAY: u64 = 4;
+const INVERTER_DELAY: u64 = 2;
+// World ticks per step of a repeater's delay.
+const REPEATER_TICKS_PER_DELAY: u64 = 2;
+
+// The power the block at the source position sends to its neighbour in the given direction.
+fn get_power_towards(world: &ServerWorld, source: &BlockPos, direction: Direction) -> u8 {
+    match SignalBlock::from_block(world.get_block(source)) {
+        Some(SignalBlock::Lever { is_on: true } | SignalBlock::Button { is_pressed: true }) => SignalBlock::MAX_POWER,
+        Some(SignalBlock::Wire { power }) => power,
+        Some(SignalBlock::Repeater { facing, is_powered: true, .. }) if facing == direction => SignalBlock::MAX_POWER,
+        Some(SignalBlock::Inverter { facing, is_on: true }) if facing == direction => SignalBlock::MAX_POWER,
+        _ => 0,
+    }
+}
+fn is_powered(world: &ServerWorld, position: &BlockPos) -> bool {
+    Direction::ALL.into_iter().any(|direction| get_power_towards(world, &position.offset(direction), direction.get_opposite()) > 0)
+}
+// Repeaters and inverters only listen to the block behind them.
+fn has_input(world: &ServerWorld, position: &BlockPos, facing: Direction) -> bool {
+    get_power_towards(world, &position.offset(facing.get_opposite()), facing) > 0
+}
+
+// Wire takes the strongest power next to it, one less if it comes from other wire.
+fn get_wire_power(world: &ServerWorld, position: &BlockPos) -> u8 {
+    Direction::ALL.into_iter()
+        .map(|direction| {
+            let neighbour = position.offset(direction);
+            match SignalBlock::from_block(world.get_block(&neighbour)) {
+                Some(SignalBlock::Wire { power }) => power.saturating_sub(1),
+                _ => get_power_towards(world, &neighbour, direction.get_opposite()),
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn on_neighbour_changed(world: &mut ServerWorld, position: &BlockPos, signal: SignalBlock) {
+    match signal {
+        SignalBlock::Wire { power } => {
+            let new_power = get_wire_power(world, position);
+            if new_power != power {
+                world.set_block(position, SignalBlock::Wire { power: new_power }.get_block());
+            }
+        },
+        SignalBlock::Lamp { is_lit } => {
+            let is_powered = is_powered(world, position);
+            if is_powered && !is_lit {
+                world.set_block(position, SignalBlock::Lamp { is_lit: true }.get_block());
+            } else if !is_powered && is_lit {
+                world.schedule_tick(position, LAMP_OFF_DELAY);
+            }
+        },
+        SignalBlock::Door { is_powered: was_powered, .. } => {
+            let is_powered = is_powered(world, position);
+            if is_powered != was_powered {
+                world.set_block(position, SignalBlock::Door { is_open: is_powered, is_powered }.get_block());
+            }
+        },
+        SignalBlock::Repeater { facing, delay, is_powered } => {
+            if has_input(world, position, facing) != is_powered {
+                world.schedule_tick(position, delay as u64 * REPEATER_TICKS_PER_DELAY);
+            }
+        },
+        SignalBlock::Inverter { facing, is_on } => {
+            if has_input(world, position, facing) == is_on {
+                world.schedule_tick(position, INVERTER_DELAY);
+            }
+        },
+        SignalBlock::Lever { .. } | SignalBlock::Button { .. } => {},
+    }
+}
+
+// Delayed blocks look at their input again when the tick is due, so a pulse shorter than the delay still goes through.
+pub fn on_scheduled_tick(world: &mut ServerWorld, position: &BlockPos, signal: SignalBlock) {
+    match signal {
+        SignalBlock::Button { is_pressed: true } => {
+            world.set_block(position, SignalBlock::Button { is_pressed: false }.get_block());
+        },
+        SignalBlock::Lamp { is_lit: true } if !is_powered(world, position) => {
+            world.set_block(position, SignalBlock::Lamp { is_lit: false }.get_block());
+        },
+        SignalBlock::Repeater { facing, delay, is_powered } => {
+            let has_input = has_input(world, position, facing);
+            if has_input != is_powered {
+                world.set_block(position, SignalBlock::Repeater { facing, delay, is_powered: has_input }.get_block());
+            }
+        },
+        SignalBlock::Inverter { facing, is_on } => {
+            let has_input = has_input(world, position, facing);
+            if has_input == is_on {
+                world.set_block(position, SignalBlock::Inverter { facing, is_on: !has_input }.get_block());
+            }
+        },
+        _ => {},
+    }
+}
+
+// A player right clicked the block. Returns false if the block can't be used.
+pub fn use_block(world: &mut ServerWorld, position: &BlockPos, signal: SignalBlock) -> bool {
+    let new_signal = match signal {
+        SignalBlock::Lever { is_on } => SignalBlock::Lever { is_on: !is_on },
+        SignalBlock::Button { is_pressed: false } => {
+            world.schedule_tick(position, BUTTON_DURATION);
+            SignalBlock::Button { is_pressed: true }
+        },
+        SignalBlock::Door { is_open, is_powered } => SignalBlock::Door { is_open: !is_open, is_powered },
+        SignalBlock::Repeater { facing, delay, is_powered } => {
+            SignalBlock::Repeater { facing, delay: delay % SignalBlock::MAX_REPEATER_DELAY + 1, is_powered }
+        },
+        _ => return false,
+    };
+
+    world.set_block(position, new_signal.get_block());
+    true
+}
@@ -0,0 +1,236 @@
+use std::{fs, io, path::{Path, PathBuf}};
+
+use crate::game::common::{coords::{ChunkPos, LocalBlockPos}, inventory::Inventory, player::{GameMode, INVENTORY_SLOTS, MAX_HEALTH}, protocol::buffer::{PacketReader, PacketWriter}, weather::Weather, world::{block_entity::BlockEntity, liquid::Liquid}};
+
+use super::{chunk::Chunk, rules::WorldRules};
+
+pub struct LevelData {
+    pub seed: u32,
+    pub time: u64,
+    pub rules: WorldRules,
+    // The weather and how many ticks it still lasts, worlds saved before there was weather have none.
+    pub weather: Option<(Weather, u64)>,
+}
+
+// What is kept of a player between sessions, by name.
+#[derive(Clone)]
+pub struct PlayerData {
+    pub game_mode: GameMode,
+    pub health: u8,
+    pub inventory: Inventory,
+}
+
+impl PlayerData {
+    // Players new to the world start in survival with nothing.
+    pub fn create() -> Self {
+        Self { game_mode: GameMode::Survival, health: MAX_HEALTH, inventory: Inventory::create(INVENTORY_SLOTS) }
+    }
+}
+
+// A world is stored as a directory with level data, operator and forced chunk lists, one file per modified chunk
+// and one per player that played in it.
+#[derive(Clone)]
+pub struct WorldStorage {
+    directory: PathBuf,
+}
+
+impl WorldStorage {
+    // Version 1 level data has no rules, version 2 adds the flow distances, version 3 stores rules by name
+    // and version 4 adds the weather.
+    const FORMAT_VERSION: u32 = 4;
+    // Version 1 chunks only have blocks, version 2 adds scheduled ticks, version 3 block entities
+    // and version 4 stores item ids in two bytes.
+    const CHUNK_FORMAT_VERSION: u32 = 4;
+    const PLAYER_FORMAT_VERSION: u32 = 1;
+
+    pub fn open<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(directory.join("chunks"))?;
+        fs::create_dir_all(directory.join("players"))?;
+
+        Ok(Self { directory })
+    }
+
+    // Files are written next to their destination first, so a crash mid-save never leaves a truncated file behind.
+    fn write_file(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        let temporary_path = path.with_extension("tmp");
+        fs::write(&temporary_path, bytes)?;
+        fs::rename(temporary_path, path)
+    }
+
+    pub fn load_level(&self) -> Option<LevelData> {
+        let bytes = fs::read(self.directory.join("level.dat")).ok()?;
+        let mut reader = PacketReader::create(&bytes);
+
+        let version = reader.read_u32()?;
+        if version == 0 || version > Self::FORMAT_VERSION {
+            return None;
+        }
+
+        let seed = reader.read_u32()?;
+        let time = reader.read_u64()?;
+        let rules = match version {
+            1 => WorldRules::create(),
+            2 => {
+                let mut rules = WorldRules::create();
+                rules.set_flow_distance(Liquid::Water, reader.read_u8()?);
+                rules.set_flow_distance(Liquid::Lava, reader.read_u8()?);
+                rules
+            },
+            _ => WorldRules::read(&mut reader)?,
+        };
+        let weather = if version >= 4 { Some((Weather::from_id(reader.read_u8()?)?, reader.read_u64()?)) } else { None };
+
+        Some(LevelData { seed, time, rules, weather })
+    }
+    pub fn save_level(&self, level: &LevelData) -> io::Result<()> {
+        let mut writer = PacketWriter::create();
+        writer.write_u32(Self::FORMAT_VERSION);
+        writer.write_u32(level.seed);
+        writer.write_u64(level.time);
+        level.rules.write(&mut writer);
+        let (weather, duration) = level.weather.unwrap_or((Weather::Clear, 0));
+        writer.write_u8(weather.get_id());
+        writer.write_u64(duration);
+
+        self.write_file(&self.directory.join("level.dat"), &writer.into_bytes())
+    }
+
+    fn get_chunk_path(&self, position: &ChunkPos) -> PathBuf {
+        self.directory.join("chunks").join(format!("{}.{}.chunk", position.x, position.z))
+    }
+    // Scheduled ticks are stored as the ticks left until they are due, now is the current world tick.
+    pub fn encode_chunk(chunk: &Chunk, now: u64) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+        writer.write_u32(Self::CHUNK_FORMAT_VERSION);
+        writer.write_bytes(chunk.get_blocks());
+
+        let scheduled_ticks: Vec<(u64, LocalBlockPos)> = chunk.get_scheduled_ticks().collect();
+        writer.write_u32(scheduled_ticks.len() as u32);
+        for (due, position) in scheduled_ticks {
+            writer.write_u8(position.x as u8);
+            writer.write_u8(position.y as u8);
+            writer.write_u8(position.z as u8);
+            writer.write_u32(due.saturating_sub(now).min(u32::MAX as u64) as u32);
+        }
+
+        let block_entities: Vec<(LocalBlockPos, &BlockEntity)> = chunk.get_block_entities().collect();
+        writer.write_u32(block_entities.len() as u32);
+        for (position, entity) in block_entities {
+            writer.write_u8(position.x as u8);
+            writer.write_u8(position.y as u8);
+            writer.write_u8(position.z as u8);
+            entity.write(&mut writer);
+        }
+
+        writer.into_bytes()
+    }
+    pub fn decode_chunk(bytes: &[u8], now: u64) -> Option<Chunk> {
+        let mut reader = PacketReader::create(bytes);
+        let version = reader.read_u32()?;
+        if version == 0 || version > Self::CHUNK_FORMAT_VERSION {
+            return None;
+        }
+
+        let mut chunk = Chunk::from_blocks(reader.read_bytes()?)?;
+        if version >= 2 {
+            for _ in 0..reader.read_u32()? {
+                let position = LocalBlockPos::new(reader.read_u8()? as usize, reader.read_u8()? as usize, reader.read_u8()? as usize);
+                chunk.schedule_tick(&position, now + reader.read_u32()? as u64);
+            }
+        }
+        if version >= 3 {
+            for _ in 0..reader.read_u32()? {
+                let position = LocalBlockPos::new(reader.read_u8()? as usize, reader.read_u8()? as usize, reader.read_u8()? as usize);
+                chunk.set_block_entity(&position, BlockEntity::read(&mut reader, version == 3)?);
+            }
+        }
+        chunk.mark_saved();
+
+        if reader.is_empty() { Some(chunk) } else { None }
+    }
+
+    pub fn load_chunk(&self, position: &ChunkPos, now: u64) -> Option<Chunk> {
+        let bytes = fs::read(self.get_chunk_path(position)).ok()?;
+
+        let chunk = Self::decode_chunk(&bytes, now);
+        if chunk.is_none() {
+            println!("Failed to read chunk at: ({}, {}), it will be generated again.", position.x, position.z);
+        }
+
+        chunk
+    }
+    pub fn save_chunk(&self, position: &ChunkPos, chunk: &Chunk, now: u64) -> io::Result<()> {
+        self.write_file(&self.get_chunk_path(position), &Self::encode_chunk(chunk, now))
+    }
+
+    // Names are case insensitive like everywhere else, so the file is named in lowercase.
+    fn get_player_path(&self, name: &str) -> PathBuf {
+        self.directory.join("players").join(format!("{}.dat", name.to_ascii_lowercase()))
+    }
+    pub fn encode_player(data: &PlayerData) -> Vec<u8> {
+        let mut writer = PacketWriter::create();
+        writer.write_u32(Self::PLAYER_FORMAT_VERSION);
+        writer.write_u8(data.game_mode.get_id());
+        writer.write_u8(data.health);
+        data.inventory.write(&mut writer);
+
+        writer.into_bytes()
+    }
+    pub fn decode_player(bytes: &[u8]) -> Option<PlayerData> {
+        let mut reader = PacketReader::create(bytes);
+        let version = reader.read_u32()?;
+        if version == 0 || version > Self::PLAYER_FORMAT_VERSION {
+            return None;
+        }
+
+        let game_mode = GameMode::from_id(reader.read_u8()?)?;
+        let health = reader.read_u8()?.clamp(1, MAX_HEALTH);
+        let inventory = Inventory::read(&mut reader, INVENTORY_SLOTS, false)?;
+
+        if reader.is_empty() { Some(PlayerData { game_mode, health, inventory }) } else { None }
+    }
+
+    pub fn load_player(&self, name: &str) -> Option<PlayerData> {
+        let bytes = fs::read(self.get_player_path(name)).ok()?;
+
+        let data = Self::decode_player(&bytes);
+        if data.is_none() {
+            println!("Failed to read the data of player {}, they start over.", name);
+        }
+
+        data
+    }
+    pub fn save_player(&self, name: &str, data: &PlayerData) -> io::Result<()> {
+        self.write_file(&self.get_player_path(name), &Self::encode_player(data))
+    }
+
+    pub fn load_operators(&self) -> Vec<String> {
+        match fs::read_to_string(self.directory.join("operators.txt")) {
+            Ok(text) => text.lines().map(str::trim).filter(|line| !line.is_empty()).map(String::from).collect(),
+            Err(_) => vec![],
+        }
+    }
+    pub fn save_operators(&self, operators: &[String]) -> io::Result<()> {
+        let mut text = operators.join("\n");
+        text.push('\n');
+
+        self.write_file(&self.directory.join("operators.txt"), text.as_bytes())
+    }
+
+    pub fn load_forced_chunks(&self) -> Vec<ChunkPos> {
+        let Ok(text) = fs::read_to_string(self.directory.join("forced_chunks.txt")) else { return vec![]; };
+
+        text.lines().filter_map(|line| {
+            let mut parts = line.split_whitespace().map(str::parse);
+            match (parts.next(), parts.next()) {
+                (Some(Ok(x)), Some(Ok(z))) => Some(ChunkPos::new(x, z)),
+                _ => None,
+            }
+        }).collect()
+    }
+    pub fn save_forced_chunks(&self, positions: &[ChunkPos]) -> io::Result<()> {
+        let text: String = positions.iter().map(|position| format!("{} {}\n", position.x, position.z)).collect();
+        self.write_file(&self.directory.join("forced_chunks.txt"), text.as_bytes())
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashMap, time::Duration};
+
+use crate::game::common::{coords::{ChunkAxis, ChunkPos, CoordAxis}, player::PlayerId, world::mapping::ChunkPosHasherBuilder};
+
+// Who keeps the chunks loaded. Each holder owns at most one ticket, setting it again replaces the old one.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum TicketHolder {
+    Player(PlayerId),
+    Spawn,
+    Forced(ChunkPos),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ChunkLevel {
+    // Loaded so the ticking chunks next to it can see its blocks, but nothing happens in it.
+    Border,
+    // Fully simulated.
+    Ticking,
+}
+
+impl ChunkLevel {
+    const COUNT: usize = 2;
+
+    fn below(self) -> Option<ChunkLevel> {
+        match self {
+            ChunkLevel::Border => None,
+            ChunkLevel::Ticking => Some(ChunkLevel::Border),
+        }
+    }
+}
+
+// Chunks within the radius get the ticket level, and one more ring around them gets the level below it.
+#[derive(Clone, PartialEq)]
+pub struct Ticket {
+    pub center: ChunkPos,
+    pub radius: usize,
+    pub level: ChunkLevel,
+}
+
+impl Ticket {
+    pub fn new(center: ChunkPos, radius: usize, level: ChunkLevel) -> Self {
+        Self { center, radius, level }
+    }
+
+    fn for_each_chunk<F: FnMut(ChunkPos, ChunkLevel)>(&self, mut function: F) {
+        let outer_radius = self.radius + 1;
+        let iradius = outer_radius as ChunkAxis;
+
+        for x in -iradius..iradius + 1 {
+            for z in -iradius..iradius + 1 {
+                let distance = ((x * x + z * z) as CoordAxis).sqrt();
+                let level = if distance <= self.radius as CoordAxis {
+                    Some(self.level)
+                } else if distance <= outer_radius as CoordAxis {
+                    self.level.below()
+                } else {
+                    None
+                };
+
+                if let Some(level) = level {
+                    function(&self.center + ChunkPos::new(x, z), level);
+                }
+            }
+        }
+    }
+}
+
+// Counts how many tickets cover each chunk per level. Chunks that lose their last ticket are only
+// released after a delay, so walking back and forth over a border does not reload them every time.
+pub struct ChunkTickets {
+    tickets: HashMap<TicketHolder, Ticket>,
+    references: HashMap<ChunkPos, [u32; ChunkLevel::COUNT], ChunkPosHasherBuilder>,
+
+    added: Vec<ChunkPos>,
+    released: HashMap<ChunkPos, Duration, ChunkPosHasherBuilder>,
+    // Server time, it comes from the server so a replay releases chunks at the same moments.
+    time: Duration,
+}
+
+impl ChunkTickets {
+    pub const UNLOAD_DELAY: Duration = Duration::from_secs(5);
+
+    pub fn new() -> Self {
+        Self {
+            tickets: HashMap::new(),
+            references: HashMap::default(),
+
+            added: vec![],
+            released: HashMap::default(),
+            time: Duration::ZERO,
+        }
+    }
+
+    pub fn set(&mut self, holder: TicketHolder, ticket: Ticket) {
+        if self.tickets.get(&holder) == Some(&ticket) {
+            return;
+        }
+
+        // The new ticket is counted first, so chunks covered by both never drop to zero references.
+        ticket.for_each_chunk(|position, level| {
+            let references = self.references.entry(position.clone()).or_insert_with(|| {
+                self.added.push(position.clone());
+                [0; ChunkLevel::COUNT]
+            });
+            references[level as usize] += 1;
+            self.released.remove(&position);
+        });
+
+        if let Some(old_ticket) = self.tickets.insert(holder, ticket) {
+            self.release(&old_ticket);
+        }
+    }
+    pub fn remove(&mut self, holder: &TicketHolder) {
+        if let Some(ticket) = self.tickets.remove(holder) {
+            self.release(&ticket);
+        }
+    }
+    fn release(&mut self, ticket: &Ticket) {
+        ticket.for_each_chunk(|position, level| {
+            let Some(references) = self.references.get_mut(&position) else { return; };
+            references[level as usize] -= 1;
+
+            if references.iter().all(|count| *count == 0) {
+                self.references.remove(&position);
+                self.released.insert(position, self.time);
+            }
+        });
+    }
+
+    pub fn get_ticket(&self, holder: &TicketHolder) -> Option<&Ticket> {
+        self.tickets.get(holder)
+    }
+    pub fn get_tickets(&self) -> impl Iterator<Item = (&TicketHolder, &Ticket)> {
+        self.tickets.iter()
+    }
+
+    // The highest level of any ticket covering the chunk, or None if it is not wanted.
+    pub fn get_level(&self, position: &ChunkPos) -> Option<ChunkLevel> {
+        let references = self.references.get(position)?;
+        [ChunkLevel::Ticking, ChunkLevel::Border].into_iter().find(|level| references[*level as usize] > 0)
+    }
+
+    // Chunks that got their first ticket since the last call, they may still be loaded from before.
+    pub fn take_added(&mut self) -> Vec<ChunkPos> {
+        std::mem::take(&mut self.added)
+    }
+    pub fn set_time(&mut self, time: Duration) {
+        self.time = time;
+    }
+    // Chunks that have been without a ticket for longer than the unload delay.
+    pub fn take_expired(&mut self) -> Vec<ChunkPos> {
+        let expired: Vec<ChunkPos> = self.released.iter()
+            .filter(|(_, released_at)| self.time.saturating_sub(**released_at) >= Self::UNLOAD_DELAY)
+            .map(|(position, _)| position.clone())
+            .collect();
+
+        for position in &expired {
+            self.released.remove(position);
+        }
+        expired
+    }
+}
@@ -2,94 +2,149 @@ mod engine;
 mod game;
 mod camera;
 
-use std::f32;
+use std::{net::{Ipv4Addr, SocketAddr, ToSocketAddrs}, time::Duration};
 
 use camera::Camera;
-use cgmath::InnerSpace;
-use cgmath::Point3;
-use cgmath::Vector3;
-use cgmath::Zero;
 use engine::texture::Texture;
 use engine::timer::Timer;
 use engine::window::*;
 use engine::shader::*;
+use game::client::block_breaking::BlockBreaking;
+use game::client::block_entity_renderer::BlockEntityRenderer;
+use game::client::chat::Chat;
 use game::client::debug::LineDebug;
+use game::client::hotbar::Hotbar;
+use game::client::lan_discovery::LanDiscovery;
+use game::client::network::ServerConnection;
+use game::client::entity_renderer::EntityRenderer;
+use game::client::player_renderer::PlayerRenderer;
+use game::client::prediction::BlockPredictions;
+use game::client::remote_entity::RemoteEntity;
+use game::client::precipitation::Precipitation;
+use game::client::remote_player::RemotePlayer;
+use game::client::sky::Sky;
 use game::client::resources::Block;
 use game::client::resources::BlockRegistry;
+use game::client::resources::EntityShader;
 use game::client::resources::LineShader;
 use game::client::resources::TerrainAtlas;
 use game::client::resources::TerrainShader;
 use game::client::world::ClientWorld;
-use game::common::coords::BlockPos;
 use game::common::coords::ChunkPos;
 use game::common::coords::Coord;
+use game::common::coords::Direction;
+use game::common::inventory::Inventory;
+use game::common::item::ItemTypes;
+use game::common::physics::PlayerBody;
+use game::common::player::GameMode;
+use game::common::player::INVENTORY_SLOTS;
+use game::common::player::MAX_HEALTH;
+use game::common::player::MAX_REACH_DISTANCE;
+use game::common::protocol::DEFAULT_PORT;
+use game::common::protocol::packet::ClientPacket;
+use game::common::protocol::packet::ServerPacket;
 use game::common::world::block_types::BlockTypes;
+use game::common::world::liquid::Liquid;
+use game::common::world::raycast::raycast;
+use game::common::world::signal::SignalBlock;
 use game::server::ServerWorld;
-use game::server::world::worldgen::WorldGen;
+use game::server::command::CommandSource;
+use game::server::console::Console;
+use game::server::game_server::GameServer;
+use game::server::integrated_server::IntegratedServer;
+use game::server::recording::SessionRecording;
+use game::server::remote_console::RemoteConsole;
+use game::server::replay::replay;
+use game::server::world::chunk::Chunk;
+use game::server::world::storage::WorldStorage;
+
+fn open_world(name: &str) -> ServerWorld {
+    let directory = format!("./saves/{}", name);
+    match WorldStorage::open(&directory) {
+        Ok(storage) => ServerWorld::open(storage),
+        Err(error) => panic!("Failed to open world at: {}. Error: {}.", directory, error),
+    }
+}
+
+// Listens for LAN announcements for a few seconds and returns the address of the first compatible server.
+fn find_lan_server() -> Option<String> {
+    let mut discovery = match LanDiscovery::listen() {
+        Ok(discovery) => discovery,
+        Err(error) => panic!("Failed to listen for LAN servers. Error: {}.", error),
+    };
+
+    println!("Searching for LAN servers...");
+    let start = std::time::Instant::now();
+    while start.elapsed() < Duration::from_secs(3) {
+        discovery.update();
+        std::thread::sleep(Duration::from_millis(50));
+    }
 
-struct RayHitInfo {
-    position: Point3<i64>,
-    normal: Vector3<i64>,
+    for server in discovery.get_servers() {
+        println!("Found LAN server: {}.", server.get_description());
+    }
+    discovery.get_servers().iter()
+        .find(|server| server.is_compatible())
+        .map(|server| server.address.to_string())
 }
 
-fn raycast(world: &ServerWorld, origin: &Coord, direction: &Vector3<f32>, max_distance: f32) -> Option<RayHitInfo> {
-    let step = direction.map(|v| v.signum() as i64);
-    let delta = direction.map(|v| {
-        if v != 0.0 {
-            (1.0 / v).abs()
-        } else {
-            f32::INFINITY
-        }
-    });
-
-    let mut position = Point3::new(origin.get_block_x(), origin.get_block_y(), origin.get_block_z());
-    let mut tmax = Point3::new(
-        if step.x > 0 { 1.0 - origin.get_frac_x() } else { origin.get_frac_x() } * delta.x,
-        if step.y > 0 { 1.0 - origin.get_frac_y() } else { origin.get_frac_y() } * delta.y,
-        if step.z > 0 { 1.0 - origin.get_frac_z() } else { origin.get_frac_z() } * delta.z
-    );
-
-    let mut traveled_distance = 0.0f32;
-    let mut normal = Vector3::<f32>::zero();
-
-    while traveled_distance < max_distance {
-        if position.y >= u16::MIN as i64 && position.y <= u16::MAX as i64 && world.get_block(&BlockPos::new(position.x, position.y, position.z)) != BlockTypes::Air as u8 {
-            return Some(RayHitInfo {
-                position,
-                normal: normal.normalize().map(|v| v as i64),
-            })
-        }
+fn start_recording(server: &mut GameServer, path: &Option<String>) {
+    let Some(path) = path else { return; };
+    match server.start_recording(path) {
+        Ok(()) => println!("Recording the session to: {}.", path),
+        Err(error) => panic!("Failed to record the session to: {}. Error: {}.", path, error),
+    }
+}
 
-        if tmax.x < tmax.y {
-            if tmax.x < tmax.z {
-                position.x += step.x;
-                normal = Vector3::unit_x() * -step.x as f32;
+// Replays a recorded session without a window and exits with an error if the server did anything differently.
+fn run_replay(path: &str) {
+    let recording = match SessionRecording::load(path) {
+        Ok(recording) => recording,
+        Err(error) => {
+            println!("Failed to load the recording: {}. Error: {}.", path, error);
+            std::process::exit(2);
+        },
+    };
+
+    let report = replay(&recording);
+    println!("Replayed {} updates, {} packets and {} block changes.", report.updates, report.packets, report.block_changes);
+    match report.mismatch {
+        Some(mismatch) => {
+            println!("The replay diverged: {}.", mismatch);
+            std::process::exit(1);
+        },
+        None => println!("The replay matches the recording."),
+    }
+}
 
-                traveled_distance = tmax.x;
-                tmax.x += delta.x;
-            } else {
-                position.z += step.z;
-                normal = Vector3::unit_z() * -step.z as f32;
+fn run_dedicated_server(port: u16, world_name: &str, server_name: &str, remote_console: Option<(String, String)>, record_path: &Option<String>) {
+    let mut server = GameServer::create(open_world(world_name));
+    start_recording(&mut server, record_path);
+    match server.bind(("0.0.0.0", port)) {
+        Ok(address) => println!("Server is listening on: {}.", address),
+        Err(error) => panic!("Failed to bind server to port: {}. Error: {}.", port, error),
+    }
+    if let Err(error) = server.announce_on_lan(server_name.to_string()) {
+        println!("Failed to announce the server on LAN. Error: {}.", error);
+    }
+    if let Some((address, password)) = remote_console {
+        match server.enable_remote_console(address.as_str(), password) {
+            Ok(address) => println!("Remote console is listening on: {}.", address),
+            Err(error) => panic!("Failed to start remote console on: {}. Error: {}.", address, error),
+        }
+    }
 
-                traveled_distance = tmax.z;
-                tmax.z += delta.z;
+    let console = Console::create();
+    while server.is_running() {
+        while let Some(line) = console.poll() {
+            for feedback in server.execute_command(CommandSource::Console, &line) {
+                println!("{}", feedback);
             }
-        } else if tmax.y < tmax.z {
-            position.y += step.y;
-            normal = Vector3::unit_y() * -step.y as f32;
-
-            traveled_distance = tmax.y;
-            tmax.y += delta.y;
-        } else {
-            position.z += step.z;
-            normal = Vector3::unit_z() * -step.z as f32;
-
-            traveled_distance = tmax.z;
-            tmax.z += delta.z;
         }
-    }
 
-    None
+        server.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
 }
 
 fn main() {
@@ -98,12 +153,67 @@ fn main() {
     if vsync { println!("Using vsync."); }
 
     let mut max_fps = WindowBuilder::NO_MAX_FPS;
-    for arg in args {
+    let mut port = DEFAULT_PORT;
+    let mut connect_address: Option<String> = None;
+    let mut name = format!("Player{}", rand::random::<u16>() % 1000);
+    let mut world_name = String::from("world");
+    let mut server_name = String::from("A MyFirstOpenGLGame server");
+    let mut remote_console_address = String::from(RemoteConsole::DEFAULT_ADDRESS);
+    let mut remote_console_password = std::env::var("RCON_PASSWORD").ok();
+    let mut record_path: Option<String> = None;
+
+    for arg in &args {
         if let Some(max_fps_arg) = arg.strip_prefix("--max-fps=") {
             if let Ok(max_fps_eval) = meval::eval_str(max_fps_arg) {
                 max_fps = max_fps_eval as u32;
             }
         }
+        if let Some(port_arg) = arg.strip_prefix("--port=") {
+            if let Ok(port_value) = port_arg.parse() {
+                port = port_value;
+            }
+        }
+        if let Some(address_arg) = arg.strip_prefix("--connect=") {
+            connect_address = Some(address_arg.to_string());
+        }
+        if let Some(name_arg) = arg.strip_prefix("--name=") {
+            name = name_arg.to_string();
+        }
+        if let Some(world_arg) = arg.strip_prefix("--world=") {
+            world_name = world_arg.to_string();
+        }
+        if let Some(server_name_arg) = arg.strip_prefix("--server-name=") {
+            server_name = server_name_arg.to_string();
+        }
+        if let Some(address_arg) = arg.strip_prefix("--rcon-address=") {
+            remote_console_address = address_arg.to_string();
+        }
+        if let Some(password_arg) = arg.strip_prefix("--rcon-password=") {
+            remote_console_password = Some(password_arg.to_string());
+        }
+        if let Some(record_arg) = arg.strip_prefix("--record=") {
+            record_path = Some(record_arg.to_string());
+        }
+        if let Some(replay_arg) = arg.strip_prefix("--replay=") {
+            run_replay(replay_arg);
+            return;
+        }
+    }
+
+    if args.contains(&"--server".to_string()) {
+        let remote_console = remote_console_password.map(|password| (remote_console_address, password));
+        run_dedicated_server(port, &world_name, &server_name, remote_console, &record_path);
+        return;
+    }
+
+    if connect_address.as_deref() == Some("lan") {
+        match find_lan_server() {
+            Some(address) => connect_address = Some(address),
+            None => {
+                println!("No LAN servers found.");
+                return;
+            },
+        }
     }
 
     if max_fps != WindowBuilder::NO_MAX_FPS { println!("Max fps set to: {}.", max_fps) }
@@ -130,10 +240,13 @@ fn main() {
     }
 
     let mut terrain_shader = TerrainShader::create();
+    let mut entity_shader = EntityShader::create();
     let mut line_shader = LineShader::create();
 
     let mut line_debug = LineDebug::new();
+    let mut hud_debug = LineDebug::new();
     let terrain_atlas = TerrainAtlas::new();
+    let player_renderer = PlayerRenderer::new();
 
     let mut block_registry = BlockRegistry::create();
     block_registry.register(Block::all(1)); // BlockTypes::Dirt
@@ -143,18 +256,99 @@ fn main() {
     block_registry.register(Block::all(6)); // BlockTypes::Bedrock
     block_registry.register(Block::all(7)); // BlockTypes::Gravel
     block_registry.register(Block::all(8)); // BlockTypes::Sand
-
-    let mut server_world = ServerWorld::create(WorldGen::random_seed());
-    let mut client_world = ClientWorld::create();
+    // BlockTypes::Water and BlockTypes::Lava, each followed by their flow levels.
+    for texture in [9, 10] {
+        for _ in 0..=Liquid::FALLING_LEVEL {
+            block_registry.register(Block::all(texture));
+        }
+    }
+    // The signal blocks, every state of them. Directional ones mark the face their power comes out of.
+    for block in BlockTypes::Lever as u8..BlockTypes::Log as u8 {
+        block_registry.register(match SignalBlock::from_block(block) {
+            Some(SignalBlock::Lever { is_on }) => Block::all(11 + is_on as usize),
+            Some(SignalBlock::Button { is_pressed }) => Block::all(13 + is_pressed as usize),
+            Some(SignalBlock::Wire { power }) => Block::all(15 + (power > 0) as usize),
+            Some(SignalBlock::Lamp { is_lit }) => Block::all(17 + is_lit as usize),
+            Some(SignalBlock::Door { is_open, .. }) => Block::all(19 + is_open as usize),
+            Some(SignalBlock::Repeater { facing, is_powered, .. }) => Block::facing(21 + is_powered as usize, 23 + is_powered as usize, facing),
+            Some(SignalBlock::Inverter { facing, is_on }) => Block::facing(25 + is_on as usize, 27 + is_on as usize, facing),
+            None => Block::all(0),
+        });
+    }
+    block_registry.register(Block::side(29, 30, 30)); // BlockTypes::Log
+    block_registry.register(Block::all(31)); // BlockTypes::Leaves
+    block_registry.register(Block::all(32)); // BlockTypes::Planks
+    // BlockTypes::Fire and its ages.
+    for _ in 0..=BlockTypes::MAX_FIRE_AGE {
+        block_registry.register(Block::all(33));
+    }
+    block_registry.register(Block::side(34, 35, 35)); // BlockTypes::Chest
+    block_registry.register(Block::all(36)); // BlockTypes::Sign
+    // BlockTypes::Farmland, dry and then moist.
+    block_registry.register(Block::side(1, 37, 1));
+    block_registry.register(Block::side(1, 38, 1));
+    // BlockTypes::Wheat and its ages, two ages share a stage of the texture.
+    for age in 0..=BlockTypes::MAX_WHEAT_AGE {
+        block_registry.register(Block::all(39 + age as usize / 2));
+    }
+    let entity_renderer = EntityRenderer::new(&block_registry);
 
     const VIEW_DISTANCE: usize = 24;
 
-    const SKY_COLOR: Vector3<f32> = Vector3::new(0.08, 0.47, 0.8);
-    const SUN_COLOR: Vector3<f32> = Vector3::new(1.23, 1.18, 1.1);
+    // Without an address to connect to we host the world ourselves on a separate thread, and other players can join it.
+    let mut integrated_server: Option<IntegratedServer> = None;
+    let mut server_address: Option<SocketAddr> = None;
+    let mut connection = match connect_address {
+        Some(address) => {
+            server_address = address.to_socket_addrs().ok().and_then(|mut addresses| addresses.next());
+            match ServerConnection::connect(address.as_str()) {
+                Ok(connection) => connection,
+                Err(error) => panic!("Failed to connect to: {}. Error: {}.", address, error),
+            }
+        },
+        None => {
+            let mut server = GameServer::create(open_world(&world_name));
+            start_recording(&mut server, &record_path);
+
+            match server.bind(("0.0.0.0", port)).or_else(|_| server.bind(("0.0.0.0", 0))) {
+                Ok(address) => {
+                    println!("Hosting world on port: {}.", address.port());
+                    server_address = Some(SocketAddr::new(Ipv4Addr::LOCALHOST.into(), address.port()));
+                    if let Err(error) = server.announce_on_lan(format!("{}'s world", name)) {
+                        println!("Failed to announce the world on LAN. Error: {}.", error);
+                    }
+                },
+                Err(error) => println!("Failed to open the world to other players. Error: {}.", error),
+            }
+
+            let (server, local_connection) = IntegratedServer::start(server);
+            integrated_server = Some(server);
+            ServerConnection::create(Box::new(local_connection))
+        },
+    };
+
+    let mut player_id = None;
+    let mut client_world = ClientWorld::create();
+    let mut chat = Chat::new();
+    let mut hotbar = Hotbar::new();
+    // Other worlds showing up on the network are posted to the chat, the one we are playing on is skipped.
+    let mut lan_discovery = LanDiscovery::listen().ok();
+
+    let mut sky = Sky::create();
+    let mut precipitation = Precipitation::new();
 
     let mut camera = Camera::create();
     // camera.position += Vector3::new(0.0, 0.0, 30000000.0);
-    unsafe { gl::ClearColor(SKY_COLOR.x, SKY_COLOR.y, SKY_COLOR.z, 1.0); }
+    let mut body = PlayerBody::create(&camera.position);
+    // The server decides the game mode. Survival players walk, spectators fly through blocks
+    // and in creative F switches between the two.
+    let mut game_mode = GameMode::Survival;
+    let mut is_flying = false;
+    let mut health = MAX_HEALTH;
+    let mut inventory = Inventory::create(INVENTORY_SLOTS);
+    let mut block_breaking = BlockBreaking::create();
+    let mut predictions = BlockPredictions::create();
+    let mut last_move_sent = 0.0f32;
 
     let mut timer = Timer::create();
     
@@ -176,22 +370,189 @@ fn main() {
             fps_counter = 0;
         }
 
-        if window.is_key_just_pressed(glfw::Key::Escape) {
-            window.toggle_mouse();
+        // While the chat is open the keyboard belongs to it.
+        let was_chat_open = chat.is_open();
+        if player_id.is_some() {
+            if let Some(packet) = chat.update(&window) {
+                connection.send(packet);
+            }
         }
-        if window.is_key_just_pressed(glfw::Key::R) {
-            terrain_shader = TerrainShader::create();
-            line_shader = LineShader::create();
+
+        if !was_chat_open && !chat.is_open() {
+            if window.is_key_just_pressed(glfw::Key::Escape) {
+                window.toggle_mouse();
+            }
+            hotbar.update(&window);
+            if game_mode == GameMode::Creative && window.is_key_just_pressed(glfw::Key::F) {
+                is_flying = !is_flying;
+                body.set_eye_position(&camera.position);
+            }
+            if window.is_key_just_pressed(glfw::Key::R) {
+                terrain_shader = TerrainShader::create();
+                entity_shader = EntityShader::create();
+                line_shader = LineShader::create();
+            }
         }
 
-        if window.is_mouse_grabbed() {
-            camera.fly(&window, &timer);
+        while let Some(packet) = connection.receive() {
+            match packet {
+                ServerPacket::Handshake { protocol_version, registry_hash, compression_threshold } => {
+                    match connection.accept_handshake(protocol_version, registry_hash, compression_threshold) {
+                        Ok(()) => connection.send(ClientPacket::Login { name: name.clone(), view_distance: VIEW_DISTANCE as u8 }),
+                        Err(reason) => {
+                            println!("Can not join the server: {}.", reason);
+                            window.close();
+                        },
+                    }
+                },
+                ServerPacket::LoginSuccess { player_id: id, position } => {
+                    player_id = Some(id);
+                    body.set_eye_position(&position);
+                    camera.position = position;
+                },
+                ServerPacket::Disconnect { reason } => {
+                    println!("Disconnected: {}.", reason);
+                    window.close();
+                },
+                ServerPacket::ChunkData { position, blocks, block_entities } => {
+                    match Chunk::from_blocks(&blocks) {
+                        Some(mut chunk) => {
+                            for (local, entity) in block_entities {
+                                chunk.set_block_entity(&local, entity);
+                            }
+                            client_world.insert_chunk(position.clone(), chunk);
+                            predictions.reapply(&mut client_world, &position, None);
+                        },
+                        None => println!("Received a chunk with invalid size at: ({}, {}).", position.x, position.z),
+                    }
+                },
+                ServerPacket::UnloadChunk { position } => {
+                    client_world.remove_chunk(&position);
+                },
+                ServerPacket::MultiBlockChange { position, section, mut changes } => {
+                    predictions.filter_changes(&position, section as usize, &mut changes);
+                    if !changes.is_empty() && !client_world.apply_block_changes(&position, section as usize, &changes) {
+                        println!("Received block changes for a missing chunk section at: ({}, {}) #{}.", position.x, position.z, section);
+                    }
+                },
+                ServerPacket::SectionData { position, section, blocks } => {
+                    if client_world.set_section(&position, section as usize, &blocks) {
+                        predictions.reapply(&mut client_world, &position, Some(section as usize));
+                    } else {
+                        println!("Received an invalid chunk section at: ({}, {}) #{}.", position.x, position.z, section);
+                    }
+                },
+                ServerPacket::BlockEditRejected { sequence, position, block, reason } => {
+                    println!("Block edit #{} at ({}, {}, {}) rejected: {}.", sequence, position.x, position.y, position.z, reason.get_reason());
+                    predictions.reject(&mut client_world, sequence, &position, block);
+                },
+                ServerPacket::BlockEditAcknowledged { sequence } => {
+                    predictions.acknowledge(&mut client_world, sequence);
+                },
+                ServerPacket::SpawnEntity { entity_id, kind, position } => {
+                    client_world.add_entity(entity_id, RemoteEntity::create(kind, position));
+                },
+                ServerPacket::EntityMoved { entity_id, position } => {
+                    if let Some(entity) = client_world.get_entity_mut(entity_id) {
+                        entity.set_target(position);
+                    }
+                },
+                ServerPacket::RemoveEntity { entity_id } => {
+                    client_world.remove_entity(entity_id);
+                },
+                ServerPacket::TimeUpdate { time } => {
+                    sky.set_time(time);
+                },
+                ServerPacket::WeatherChanged { weather } => {
+                    sky.set_weather(weather);
+                },
+                ServerPacket::BlockEntityData { position, entity } => {
+                    if !client_world.set_block_entity(&position, entity) {
+                        println!("Received a block entity that does not fit the block at: ({}, {}, {}).", position.x, position.y, position.z);
+                    }
+                },
+                ServerPacket::PlayerJoined { player_id: id, name, position, yaw, pitch } => {
+                    println!("{} joined the game.", name);
+                    client_world.add_player(id, RemotePlayer::create(name, position, yaw, pitch));
+                },
+                ServerPacket::PlayerLeft { player_id: id } => {
+                    client_world.remove_player(id);
+                },
+                ServerPacket::PlayerMoved { player_id: id, position, yaw, pitch } => {
+                    if let Some(player) = client_world.get_player_mut(id) {
+                        player.set_target(position, yaw, pitch);
+                    }
+                },
+                ServerPacket::ChatMessage { message } => {
+                    println!("{}", message);
+                    chat.add_message(message, timer.get_time());
+                },
+                ServerPacket::TabCompletions { completions } => {
+                    chat.set_completions(completions);
+                },
+                ServerPacket::Teleport { position } => {
+                    body.set_eye_position(&position);
+                    camera.position = position;
+                },
+                ServerPacket::GameModeChanged { mode } => {
+                    game_mode = mode;
+                    is_flying = mode != GameMode::Survival;
+                    body.set_eye_position(&camera.position);
+                },
+                ServerPacket::HealthChanged { health: new_health } => {
+                    health = new_health;
+                },
+                ServerPacket::InventoryChanged { inventory: new_inventory } => {
+                    inventory = new_inventory;
+                },
+            }
+        }
+        if !connection.is_open() {
+            println!("Lost connection to the server.");
+            window.close();
+        }
+
+        if let Some(discovery) = &mut lan_discovery {
+            let new_servers = discovery.update();
+            let servers = discovery.get_servers();
+            for server in servers[servers.len() - new_servers..].iter().filter(|server| !server_address.is_some_and(|address| server.is_at(&address))) {
+                let message = format!("LAN world found: {}", server.get_description());
+                println!("{}.", message);
+                chat.add_message(message, timer.get_time());
+            }
+        }
+
+        if window.is_mouse_grabbed() && player_id.is_some() && !chat.is_open() {
+            if is_flying {
+                camera.fly(&window, &timer);
+            } else if client_world.get_height(body.position.get_block_x(), body.position.get_block_z()).is_some() {
+                // Until the chunk below has arrived there is nothing to stand on.
+                camera.walk(&window, &timer, &mut body, &client_world);
+            }
         }
         camera.update(90.0, window.get_aspect(), 0.01, 1500.0);
 
+        // Movement is sent once per server tick, more often would only depend on the frame rate.
+        if player_id.is_some() && timer.get_time() - last_move_sent >= 1.0 / GameServer::TICKS_PER_SECOND as f32 {
+            last_move_sent = timer.get_time();
+            connection.send(ClientPacket::PlayerMove {
+                position: camera.position.clone(),
+                yaw: camera.rotation.y,
+                pitch: camera.rotation.x,
+                is_on_ground: !is_flying && body.is_on_ground(),
+            });
+        }
+
         // TODO: Add world serialization/deserialization
-        const MAX_REACH_DISTANCE: f32 = 5.0;
-        if let Some(hit_info) = raycast(&server_world, &camera.position, camera.get_front(), MAX_REACH_DISTANCE) {
+        // Spectators only watch, they don't target blocks.
+        let hit = if game_mode.can_interact() { raycast(&client_world, &camera.position, camera.get_front(), MAX_REACH_DISTANCE) } else { None };
+        // In survival the hotbar picks from the inventory, otherwise every item is at hand.
+        let hotbar_inventory = (game_mode == GameMode::Survival).then_some(&inventory);
+        // Survival players have to mine blocks, creative ones break them with a click.
+        let is_mining = game_mode == GameMode::Survival && !chat.is_open() && window.is_mouse_button_pressed(glfw::MouseButton::Left);
+        let mined = block_breaking.update(&mut connection, &client_world, hit.as_ref().map(|hit_info| hit_info.get_block_pos()), is_mining, timer.get_delta());
+        block_breaking.draw(&client_world, &mut line_debug);
+        if let Some(hit_info) = &hit {
             // Draw outline
 
             // let time = timer.get_time() * f32::consts::PI;
@@ -207,30 +568,56 @@ fn main() {
             );
 
             // Break / Place blocks
-            if window.is_mouse_button_just_pressed(glfw::MouseButton::Left) {
-                server_world.set_block(&BlockPos::new(hit_info.position.x, hit_info.position.y, hit_info.position.z), BlockTypes::Air as u8);
+            let clicked = game_mode == GameMode::Creative && window.is_mouse_button_just_pressed(glfw::MouseButton::Left) && !chat.is_open();
+            if let Some(position) = mined.or_else(|| clicked.then(|| hit_info.get_block_pos())) {
+                connection.send(ClientPacket::BreakBlock {
+                    sequence: predictions.predict(&mut client_world, position.clone(), BlockTypes::Air as u8),
+                    position,
+                });
             }
-            if window.is_mouse_button_just_pressed(glfw::MouseButton::Right) {
-                if let Some(mut hit_info) = raycast(&server_world, &camera.position, camera.get_front(), MAX_REACH_DISTANCE) {
-                    if !hit_info.normal.is_zero() {
-                        hit_info.position += hit_info.normal;
-                        server_world.set_block(&BlockPos::new(hit_info.position.x, hit_info.position.y, hit_info.position.z), BlockTypes::Cobblestone as u8);
+            if window.is_mouse_button_just_pressed(glfw::MouseButton::Right) && !chat.is_open() {
+                let position = hit_info.get_block_pos();
+                if SignalBlock::from_block(client_world.get_block(&position)).is_some_and(|signal| signal.is_usable()) {
+                    connection.send(ClientPacket::UseBlock { position });
+                } else if let (Some(face), Some(item)) = (Direction::from_normal(&hit_info.normal), hotbar.get_selected(hotbar_inventory)) {
+                    match ItemTypes::get_block(item) {
+                        Some(block) => {
+                            let front = camera.get_front();
+                            let block = SignalBlock::get_placed_block(block, Direction::from_horizontal(front.x, front.z));
+                            connection.send(ClientPacket::PlaceBlock {
+                                sequence: predictions.predict(&mut client_world, position.offset(face), block),
+                                position,
+                                face,
+                                block,
+                            });
+                        },
+                        None => connection.send(ClientPacket::UseItem { position, face, item }),
                     }
-                };
+                }
             }
         };
+        connection.flush();
 
-        server_world.load_region(&ChunkPos::new(camera.position.get_chunk_x(), camera.position.get_chunk_z()), VIEW_DISTANCE / 2);
-        client_world.update(&block_registry, &camera.position, VIEW_DISTANCE / 2, &mut server_world);
+        client_world.update(&block_registry, &camera.position, VIEW_DISTANCE / 2, timer.get_delta());
+        player_renderer.draw_name_tags(client_world.get_all_players(), &camera, &mut line_debug);
+        BlockEntityRenderer::draw(&client_world, &camera, hit.map(|hit_info| hit_info.get_block_pos()).as_ref(), &mut line_debug);
+
+        sky.update(timer.get_delta());
+        precipitation.update(&client_world, &camera.position, sky.get_rain_strength(), timer.get_delta());
+        precipitation.draw(&mut line_debug);
+        let sun_direction = sky.get_sun_direction();
+        let sky_color = sky.get_sky_color();
+        let sun_color = sky.get_sun_color();
 
         unsafe {
+            gl::ClearColor(sky_color.x, sky_color.y, sky_color.z, 1.0);
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         }
         
         terrain_shader.bind();
-        terrain_shader.set_sun_direction(&Vector3::new(-0.3, -1.0, 0.6).normalize());
-        terrain_shader.set_sky_color(&SKY_COLOR);
-        terrain_shader.set_sun_color(&SUN_COLOR);
+        terrain_shader.set_sun_direction(&sun_direction);
+        terrain_shader.set_sky_color(&sky_color);
+        terrain_shader.set_sun_color(&sun_color);
 
         terrain_atlas.texture().bind(TerrainShader::COLOR_TEXTURE_SLOT);
 
@@ -244,13 +631,48 @@ fn main() {
 
         Texture::unbind();
 
+        entity_shader.bind();
+        entity_shader.set_sun_direction(&sun_direction);
+        entity_shader.set_sky_color(&sky_color);
+        entity_shader.set_sun_color(&sun_color);
+
+        player_renderer.render(client_world.get_all_players(), &camera, &entity_shader);
+        terrain_atlas.texture().bind(EntityShader::COLOR_TEXTURE_SLOT);
+        entity_renderer.render(client_world.get_all_entities(), &camera, &entity_shader, timer.get_time());
+        Texture::unbind();
+
         line_shader.bind();
         line_shader.set_project_view_matrix(camera.get_project_view_matrix());
         line_shader.set_render_offset(&ChunkPos::new(camera.position.get_chunk_x(), camera.position.get_chunk_z()));
 
         line_debug.render_all();
+
+        chat.draw(&mut hud_debug, timer.get_time());
+        if game_mode.can_interact() {
+            hotbar.draw(&mut hud_debug, window.get_width() as f32, hotbar_inventory);
+        }
+        if game_mode == GameMode::Survival {
+            Hotbar::draw_health(&mut hud_debug, window.get_width() as f32, health);
+        }
+        line_shader.set_project_view_matrix(&cgmath::ortho(0.0, window.get_width() as f32, 0.0, window.get_height() as f32, -1.0, 1.0));
+        line_shader.set_render_offset(&ChunkPos::new(0, 0));
+
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::LineWidth(2.0);
+        }
+        hud_debug.render_all();
+        unsafe {
+            gl::LineWidth(5.0);
+            gl::Enable(gl::DEPTH_TEST);
+        }
+
         Shader::unbind();
 
         window.swap_buffers();
     }
+
+    if let Some(server) = &mut integrated_server {
+        server.stop();
+    }
 }
\ No newline at end of file